@@ -82,18 +82,19 @@
 //! [mm]: https://github.com/rust-lang/measureme/
 
 use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::error::Error;
 use std::fmt::Display;
 use std::intrinsics::unlikely;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::{fs, process};
 
 pub use measureme::EventId;
 use measureme::{EventIdBuilder, Profiler, SerializableString, StringId};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use smallvec::SmallVec;
 use tracing::warn;
 
@@ -114,6 +115,7 @@ struct EventFilter: u16 {
         const LLVM                = 1 << 7;
         const INCR_RESULT_HASHING = 1 << 8;
         const ARTIFACT_SIZES = 1 << 9;
+        const FUNCTION_TIMES = 1 << 10;
 
         const DEFAULT = Self::GENERIC_ACTIVITIES.bits() |
                         Self::QUERY_PROVIDERS.bits() |
@@ -142,6 +144,13 @@ struct EventFilter: u16 {
     ("llvm", EventFilter::LLVM),
     ("incr-result-hashing", EventFilter::INCR_RESULT_HASHING),
     ("artifact-sizes", EventFilter::ARTIFACT_SIZES),
+    // The time attributed to functions is taken from query providers and generic activities.
+    (
+        "function-times",
+        EventFilter::FUNCTION_TIMES
+            .union(EventFilter::QUERY_PROVIDERS)
+            .union(EventFilter::GENERIC_ACTIVITIES),
+    ),
 ];
 
 /// Something that uniquely identifies a query invocation.
@@ -275,6 +284,10 @@ pub fn generic_activity_with_arg<A>(
         A: Borrow<str> + Into<String>,
     {
         self.exec(EventFilter::GENERIC_ACTIVITIES, |profiler| {
+            let function_time_key = profiler
+                .function_times
+                .as_ref()
+                .map(|_| FunctionTimeKey::Activity(event_label, event_arg.borrow().to_owned()));
             let builder = EventIdBuilder::new(&profiler.profiler);
             let event_label = profiler.get_or_alloc_cached_string(event_label);
             let event_id = if profiler.event_filter_mask.contains(EventFilter::FUNCTION_ARGS) {
@@ -284,6 +297,7 @@ pub fn generic_activity_with_arg<A>(
                 builder.from_label(event_label)
             };
             TimingGuard::start(profiler, profiler.generic_activity_event_kind, event_id)
+                .with_function_time(profiler, function_time_key)
         })
     }
 
@@ -293,7 +307,8 @@ pub fn generic_activity_with_arg<A>(
     /// If the arguments to a generic activity are cheap to create, use `generic_activity_with_arg`
     /// or `generic_activity_with_args` for their simpler API. However, if they are costly or
     /// require allocation in sufficiently hot contexts, then this allows for a closure to be called
-    /// only when arguments were asked to be recorded via `-Z self-profile-events=args`.
+    /// only when arguments were asked to be recorded via `-Z self-profile-events=args`, or are
+    /// needed to attribute the time of the activity via `-Z self-profile-events=function-times`.
     ///
     /// In this case, the closure will be passed a `&mut EventArgRecorder`, to help with recording
     /// one or many arguments within the generic activity being profiled, by calling its
@@ -319,14 +334,19 @@ pub fn generic_activity_with_arg_recorder<F>(
         // Ensure this event will only be recorded when self-profiling is turned on.
         self.exec(EventFilter::GENERIC_ACTIVITIES, |profiler| {
             let builder = EventIdBuilder::new(&profiler.profiler);
+            let function_time_label = event_label;
             let event_label = profiler.get_or_alloc_cached_string(event_label);
+            let mut function_time_key = None;
 
             // Ensure the closure to create event arguments will only be called when argument
-            // recording is turned on.
-            let event_id = if profiler.event_filter_mask.contains(EventFilter::FUNCTION_ARGS) {
+            // recording is turned on, or when the arguments are needed to attribute the time
+            // of the activity.
+            let record_args = profiler.event_filter_mask.contains(EventFilter::FUNCTION_ARGS);
+            let event_id = if record_args || profiler.function_times.is_some() {
                 // Set up the builder and call the user-provided closure to record potentially
                 // costly event arguments.
-                let mut recorder = EventArgRecorder { profiler, args: SmallVec::new() };
+                let mut recorder =
+                    EventArgRecorder { profiler, args: SmallVec::new(), first_arg: None };
                 f(&mut recorder);
 
                 // It is expected that the closure will record at least one argument. If that
@@ -339,11 +359,21 @@ pub fn generic_activity_with_arg_recorder<F>(
                     );
                 }
 
-                builder.from_label_and_args(event_label, &recorder.args)
+                if let Some(first_arg) = recorder.first_arg {
+                    function_time_key =
+                        Some(FunctionTimeKey::Activity(function_time_label, first_arg));
+                }
+
+                if record_args {
+                    builder.from_label_and_args(event_label, &recorder.args)
+                } else {
+                    builder.from_label(event_label)
+                }
             } else {
                 builder.from_label(event_label)
             };
             TimingGuard::start(profiler, profiler.generic_activity_event_kind, event_id)
+                .with_function_time(profiler, function_time_key)
         })
     }
 
@@ -401,7 +431,10 @@ pub fn generic_activity_with_args(
     #[inline(always)]
     pub fn query_provider(&self) -> TimingGuard<'_> {
         self.exec(EventFilter::QUERY_PROVIDERS, |profiler| {
+            // The invocation the time is attributed to is only known once the query finished,
+            // see `TimingGuard::finish_with_query_invocation_id`.
             TimingGuard::start(profiler, profiler.query_event_kind, EventId::INVALID)
+                .with_function_time(profiler, None)
         })
     }
 
@@ -515,6 +548,10 @@ pub struct EventArgRecorder<'p> {
     /// The most common case, when actually recording event arguments, is to have one argument. Then
     /// followed by recording two, in a couple places.
     args: SmallVec<[StringId; 2]>,
+
+    /// The first recorded argument, which identifies the activity when attributing its time
+    /// for `-Z self-profile-events=function-times`.
+    first_arg: Option<String>,
 }
 
 impl EventArgRecorder<'_> {
@@ -526,6 +563,9 @@ pub fn record_arg<A>(&mut self, event_arg: A)
     where
         A: Borrow<str> + Into<String>,
     {
+        if self.first_arg.is_none() && self.profiler.function_times.is_some() {
+            self.first_arg = Some(event_arg.borrow().to_owned());
+        }
         let event_arg = self.profiler.get_or_alloc_cached_string(event_arg);
         self.args.push(event_arg);
     }
@@ -544,6 +584,9 @@ pub struct SelfProfiler {
     query_blocked_event_kind: StringId,
     query_cache_hit_event_kind: StringId,
     artifact_size_event_kind: StringId,
+
+    /// Only present if `-Z self-profile-events=function-times` was passed.
+    function_times: Option<FunctionTimes>,
 }
 
 impl SelfProfiler {
@@ -607,6 +650,11 @@ pub fn new(
             event_filter_mask = EventFilter::DEFAULT;
         }
 
+        let function_times = event_filter_mask.contains(EventFilter::FUNCTION_TIMES).then(|| {
+            let filename = format!("{crate_name}-{pid:07}.function_times.json");
+            FunctionTimes::new(output_directory.join(filename))
+        });
+
         Ok(SelfProfiler {
             profiler,
             event_filter_mask,
@@ -618,6 +666,7 @@ pub fn new(
             query_blocked_event_kind,
             query_cache_hit_event_kind,
             artifact_size_event_kind,
+            function_times,
         })
     }
 
@@ -676,10 +725,15 @@ pub fn query_key_recording_enabled(&self) -> bool {
     pub fn event_id_builder(&self) -> EventIdBuilder<'_> {
         EventIdBuilder::new(&self.profiler)
     }
+
+    /// Returns the times recorded for `-Z self-profile-events=function-times`, if enabled.
+    pub fn function_times(&self) -> Option<&FunctionTimes> {
+        self.function_times.as_ref()
+    }
 }
 
 #[must_use]
-pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>);
+pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>, Option<FunctionTimer<'a>>);
 
 impl<'a> TimingGuard<'a> {
     #[inline]
@@ -692,23 +746,40 @@ pub fn start(
         let raw_profiler = &profiler.profiler;
         let timing_guard =
             raw_profiler.start_recording_interval_event(event_kind, event_id, thread_id);
-        TimingGuard(Some(timing_guard))
+        TimingGuard(Some(timing_guard), None)
+    }
+
+    /// Also measures the self time of the event if `-Z self-profile-events=function-times`
+    /// is enabled, which is attributed to `key` once the guard is dropped.
+    fn with_function_time(
+        mut self,
+        profiler: &'a SelfProfiler,
+        key: Option<FunctionTimeKey>,
+    ) -> TimingGuard<'a> {
+        if let Some(function_times) = &profiler.function_times {
+            self.1 = Some(FunctionTimer::start(function_times, key));
+        }
+        self
     }
 
     #[inline]
     pub fn finish_with_query_invocation_id(self, query_invocation_id: QueryInvocationId) {
-        if let Some(guard) = self.0 {
+        let TimingGuard(guard, function_timer) = self;
+        if let Some(guard) = guard {
             outline(|| {
                 let event_id = StringId::new_virtual(query_invocation_id.0);
                 let event_id = EventId::from_virtual(event_id);
                 guard.finish_with_override_event_id(event_id);
+                if let Some(mut function_timer) = function_timer {
+                    function_timer.key = Some(FunctionTimeKey::Query(query_invocation_id.0));
+                }
             });
         }
     }
 
     #[inline]
     pub fn none() -> TimingGuard<'a> {
-        TimingGuard(None)
+        TimingGuard(None, None)
     }
 
     #[inline(always)]
@@ -718,6 +789,163 @@ pub fn run<R>(self, f: impl FnOnce() -> R) -> R {
     }
 }
 
+/// What compiling a function spent its time on, see `FunctionTimes`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FunctionTimeCategory {
+    Typeck,
+    Borrowck,
+    MirOpt,
+    Mono,
+    /// Generating and optimizing the code of a codegen unit. This time is only known for the
+    /// codegen unit as a whole, so it is split between its items by their size estimate.
+    Codegen,
+}
+
+/// Time spent compiling a function, or one instance of it.
+#[derive(Clone, Debug)]
+pub struct FunctionTime {
+    /// The def-path of the function.
+    pub def_path: String,
+    /// The instance, if the time was spent on one instance of the function.
+    pub instance: Option<String>,
+    pub category: FunctionTimeCategory,
+    pub self_time: Duration,
+}
+
+/// An item of a codegen unit, see `FunctionTimes::record_codegen_unit`.
+#[derive(Clone, Debug)]
+pub struct CodegenUnitItem {
+    pub def_path: String,
+    pub instance: String,
+    pub size_estimate: usize,
+}
+
+/// The self times recorded for `-Z self-profile-events=function-times`.
+///
+/// While compiling, the self time, i.e. the time which was not spent in a nested query or
+/// activity, is recorded for each query invocation and for each generic activity with
+/// arguments. Once the queries are done, the query engine attributes the times of the queries
+/// which compile individual functions to those functions with `record`, and the codegen units
+/// are described with `record_codegen_unit`. `attributed_times` then also attributes the time of
+/// the activities working on a codegen unit to the items of the codegen unit.
+pub struct FunctionTimes {
+    report_path: PathBuf,
+    /// The self time of each query invocation, keyed by its `QueryInvocationId`.
+    queries: Mutex<FxHashMap<u32, Duration>>,
+    /// The self time of generic activities, keyed by their label and first argument.
+    activities: Mutex<FxHashMap<(&'static str, String), Duration>>,
+    functions: Mutex<Vec<FunctionTime>>,
+    codegen_units: Mutex<FxHashMap<String, Vec<CodegenUnitItem>>>,
+}
+
+impl FunctionTimes {
+    fn new(report_path: PathBuf) -> FunctionTimes {
+        FunctionTimes {
+            report_path,
+            queries: Default::default(),
+            activities: Default::default(),
+            functions: Default::default(),
+            codegen_units: Default::default(),
+        }
+    }
+
+    /// The path the report of the attributed times should be written to.
+    pub fn report_path(&self) -> &Path {
+        &self.report_path
+    }
+
+    /// Returns the self time of a query invocation, if it was executed.
+    pub fn query_self_time(&self, query_invocation_id: QueryInvocationId) -> Option<Duration> {
+        self.queries.lock().get(&query_invocation_id.0).copied()
+    }
+
+    pub fn record(&self, function_time: FunctionTime) {
+        self.functions.lock().push(function_time);
+    }
+
+    /// Records the items of a codegen unit, so that the time of the activities whose first
+    /// argument is the name of the codegen unit can be attributed to them.
+    pub fn record_codegen_unit(&self, name: String, items: Vec<CodegenUnitItem>) {
+        self.codegen_units.lock().insert(name, items);
+    }
+
+    /// Returns all times attributed to functions so far.
+    pub fn attributed_times(&self) -> Vec<FunctionTime> {
+        let mut times = self.functions.lock().clone();
+        let codegen_units = self.codegen_units.lock();
+        for ((_, arg), &self_time) in self.activities.lock().iter() {
+            let Some(items) = codegen_units.get(arg) else { continue };
+            let total_size: usize = items.iter().map(|item| item.size_estimate).sum();
+            if total_size == 0 {
+                continue;
+            }
+            for item in items {
+                let share = item.size_estimate as f64 / total_size as f64;
+                times.push(FunctionTime {
+                    def_path: item.def_path.clone(),
+                    instance: Some(item.instance.clone()),
+                    category: FunctionTimeCategory::Codegen,
+                    self_time: self_time.mul_f64(share),
+                });
+            }
+        }
+        times
+    }
+
+    fn add(&self, key: FunctionTimeKey, self_time: Duration) {
+        match key {
+            FunctionTimeKey::Query(id) => {
+                *self.queries.lock().entry(id).or_default() += self_time;
+            }
+            FunctionTimeKey::Activity(label, arg) => {
+                *self.activities.lock().entry((label, arg)).or_default() += self_time;
+            }
+        }
+    }
+}
+
+enum FunctionTimeKey {
+    Query(u32),
+    Activity(&'static str, String),
+}
+
+thread_local! {
+    /// For each `FunctionTimer` running on this thread, the time spent in the timers nested in
+    /// it so far, from the outermost to the innermost timer.
+    static NESTED_FUNCTION_TIMES: RefCell<Vec<Duration>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Measures the self time of an event for `FunctionTimes`.
+struct FunctionTimer<'a> {
+    function_times: &'a FunctionTimes,
+    /// The time isn't recorded if this isn't known by the time the timer is dropped.
+    key: Option<FunctionTimeKey>,
+    start: Instant,
+}
+
+impl<'a> FunctionTimer<'a> {
+    fn start(function_times: &'a FunctionTimes, key: Option<FunctionTimeKey>) -> Self {
+        NESTED_FUNCTION_TIMES.with_borrow_mut(|nested| nested.push(Duration::ZERO));
+        FunctionTimer { function_times, key, start: Instant::now() }
+    }
+}
+
+impl Drop for FunctionTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let nested = NESTED_FUNCTION_TIMES.with_borrow_mut(|nested| {
+            let time = nested.pop().unwrap_or_default();
+            if let Some(parent) = nested.last_mut() {
+                *parent += elapsed;
+            }
+            time
+        });
+        if let Some(key) = self.key.take() {
+            self.function_times.add(key, elapsed.saturating_sub(nested));
+        }
+    }
+}
+
 struct VerboseInfo {
     start_time: Instant,
     start_rss: Option<usize>,
@@ -1,4 +1,10 @@
-use super::JsonTimePassesEntry;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{
+    CodegenUnitItem, FunctionTimeCategory, FunctionTimeKey, FunctionTimer, FunctionTimes,
+    JsonTimePassesEntry, QueryInvocationId,
+};
 
 #[test]
 fn with_rss() {
@@ -17,3 +23,54 @@ fn no_rss() {
         r#"{"pass":"typeck","time":56.1,"rss_start":null,"rss_end":null}"#
     )
 }
+
+#[test]
+fn function_times_self_time() {
+    let function_times = FunctionTimes::new(PathBuf::new());
+    {
+        let _outer = FunctionTimer::start(&function_times, Some(FunctionTimeKey::Query(0)));
+        let _inner = FunctionTimer::start(&function_times, Some(FunctionTimeKey::Query(1)));
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let outer = function_times.query_self_time(QueryInvocationId(0)).unwrap();
+    let inner = function_times.query_self_time(QueryInvocationId(1)).unwrap();
+    assert!(inner >= Duration::from_millis(20));
+    assert!(outer < inner);
+}
+
+#[test]
+fn function_times_codegen_unit() {
+    let function_times = FunctionTimes::new(PathBuf::new());
+    function_times.add(
+        FunctionTimeKey::Activity("LLVM_module_optimize", "cgu".to_owned()),
+        Duration::from_secs(4),
+    );
+    function_times.add(
+        FunctionTimeKey::Activity("LLVM_module_optimize", "other".to_owned()),
+        Duration::from_secs(1),
+    );
+    function_times.record_codegen_unit(
+        "cgu".to_owned(),
+        vec![
+            CodegenUnitItem {
+                def_path: "a".to_owned(),
+                instance: "a::<u8>".to_owned(),
+                size_estimate: 3,
+            },
+            CodegenUnitItem {
+                def_path: "b".to_owned(),
+                instance: "b".to_owned(),
+                size_estimate: 1,
+            },
+        ],
+    );
+
+    let mut times = function_times.attributed_times();
+    times.sort_by(|a, b| a.def_path.cmp(&b.def_path));
+    assert_eq!(times.len(), 2);
+    assert_eq!(times[0].instance.as_deref(), Some("a::<u8>"));
+    assert_eq!(times[0].category, FunctionTimeCategory::Codegen);
+    assert_eq!(times[0].self_time, Duration::from_secs(3));
+    assert_eq!(times[1].self_time, Duration::from_secs(1));
+}
@@ -0,0 +1,729 @@
+//! The `-Z api-diff` mode, which compares the public API of two versions of a crate.
+//!
+//! Both versions are loaded like any other dependency, so everything compared here is decoded
+//! from their metadata. Changes are classified following Cargo's SemVer compatibility guide
+//! (<https://doc.rust-lang.org/cargo/reference/semver.html>): a `major` change can break
+//! downstream crates, while a `minor` change only adds to the API.
+
+use std::ops::ControlFlow;
+use std::{fmt, iter};
+
+use rustc_data_structures::fx::{FxHashSet, FxIndexMap, FxIndexSet};
+use rustc_hir::def::{CtorKind, DefKind, Namespace, Res};
+use rustc_hir::def_id::{CrateNum, DefId};
+use rustc_infer::infer::TyCtxtInferExt;
+use rustc_metadata::creader::CStore;
+use rustc_middle::ty::print::{PrintTraitRefExt as _, with_no_trimmed_paths};
+use rustc_middle::ty::{self, GenericArgsRef, GenericParamDefKind, Ty, TyCtxt, TypingMode};
+use rustc_privacy::DefIdVisitor;
+use rustc_session::config::OutFileName;
+use rustc_span::symbol::{Symbol, kw, sym};
+use rustc_trait_selection::infer::InferCtxtExt;
+use serde_json::json;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    /// A backwards compatible addition, which needs a minor version bump.
+    Minor,
+    /// A change which can break downstream crates, which needs a major version bump.
+    Major,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Minor => "minor",
+            Severity::Major => "major",
+        }
+    }
+}
+
+struct Change {
+    path: String,
+    kind: &'static str,
+    change: &'static str,
+    severity: Severity,
+    description: String,
+}
+
+/// A public item, keyed by the path it is reachable at and its namespace.
+struct Item {
+    def_id: DefId,
+    /// The path of the type or trait this item belongs to, for variants and associated items.
+    owner: Option<String>,
+}
+
+type Items = FxIndexMap<(String, Namespace), Item>;
+
+/// Looks for items of `krate` outside of its public API in the types and traits it visits.
+struct PrivateItemFinder<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    krate: CrateNum,
+    public: &'a FxHashSet<DefId>,
+}
+
+impl<'tcx> DefIdVisitor<'tcx> for PrivateItemFinder<'_, 'tcx> {
+    type Result = ControlFlow<()>;
+
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_def_id(
+        &mut self,
+        def_id: DefId,
+        _kind: &str,
+        _descr: &dyn fmt::Display,
+    ) -> Self::Result {
+        if def_id.krate == self.krate && !self.public.contains(&def_id) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// Compares the crates passed to `-Z api-diff` and prints the changes between them as JSON.
+pub(crate) fn print(tcx: TyCtxt<'_>) {
+    // If one of the crates couldn't be loaded, the error was already reported.
+    let Some((old, new)) = CStore::from_tcx(tcx).api_diff_crates() else {
+        return;
+    };
+
+    let mut diff = ApiDiff { tcx, old, new, changes: Vec::new() };
+    diff.compare();
+    let mut changes = diff.changes;
+    changes.sort_by(|a, b| (&a.path, &a.description).cmp(&(&b.path, &b.description)));
+
+    let required_bump =
+        changes.iter().map(|change| change.severity).max().map_or("none", Severity::as_str);
+    let krate = |cnum: CrateNum| {
+        json!({
+            "name": tcx.crate_name(cnum).as_str(),
+            "hash": tcx.crate_hash(cnum).to_string(),
+        })
+    };
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|change| {
+            json!({
+                "path": change.path,
+                "kind": change.kind,
+                "change": change.change,
+                "severity": change.severity.as_str(),
+                "description": change.description,
+            })
+        })
+        .collect();
+    let report = json!({
+        "old": krate(old),
+        "new": krate(new),
+        "required_bump": required_bump,
+        "changes": changes,
+    });
+
+    let mut out = serde_json::to_string_pretty(&report).unwrap();
+    out.push('\n');
+    tcx.sess.io.output_file.as_ref().unwrap_or(&OutFileName::Stdout).overwrite(&out, tcx.sess);
+}
+
+fn print_with_paths(value: impl fmt::Display) -> String {
+    with_no_trimmed_paths!(value.to_string())
+}
+
+struct ApiDiff<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    old: CrateNum,
+    new: CrateNum,
+    changes: Vec<Change>,
+}
+
+impl<'tcx> ApiDiff<'tcx> {
+    fn compare(&mut self) {
+        let old_items = self.public_items(self.old);
+        let new_items = self.public_items(self.new);
+
+        // Only the type or trait itself is reported when it was added or removed, not
+        // everything that belongs to it.
+        let owner_missing = |item: &Item, items: &Items| {
+            item.owner
+                .as_ref()
+                .is_some_and(|owner| !items.contains_key(&(owner.clone(), Namespace::TypeNS)))
+        };
+
+        for (key, item) in &old_items {
+            match new_items.get(key) {
+                Some(new_item) => self.compare_items(&key.0, item.def_id, new_item.def_id),
+                None if owner_missing(item, &new_items) => {}
+                None => {
+                    let kind = self.tcx.def_descr(item.def_id);
+                    let description = "removed from the public API".to_string();
+                    self.push(&key.0, kind, "removed", Severity::Major, description);
+                }
+            }
+        }
+        for (key, item) in &new_items {
+            if old_items.contains_key(key) || owner_missing(item, &old_items) {
+                continue;
+            }
+            let (severity, description) = self.addition(item.def_id);
+            self.push(&key.0, self.tcx.def_descr(item.def_id), "added", severity, description);
+        }
+
+        let old_public: FxHashSet<_> = old_items.values().map(|item| item.def_id).collect();
+        let new_public: FxHashSet<_> = new_items.values().map(|item| item.def_id).collect();
+        self.compare_trait_impls(&old_public, &new_public);
+    }
+
+    fn push(
+        &mut self,
+        path: &str,
+        kind: &'static str,
+        change: &'static str,
+        severity: Severity,
+        description: String,
+    ) {
+        self.changes.push(Change { path: path.to_string(), kind, change, severity, description });
+    }
+
+    /// Collects the items which other crates can name, by walking the public modules of
+    /// `krate` starting at its root.
+    fn public_items(&self, krate: CrateNum) -> Items {
+        let tcx = self.tcx;
+        let mut items = Items::default();
+        let mut visited = FxHashSet::default();
+        let mut stack = vec![(krate.as_def_id(), None::<String>)];
+        while let Some((parent, parent_path)) = stack.pop() {
+            if !visited.insert(parent) {
+                continue;
+            }
+            let owner =
+                if tcx.def_kind(parent) == DefKind::Mod { None } else { parent_path.clone() };
+            for child in tcx.module_children(parent) {
+                let Res::Def(kind, def_id) = child.res else { continue };
+                // Constructors are compared as part of their struct or variant.
+                if !child.vis.is_public()
+                    || matches!(kind, DefKind::Ctor(..))
+                    || tcx.is_doc_hidden(def_id)
+                {
+                    continue;
+                }
+                let Some(ns) = kind.ns() else { continue };
+                let path = match &parent_path {
+                    Some(parent_path) => format!("{parent_path}::{}", child.ident),
+                    None => child.ident.to_string(),
+                };
+                if def_id.krate == krate {
+                    match kind {
+                        DefKind::Mod | DefKind::Enum | DefKind::Trait => {
+                            stack.push((def_id, Some(path.clone())));
+                        }
+                        _ => {}
+                    }
+                    if matches!(kind, DefKind::Struct | DefKind::Enum | DefKind::Union) {
+                        self.inherent_items(def_id, &path, &mut items);
+                    }
+                }
+                items.entry((path, ns)).or_insert(Item { def_id, owner: owner.clone() });
+            }
+        }
+        items
+    }
+
+    fn inherent_items(&self, adt: DefId, adt_path: &str, items: &mut Items) {
+        let tcx = self.tcx;
+        for &impl_def_id in tcx.inherent_impls(adt) {
+            for &def_id in tcx.associated_item_def_ids(impl_def_id) {
+                if !tcx.visibility(def_id).is_public() || tcx.is_doc_hidden(def_id) {
+                    continue;
+                }
+                let Some(ns) = tcx.def_kind(def_id).ns() else { continue };
+                let path = format!("{adt_path}::{}", tcx.item_name(def_id));
+                let owner = Some(adt_path.to_string());
+                items.entry((path, ns)).or_insert(Item { def_id, owner });
+            }
+        }
+    }
+
+    fn addition(&self, def_id: DefId) -> (Severity, String) {
+        let tcx = self.tcx;
+        match tcx.def_kind(def_id) {
+            DefKind::Variant
+                if !tcx.adt_def(tcx.parent(def_id)).is_variant_list_non_exhaustive() =>
+            {
+                let description = "added to an exhaustive enum, which breaks exhaustive matches";
+                (Severity::Major, description.to_string())
+            }
+            DefKind::AssocFn | DefKind::AssocConst | DefKind::AssocTy
+                if tcx.def_kind(tcx.parent(def_id)) == DefKind::Trait =>
+            {
+                if tcx.defaultness(def_id).has_value() {
+                    (Severity::Minor, "added to the trait with a default".to_string())
+                } else {
+                    let description =
+                        "added to the trait without a default, which breaks its implementations";
+                    (Severity::Major, description.to_string())
+                }
+            }
+            _ => (Severity::Minor, "added to the public API".to_string()),
+        }
+    }
+
+    fn compare_items(&mut self, path: &str, old: DefId, new: DefId) {
+        let tcx = self.tcx;
+        let kind = tcx.def_descr(new);
+
+        // The API of items which are re-exported from other crates isn't part of this crate's
+        // API, so they are only checked for still being the same item.
+        if old.krate != self.old || new.krate != self.new {
+            if old != new {
+                let (old_path, new_path) = (tcx.def_path_str(old), tcx.def_path_str(new));
+                let description = if old_path == new_path {
+                    format!("now refers to `{new_path}` from a different version of its crate")
+                } else {
+                    format!("now refers to `{new_path}` instead of `{old_path}`")
+                };
+                self.push(path, kind, "changed", Severity::Major, description);
+            }
+            return;
+        }
+
+        let def_kind = tcx.def_kind(old);
+        let same_kind = match (def_kind, tcx.def_kind(new)) {
+            // The mutability of statics is compared below.
+            (DefKind::Static { .. }, DefKind::Static { .. }) => true,
+            (old_kind, new_kind) => old_kind == new_kind,
+        };
+        if !same_kind {
+            let description = format!(
+                "changed from {} {} to {} {kind}",
+                tcx.def_descr_article(old),
+                tcx.def_descr(old),
+                tcx.def_descr_article(new),
+            );
+            self.push(path, kind, "changed", Severity::Major, description);
+            return;
+        }
+
+        match def_kind {
+            DefKind::Struct
+            | DefKind::Enum
+            | DefKind::Union
+            | DefKind::Trait
+            | DefKind::TraitAlias
+            | DefKind::TyAlias
+            | DefKind::Fn
+            | DefKind::AssocFn
+            | DefKind::AssocTy => {
+                self.compare_generics(path, kind, old, new);
+                let old_predicates = self.predicates(old, self.canonical_args(old));
+                let new_predicates = self.predicates(new, self.canonical_args(new));
+                // Removing a bound from a trait breaks the code relying on it, while removing
+                // a bound from anything else only accepts more code.
+                let removed =
+                    if def_kind == DefKind::Trait { Severity::Major } else { Severity::Minor };
+                for predicate in new_predicates.difference(&old_predicates) {
+                    let description = format!("added bound `{predicate}`");
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+                for predicate in old_predicates.difference(&new_predicates) {
+                    let description = format!("removed bound `{predicate}`");
+                    self.push(path, kind, "changed", removed, description);
+                }
+            }
+            _ => {}
+        }
+
+        match def_kind {
+            DefKind::Fn | DefKind::AssocFn => {
+                let (old_sig, new_sig) = (self.fn_sig(old), self.fn_sig(new));
+                if old_sig != new_sig {
+                    let description = format!("signature changed from `{old_sig}` to `{new_sig}`");
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+                if tcx.is_const_fn(old) && !tcx.is_const_fn(new) {
+                    let description = "is no longer a `const fn`".to_string();
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+            }
+            DefKind::Struct | DefKind::Union | DefKind::Enum => {
+                let (old_adt, new_adt) = (tcx.adt_def(old), tcx.adt_def(new));
+                if !old_adt.is_variant_list_non_exhaustive()
+                    && new_adt.is_variant_list_non_exhaustive()
+                {
+                    let description = "became `#[non_exhaustive]`".to_string();
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+                // The variants of enums are compared as separate items.
+                if !old_adt.is_enum() {
+                    self.compare_fields(
+                        path,
+                        kind,
+                        (old_adt.non_enum_variant(), self.canonical_args(old)),
+                        (new_adt.non_enum_variant(), self.canonical_args(new)),
+                    );
+                }
+                self.compare_auto_traits(path, kind, old, new);
+            }
+            DefKind::Variant => {
+                let (old_enum, new_enum) = (tcx.parent(old), tcx.parent(new));
+                self.compare_fields(
+                    path,
+                    kind,
+                    (tcx.adt_def(old_enum).variant_with_id(old), self.canonical_args(old_enum)),
+                    (tcx.adt_def(new_enum).variant_with_id(new), self.canonical_args(new_enum)),
+                );
+            }
+            DefKind::Const | DefKind::Static { .. } | DefKind::AssocConst | DefKind::TyAlias => {
+                let old_ty =
+                    print_with_paths(tcx.type_of(old).instantiate(tcx, self.canonical_args(old)));
+                let new_ty =
+                    print_with_paths(tcx.type_of(new).instantiate(tcx, self.canonical_args(new)));
+                if old_ty != new_ty {
+                    let description = format!("type changed from `{old_ty}` to `{new_ty}`");
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+                if tcx.static_mutability(old) != tcx.static_mutability(new) {
+                    let description = "mutability changed".to_string();
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+            }
+            DefKind::Trait => {
+                let (old_safety, new_safety) =
+                    (tcx.trait_def(old).safety, tcx.trait_def(new).safety);
+                if old_safety != new_safety {
+                    let description = if new_safety.is_unsafe() {
+                        "became an `unsafe trait`"
+                    } else {
+                        "is no longer an `unsafe trait`"
+                    };
+                    self.push(path, kind, "changed", Severity::Major, description.to_string());
+                }
+                if tcx.is_dyn_compatible(old) && !tcx.is_dyn_compatible(new) {
+                    let description = "is no longer dyn compatible".to_string();
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Compares the generic parameters of two items by position, as their names are not part
+    /// of the API.
+    fn compare_generics(&mut self, path: &str, kind: &'static str, old: DefId, new: DefId) {
+        // Lifetimes come before the other parameters and can't have defaults, so they are
+        // compared separately.
+        let is_lifetime =
+            |param: &&ty::GenericParamDef| matches!(param.kind, GenericParamDefKind::Lifetime);
+        let (old_lifetimes, old_params): (Vec<_>, Vec<_>) =
+            self.tcx.generics_of(old).own_params.iter().partition(is_lifetime);
+        let (new_lifetimes, new_params): (Vec<_>, Vec<_>) =
+            self.tcx.generics_of(new).own_params.iter().partition(is_lifetime);
+        for param in old_lifetimes.iter().skip(new_lifetimes.len()) {
+            let description = format!("removed lifetime parameter `{}`", param.name);
+            self.push(path, kind, "changed", Severity::Major, description);
+        }
+        for param in new_lifetimes.iter().skip(old_lifetimes.len()) {
+            let description = format!("added lifetime parameter `{}`", param.name);
+            self.push(path, kind, "changed", Severity::Major, description);
+        }
+        for (old_param, new_param) in iter::zip(&old_params, &new_params) {
+            if old_param.kind.descr() != new_param.kind.descr() {
+                let description = format!(
+                    "generic parameter `{}` changed from a {} to a {} parameter",
+                    new_param.name,
+                    old_param.kind.descr(),
+                    new_param.kind.descr(),
+                );
+                self.push(path, kind, "changed", Severity::Major, description);
+            }
+        }
+        for param in old_params.iter().skip(new_params.len()) {
+            let description = format!("removed generic parameter `{}`", param.name);
+            self.push(path, kind, "changed", Severity::Major, description);
+        }
+        for param in new_params.iter().skip(old_params.len()) {
+            let has_default = match param.kind {
+                GenericParamDefKind::Lifetime => false,
+                GenericParamDefKind::Type { has_default, .. }
+                | GenericParamDefKind::Const { has_default, .. } => has_default,
+            };
+            let (severity, description) = if has_default {
+                (
+                    Severity::Minor,
+                    format!("added generic parameter `{}` with a default", param.name),
+                )
+            } else {
+                let description =
+                    format!("added generic parameter `{}` without a default", param.name);
+                (Severity::Major, description)
+            };
+            self.push(path, kind, "changed", severity, description);
+        }
+    }
+
+    fn predicates(&self, def_id: DefId, args: GenericArgsRef<'tcx>) -> FxIndexSet<String> {
+        let tcx = self.tcx;
+        let mut predicates: FxIndexSet<_> = tcx
+            .explicit_predicates_of(def_id)
+            .predicates
+            .iter()
+            .map(|&(clause, _)| {
+                print_with_paths(ty::EarlyBinder::bind(clause).instantiate(tcx, args))
+            })
+            .collect();
+        if tcx.def_kind(def_id) == DefKind::Trait {
+            predicates.extend(
+                tcx.explicit_super_predicates_of(def_id)
+                    .iter_instantiated_copied(tcx, args)
+                    .map(|(clause, _)| print_with_paths(clause)),
+            );
+        }
+        predicates
+    }
+
+    /// Returns generic arguments which refer to the parameters of `def_id` by their position,
+    /// so that renaming a parameter doesn't show up as a changed signature. Lifetimes are
+    /// numbered apart from the other parameters, like in `compare_generics`, so that adding a
+    /// lifetime doesn't rename the type parameters after it.
+    fn canonical_args(&self, def_id: DefId) -> GenericArgsRef<'tcx> {
+        let tcx = self.tcx;
+        let (mut lifetimes, mut others) = (0, 0);
+        ty::GenericArgs::for_item(tcx, def_id, |param, _| {
+            let index = param.index;
+            let position = if let GenericParamDefKind::Lifetime = param.kind {
+                &mut lifetimes
+            } else {
+                &mut others
+            };
+            let n = *position;
+            *position += 1;
+            match param.kind {
+                GenericParamDefKind::Lifetime => {
+                    let name = Symbol::intern(&format!("'l{n}"));
+                    ty::Region::new_early_param(tcx, ty::EarlyParamRegion { index, name }).into()
+                }
+                GenericParamDefKind::Type { .. } => {
+                    let name = if param.name == kw::SelfUpper {
+                        kw::SelfUpper
+                    } else {
+                        Symbol::intern(&format!("T{n}"))
+                    };
+                    Ty::new_param(tcx, index, name).into()
+                }
+                GenericParamDefKind::Const { .. } => {
+                    let name = Symbol::intern(&format!("C{n}"));
+                    ty::Const::new_param(tcx, ty::ParamConst { index, name }).into()
+                }
+            }
+        })
+    }
+
+    /// Prints the signature of a function, independently of the names of its lifetimes.
+    fn fn_sig(&self, def_id: DefId) -> String {
+        let tcx = self.tcx;
+        let sig = tcx.fn_sig(def_id).instantiate(tcx, self.canonical_args(def_id));
+        // Late-bound lifetimes are named by the printer in order once they are anonymous.
+        print_with_paths(tcx.anonymize_bound_vars(sig))
+    }
+
+    /// Compares the fields of a struct, union or enum variant.
+    fn compare_fields(
+        &mut self,
+        path: &str,
+        kind: &'static str,
+        (old, old_args): (&ty::VariantDef, GenericArgsRef<'tcx>),
+        (new, new_args): (&ty::VariantDef, GenericArgsRef<'tcx>),
+    ) {
+        let tcx = self.tcx;
+        let ctor_descr = |ctor_kind| match ctor_kind {
+            None => "named fields",
+            Some(CtorKind::Fn) => "tuple fields",
+            Some(CtorKind::Const) => "no fields",
+        };
+        if old.ctor_kind() != new.ctor_kind() {
+            let description = format!(
+                "changed from {} to {}",
+                ctor_descr(old.ctor_kind()),
+                ctor_descr(new.ctor_kind()),
+            );
+            self.push(path, kind, "changed", Severity::Major, description);
+        }
+        if !old.is_field_list_non_exhaustive() && new.is_field_list_non_exhaustive() {
+            let description = "became `#[non_exhaustive]`".to_string();
+            self.push(path, kind, "changed", Severity::Major, description);
+        }
+
+        // Other crates can only construct it with a literal if all of its fields are public.
+        let old_constructible =
+            !old.is_field_list_non_exhaustive() && old.fields.iter().all(|f| f.vis.is_public());
+        for old_field in old.fields.iter().filter(|f| f.vis.is_public()) {
+            match new.fields.iter().find(|f| f.name == old_field.name) {
+                Some(new_field) if new_field.vis.is_public() => {
+                    let old_ty =
+                        print_with_paths(tcx.type_of(old_field.did).instantiate(tcx, old_args));
+                    let new_ty =
+                        print_with_paths(tcx.type_of(new_field.did).instantiate(tcx, new_args));
+                    if old_ty != new_ty {
+                        let description = format!(
+                            "type of field `{}` changed from `{old_ty}` to `{new_ty}`",
+                            old_field.name,
+                        );
+                        self.push(path, kind, "changed", Severity::Major, description);
+                    }
+                }
+                _ => {
+                    let description = format!("removed public field `{}`", old_field.name);
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+            }
+        }
+        for new_field in &new.fields {
+            let Some(old_field) = old.fields.iter().find(|f| f.name == new_field.name) else {
+                let (severity, description) = match (new_field.vis.is_public(), old_constructible) {
+                    (true, true) => (
+                        Severity::Major,
+                        format!(
+                            "added public field `{}`, which breaks struct literals and patterns",
+                            new_field.name,
+                        ),
+                    ),
+                    (true, false) => {
+                        (Severity::Minor, format!("added public field `{}`", new_field.name))
+                    }
+                    (false, true) => (
+                        Severity::Major,
+                        format!(
+                            "added private field `{}`, which breaks struct literals",
+                            new_field.name,
+                        ),
+                    ),
+                    (false, false) => continue,
+                };
+                self.push(path, kind, "changed", severity, description);
+                continue;
+            };
+            if new_field.vis.is_public() && !old_field.vis.is_public() {
+                let description = format!("field `{}` became public", new_field.name);
+                self.push(path, kind, "changed", Severity::Minor, description);
+            }
+        }
+    }
+
+    /// Compares the auto traits implemented by two versions of a struct, enum or union.
+    fn compare_auto_traits(&mut self, path: &str, kind: &'static str, old: DefId, new: DefId) {
+        let tcx = self.tcx;
+        let (Some(old_ty), Some(new_ty)) = (self.auto_trait_ty(old), self.auto_trait_ty(new))
+        else {
+            return;
+        };
+        let auto_traits = [
+            ("Send", tcx.get_diagnostic_item(sym::Send)),
+            ("Sync", tcx.get_diagnostic_item(sym::Sync)),
+            ("Unpin", tcx.lang_items().unpin_trait()),
+            ("UnwindSafe", tcx.get_diagnostic_item(sym::unwind_safe_trait)),
+            ("RefUnwindSafe", tcx.get_diagnostic_item(sym::ref_unwind_safe_trait)),
+        ];
+        for (name, trait_def_id) in auto_traits {
+            let Some(trait_def_id) = trait_def_id else { continue };
+            let implements = |ty: Ty<'tcx>| {
+                let infcx = tcx.infer_ctxt().build(TypingMode::non_body_analysis());
+                infcx
+                    .type_implements_trait(trait_def_id, [ty], ty::ParamEnv::empty())
+                    .must_apply_modulo_regions()
+            };
+            match (implements(old_ty), implements(new_ty)) {
+                (true, false) => {
+                    let description = format!("no longer implements `{name}`");
+                    self.push(path, kind, "changed", Severity::Major, description);
+                }
+                (false, true) => {
+                    let description = format!("now implements `{name}`");
+                    self.push(path, kind, "changed", Severity::Minor, description);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the type that auto traits are checked with, which has `'static` for all
+    /// lifetimes and `()` for all type parameters. `()` implements every auto trait, so this
+    /// only depends on the fields of the type. Types with const parameters are skipped.
+    fn auto_trait_ty(&self, def_id: DefId) -> Option<Ty<'tcx>> {
+        let tcx = self.tcx;
+        let generics = tcx.generics_of(def_id);
+        if generics.own_params.iter().any(|p| matches!(p.kind, GenericParamDefKind::Const { .. })) {
+            return None;
+        }
+        let args = ty::GenericArgs::for_item(tcx, def_id, |param, _| match param.kind {
+            GenericParamDefKind::Lifetime => tcx.lifetimes.re_static.into(),
+            GenericParamDefKind::Type { .. } | GenericParamDefKind::Const { .. } => {
+                tcx.types.unit.into()
+            }
+        });
+        Some(Ty::new_adt(tcx, tcx.adt_def(def_id), args))
+    }
+
+    /// Collects the trait impls of `krate` which other crates can observe, keyed by the trait
+    /// and type they are for.
+    fn trait_impls(
+        &self,
+        krate: CrateNum,
+        public: &FxHashSet<DefId>,
+    ) -> FxIndexMap<String, (DefId, GenericArgsRef<'tcx>)> {
+        let tcx = self.tcx;
+        let mut impls = FxIndexMap::default();
+        for &impl_def_id in tcx.trait_impls_in_crate(krate) {
+            let Some(header) = tcx.impl_trait_header(impl_def_id) else { continue };
+            let args = self.canonical_args(impl_def_id);
+            let trait_ref = header.trait_ref.instantiate(tcx, args);
+            // Impls which mention a private item anywhere in their trait or types, like
+            // `impl From<&Private> for Public` or `impl From<Box<dyn Private>> for Public`,
+            // can't be used by other crates.
+            let mut finder = PrivateItemFinder { tcx, krate, public };
+            if finder.visit_trait(trait_ref).is_break() {
+                continue;
+            }
+            let key = print_with_paths(format_args!(
+                "impl {}{} for {}",
+                header.polarity.as_str(),
+                trait_ref.print_only_trait_path(),
+                trait_ref.self_ty(),
+            ));
+            impls.entry(key).or_insert((impl_def_id, args));
+        }
+        impls
+    }
+
+    fn compare_trait_impls(
+        &mut self,
+        old_public: &FxHashSet<DefId>,
+        new_public: &FxHashSet<DefId>,
+    ) {
+        let old_impls = self.trait_impls(self.old, old_public);
+        let new_impls = self.trait_impls(self.new, new_public);
+        for (key, &(old_impl, old_args)) in &old_impls {
+            let Some(&(new_impl, new_args)) = new_impls.get(key) else {
+                let description = "removed trait impl".to_string();
+                self.push(key, "impl", "removed", Severity::Major, description);
+                continue;
+            };
+            let old_predicates = self.predicates(old_impl, old_args);
+            let new_predicates = self.predicates(new_impl, new_args);
+            for predicate in new_predicates.difference(&old_predicates) {
+                let description = format!("the impl now requires `{predicate}`");
+                self.push(key, "impl", "changed", Severity::Major, description);
+            }
+            for predicate in old_predicates.difference(&new_predicates) {
+                let description = format!("the impl no longer requires `{predicate}`");
+                self.push(key, "impl", "changed", Severity::Minor, description);
+            }
+        }
+        for key in new_impls.keys() {
+            if !old_impls.contains_key(key) {
+                let description = "added trait impl".to_string();
+                self.push(key, "impl", "added", Severity::Minor, description);
+            }
+        }
+    }
+}
@@ -42,6 +42,7 @@
 };
 use rustc_errors::emitter::stderr_destination;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifLog;
 use rustc_errors::{ColorConfig, DiagCtxt, ErrCode, FatalError, PResult, markdown};
 use rustc_feature::find_gated_cfg;
 use rustc_interface::util::{self, get_codegen_backend};
@@ -85,6 +86,7 @@
 #[allow(unused_imports)]
 use {do_not_use_print as print, do_not_use_print as println};
 
+mod api_diff;
 pub mod args;
 pub mod pretty;
 #[macro_use]
@@ -343,6 +345,10 @@ fn run_compiler(
         None => false, // no input: we will exit early
     };
 
+    // With `--error-format=sarif`, keep the log of the diagnostics emitted so far open, so that
+    // the compilation adds to it instead of printing a second log.
+    let _sarif_log =
+        matches!(config.opts.error_format, ErrorOutputType::Sarif).then(SarifLog::stderr);
     drop(default_early_dcx);
 
     callbacks.config(&mut config);
@@ -438,6 +444,12 @@ fn run_compiler(
                 }
 
                 passes::write_dep_info(tcx);
+                passes::print_crate_graph(tcx);
+
+                if sess.opts.unstable_opts.api_diff.is_some() {
+                    api_diff::print(tcx);
+                    return early_exit();
+                }
 
                 if sess.opts.output_types.contains_key(&OutputType::DepInfo)
                     && sess.opts.output_types.len() == 1
@@ -700,9 +712,15 @@ fn print_crate_info(
     #[allow(unused_imports)]
     use {do_not_use_safe_print as safe_print, do_not_use_safe_print as safe_println};
 
-    // NativeStaticLibs and LinkArgs are special - printed during linking
+    // NativeStaticLibs and LinkArgs are special - printed during linking,
+    // and CrateGraph is printed once name resolution loaded all crates
     // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs) {
+    if sess
+        .opts
+        .prints
+        .iter()
+        .all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs || p.kind == CrateGraph)
+    {
         return Compilation::Continue;
     }
 
@@ -847,6 +865,7 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            CrateGraph => {}
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
@@ -0,0 +1,289 @@
+//! Annotations that let CI services show diagnostics inline in code review.
+//!
+//! With `--error-format=human-github` or `--error-format=human-gitlab`, the
+//! human-readable emitter prints every diagnostic as usual, and additionally
+//! reports it in a format that the CI service understands:
+//!
+//! - GitHub Actions picks up [workflow commands] like
+//!   `::error file=src/lib.rs,line=1,col=5::message` from the output of a job.
+//!   These are written right after the rendered diagnostic.
+//! - GitLab reads a [code quality report] from a JSON file. Since cargo runs
+//!   the compiler once per crate, the issues are added to the issues already
+//!   in the report, instead of replacing them.
+//!
+//! [workflow commands]: https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/workflow-commands-for-github-actions
+//! [code quality report]: https://docs.gitlab.com/ee/ci/testing/code_quality.html#code-quality-report-format
+
+use std::collections::HashSet;
+use std::error::Report;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::{self, PathBuf};
+use std::{env, fs, thread};
+
+use rustc_data_structures::flock;
+use rustc_data_structures::stable_hasher::{Hash128, StableHasher};
+use rustc_error_messages::FluentArgs;
+use rustc_span::source_map::SourceMap;
+use serde::Serialize;
+
+use crate::emitter::HumanReadableErrorType;
+use crate::translation::Translate;
+use crate::{DiagInner, Level};
+
+#[cfg(test)]
+mod tests;
+
+/// The report that `--error-format=human-gitlab` writes to if
+/// `-Zgitlab-code-quality-report` isn't given, as in GitLab's examples.
+pub const DEFAULT_CODE_QUALITY_REPORT: &str = "gl-code-quality-report.json";
+
+/// The CI service to annotate diagnostics for.
+pub enum CiAnnotations {
+    /// Print a GitHub Actions workflow command for every diagnostic.
+    GitHub,
+    /// Collect the diagnostics in a GitLab code quality report.
+    GitLab(CodeQualityReport),
+}
+
+impl CiAnnotations {
+    /// Returns the annotations requested by the given error format, if any.
+    pub fn for_error_format(
+        kind: HumanReadableErrorType,
+        code_quality_report: Option<PathBuf>,
+    ) -> Option<CiAnnotations> {
+        match kind {
+            HumanReadableErrorType::GitHubActions => Some(CiAnnotations::GitHub),
+            HumanReadableErrorType::GitLabCodeQuality => {
+                let path =
+                    code_quality_report.unwrap_or_else(|| DEFAULT_CODE_QUALITY_REPORT.into());
+                Some(CiAnnotations::GitLab(CodeQualityReport::new(path)))
+            }
+            HumanReadableErrorType::Default
+            | HumanReadableErrorType::Unicode
+            | HumanReadableErrorType::AnnotateSnippet
+            | HumanReadableErrorType::Short => None,
+        }
+    }
+
+    /// Annotates a diagnostic that has just been rendered to `dst`.
+    pub(crate) fn record(&mut self, diag: CiDiagnostic, dst: &mut impl Write) -> io::Result<()> {
+        match self {
+            CiAnnotations::GitHub => {
+                let _buffer_lock = crate::lock::acquire_global_lock("rustc_errors");
+                writeln!(dst, "{}", diag.workflow_command())?;
+                dst.flush()
+            }
+            CiAnnotations::GitLab(report) => {
+                report.issues.extend(diag.code_quality_issue());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Where in the source a diagnostic points to. Lines and columns are 1-based,
+/// columns count characters.
+struct CiLocation {
+    path: String,
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+/// The parts of a diagnostic that are shown in a CI annotation.
+pub(crate) struct CiDiagnostic {
+    level: Level,
+    /// The header of the diagnostic, e.g. `error[E0308]`.
+    title: String,
+    /// The error code or lint name of the diagnostic, if any.
+    check_name: Option<String>,
+    /// The message of the diagnostic, followed by those of its children.
+    message: String,
+    location: Option<CiLocation>,
+}
+
+impl CiDiagnostic {
+    pub(crate) fn new(
+        translator: &impl Translate,
+        sm: Option<&SourceMap>,
+        diag: &DiagInner,
+        args: &FluentArgs<'_>,
+    ) -> CiDiagnostic {
+        let title = match diag.code {
+            Some(code) => format!("{}[{code}]", diag.level.to_str()),
+            None => diag.level.to_str().to_string(),
+        };
+        let check_name = match (&diag.code, &diag.is_lint) {
+            (Some(code), _) => Some(code.to_string()),
+            (None, Some(lint)) => Some(lint.name.clone()),
+            (None, None) => None,
+        };
+
+        let mut message = translator.translate_messages(&diag.messages, args).into_owned();
+        for child in &diag.children {
+            let child_message = translator.translate_messages(&child.messages, args);
+            message.push_str(&format!("\n{}: {child_message}", child.level.to_str()));
+        }
+
+        let location = sm.zip(diag.span.primary_span()).and_then(|(sm, span)| {
+            if span.is_dummy() {
+                return None;
+            }
+            let start = sm.lookup_char_pos(span.lo());
+            let end = sm.lookup_char_pos(span.hi());
+            Some(CiLocation {
+                path: sm.filename_for_diagnostics(&start.file.name).to_string(),
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+            })
+        });
+
+        CiDiagnostic { level: diag.level, title, check_name, message, location }
+    }
+
+    /// Formats the diagnostic as a GitHub Actions workflow command.
+    fn workflow_command(&self) -> String {
+        let command = match self.level {
+            Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error => "error",
+            Level::ForceWarning(_) | Level::Warning => "warning",
+            _ => "notice",
+        };
+        let mut properties = Vec::new();
+        if let Some(location) = &self.location {
+            properties.push(format!("file={}", escape_property(&location.path)));
+            properties.push(format!("line={}", location.start_line));
+            properties.push(format!("endLine={}", location.end_line));
+            properties.push(format!("col={}", location.start_column));
+            properties.push(format!("endColumn={}", location.end_column));
+        }
+        properties.push(format!("title={}", escape_property(&self.title)));
+        format!("::{command} {}::{}", properties.join(","), escape_data(&self.message))
+    }
+
+    /// Converts the diagnostic to an issue of a GitLab code quality report.
+    /// Issues have to point to a file, so diagnostics without a span are left out.
+    fn code_quality_issue(&self) -> Option<CodeQualityIssue> {
+        let location = self.location.as_ref()?;
+        let severity = match self.level {
+            Level::Bug | Level::DelayedBug => "blocker",
+            Level::Fatal | Level::Error => "critical",
+            Level::ForceWarning(_) | Level::Warning => "minor",
+            _ => "info",
+        };
+        let check_name = self.check_name.clone().unwrap_or_else(|| "rustc".to_string());
+
+        // The fingerprint identifies an issue across reports, e.g. to tell
+        // which issues a merge request introduces.
+        let mut hasher = StableHasher::new();
+        (&check_name, &location.path, location.start_line, &self.message).hash(&mut hasher);
+        let fingerprint: Hash128 = hasher.finish();
+
+        Some(CodeQualityIssue {
+            description: self.message.clone(),
+            check_name,
+            fingerprint: format!("{fingerprint:032x}"),
+            severity,
+            location: CodeQualityLocation {
+                path: location.path.clone(),
+                lines: CodeQualityLines { begin: location.start_line, end: location.end_line },
+            },
+        })
+    }
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// A GitLab code quality report, which is written when it's dropped if any
+/// issue was added to it. Only the session's emitter has one, so the report is
+/// updated at most once per compilation session.
+pub struct CodeQualityReport {
+    path: PathBuf,
+    issues: Vec<CodeQualityIssue>,
+}
+
+impl CodeQualityReport {
+    pub fn new(path: PathBuf) -> CodeQualityReport {
+        CodeQualityReport { path, issues: Vec::new() }
+    }
+
+    /// The file that is locked while the report is updated, as other compiler
+    /// processes may be writing to the same report. It's kept in the temporary
+    /// directory, so that it doesn't end up next to the report.
+    fn lock_path(&self) -> io::Result<PathBuf> {
+        let mut hasher = StableHasher::new();
+        path::absolute(&self.path)?.hash(&mut hasher);
+        let hash: Hash128 = hasher.finish();
+        Ok(env::temp_dir().join(format!("rustc-code-quality-{hash:032x}.lock")))
+    }
+
+    /// Adds the issues to those already in the report. Issues that are already
+    /// in the report (e.g. because a crate is compiled again) aren't duplicated.
+    fn write(&self) -> io::Result<()> {
+        let _lock = flock::Lock::new(&self.lock_path()?, true, true, true)?;
+
+        let mut issues: Vec<serde_json::Value> = match fs::read(&self.path) {
+            Ok(contents) if !contents.is_empty() => serde_json::from_slice(&contents)?,
+            Ok(_) => Vec::new(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let known: HashSet<String> = issues
+            .iter()
+            .filter_map(|issue| issue["fingerprint"].as_str().map(str::to_string))
+            .collect();
+        for issue in &self.issues {
+            if !known.contains(&issue.fingerprint) {
+                issues.push(serde_json::to_value(issue)?);
+            }
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&issues)?)
+    }
+}
+
+impl Drop for CodeQualityReport {
+    fn drop(&mut self) {
+        // Leave the report alone if there's nothing to add to it.
+        if self.issues.is_empty() {
+            return;
+        }
+        if let Err(e) = self.write() {
+            // Don't turn a panic (like a fatal error) into an abort.
+            if !thread::panicking() {
+                panic!("failed to write `{}`: {}", self.path.display(), Report::new(e));
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CodeQualityIssue {
+    description: String,
+    check_name: String,
+    fingerprint: String,
+    severity: &'static str,
+    location: CodeQualityLocation,
+}
+
+#[derive(Serialize)]
+struct CodeQualityLocation {
+    path: String,
+    lines: CodeQualityLines,
+}
+
+#[derive(Serialize)]
+struct CodeQualityLines {
+    begin: usize,
+    end: usize,
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::{env, process, str};
+
+use rustc_data_structures::sync::Lrc;
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::{BytePos, Span};
+use serde_json::Value;
+use termcolor::NoColor;
+
+use super::*;
+use crate::DiagCtxt;
+use crate::codes::E0308;
+use crate::emitter::HumanEmitter;
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` with a `DiagCtxt` that annotates the diagnostics for a `test.rs`
+/// file containing `code`, and returns the rendered output.
+fn with_annotations(
+    code: &str,
+    ci_annotations: CiAnnotations,
+    f: impl FnOnce(&DiagCtxt),
+) -> String {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let dst = Box::new(NoColor::new(Shared { data: output.clone() }));
+        let emitter = HumanEmitter::new(dst, fallback_bundle)
+            .sm(Some(sm))
+            .ci_annotations(Some(ci_annotations));
+        let dcx = DiagCtxt::new(Box::new(emitter));
+        f(&dcx);
+        // A code quality report is written once the emitter is dropped.
+        drop(dcx);
+
+        let bytes = output.lock().unwrap();
+        str::from_utf8(&bytes).unwrap().to_string()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn github_workflow_commands() {
+    let output = with_annotations("let x: u8 = 'a';\n", CiAnnotations::GitHub, |dcx| {
+        dcx.handle()
+            .struct_span_err(span(12, 15), "mismatched types")
+            .with_code(E0308)
+            .with_span_label(span(12, 15), "expected `u8`, found `char`")
+            .with_note("100% of bytes are not chars")
+            .emit();
+        dcx.handle().struct_warn("unused: x, y").emit();
+    });
+    let commands: Vec<&str> = output.lines().filter(|line| line.starts_with("::")).collect();
+    assert_eq!(commands, [
+        "::error file=test.rs,line=1,endLine=1,col=13,endColumn=16,title=error[E0308]::\
+         mismatched types%0Anote: 100%25 of bytes are not chars",
+        "::warning title=warning::unused: x, y",
+    ]);
+    // The rendered diagnostics are still printed.
+    assert!(output.contains("error[E0308]: mismatched types\n --> test.rs:1:13\n"));
+}
+
+#[test]
+fn escape_workflow_commands() {
+    assert_eq!(escape_data("a:b,c%d\r\ne"), "a:b,c%25d%0D%0Ae");
+    assert_eq!(escape_property("a:b,c%d\r\ne"), "a%3Ab%2Cc%25d%0D%0Ae");
+}
+
+#[test]
+fn gitlab_code_quality_report() {
+    let path = env::temp_dir().join(format!("rustc-code-quality-{}.json", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let emit = |message: &str| {
+        let report = CiAnnotations::GitLab(CodeQualityReport::new(path.clone()));
+        with_annotations("let x: u8 = 'a';\n", report, |dcx| {
+            dcx.handle().struct_span_err(span(12, 15), message.to_string()).with_code(E0308).emit();
+            dcx.handle().struct_span_warn(span(4, 5), "unused variable: `x`").emit();
+            // Issues have to point to a file.
+            dcx.handle().struct_warn("no span").emit();
+        });
+        let report: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        report.as_array().unwrap().clone()
+    };
+
+    let issues = emit("mismatched types");
+    assert_eq!(issues.len(), 2);
+    assert_eq!(issues[0]["description"], "mismatched types");
+    assert_eq!(issues[0]["check_name"], "E0308");
+    assert_eq!(issues[0]["severity"], "critical");
+    assert_eq!(issues[0]["location"]["path"], "test.rs");
+    assert_eq!(issues[0]["location"]["lines"]["begin"], 1);
+    assert_eq!(issues[1]["check_name"], "rustc");
+    assert_eq!(issues[1]["severity"], "minor");
+    assert_ne!(issues[0]["fingerprint"], issues[1]["fingerprint"]);
+
+    // Compiling again doesn't duplicate the issues, but new issues are added.
+    assert_eq!(emit("mismatched types"), issues);
+    let issues = emit("mismatched types again");
+    assert_eq!(issues.len(), 3);
+    assert_eq!(issues[2]["description"], "mismatched types again");
+
+    // The lock file isn't left next to the report.
+    let mut lock_path = path.clone().into_os_string();
+    lock_path.push(".lock");
+    assert!(!Path::new(&lock_path).exists());
+
+    // A context without issues doesn't write the report.
+    fs::remove_file(&path).unwrap();
+    let report = CiAnnotations::GitLab(CodeQualityReport::new(path.clone()));
+    with_annotations("", report, |_| {});
+    assert!(!path.exists());
+}
@@ -26,6 +26,7 @@
 use termcolor::{Buffer, BufferWriter, Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 use tracing::{debug, instrument, trace, warn};
 
+use crate::ci::{CiAnnotations, CiDiagnostic};
 use crate::diagnostic::DiagLocation;
 use crate::registry::Registry;
 use crate::snippet::{
@@ -48,6 +49,8 @@ pub enum HumanReadableErrorType {
     Unicode,
     AnnotateSnippet,
     Short,
+    GitHubActions,
+    GitLabCodeQuality,
 }
 
 impl HumanReadableErrorType {
@@ -524,6 +527,13 @@ fn emit_diagnostic(&mut self, mut diag: DiagInner, _registry: &Registry) {
             &suggestions,
             self.track_diagnostics.then_some(&diag.emitted_at),
         );
+
+        if self.ci_annotations.is_some() && !diag.level.is_failure_note() {
+            let ci_diag = CiDiagnostic::new(&*self, self.sm.as_deref(), &diag, &fluent_args);
+            if let Err(e) = self.ci_annotations.as_mut().unwrap().record(ci_diag, &mut *self.dst) {
+                panic!("failed to emit error: {e}")
+            }
+        }
     }
 
     fn should_show_explain(&self) -> bool {
@@ -626,6 +636,8 @@ pub struct HumanEmitter {
     track_diagnostics: bool,
     terminal_url: TerminalUrl,
     theme: OutputTheme,
+    /// Annotations for a CI service that are emitted in addition to the rendered diagnostics.
+    ci_annotations: Option<CiAnnotations>,
 }
 
 #[derive(Debug)]
@@ -651,6 +663,7 @@ pub fn new(dst: Destination, fallback_bundle: LazyFallbackBundle) -> HumanEmitte
             track_diagnostics: false,
             terminal_url: TerminalUrl::No,
             theme: OutputTheme::Ascii,
+            ci_annotations: None,
         }
     }
 
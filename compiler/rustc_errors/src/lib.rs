@@ -79,6 +79,7 @@
 use crate::registry::Registry;
 
 pub mod annotate_snippet_emitter_writer;
+pub mod ci;
 pub mod codes;
 mod diagnostic;
 mod diagnostic_impls;
@@ -88,6 +89,7 @@
 mod lock;
 pub mod markdown;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 #[cfg(test)]
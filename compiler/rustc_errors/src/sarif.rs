@@ -0,0 +1,550 @@
+//! A SARIF emitter for errors.
+//!
+//! This produces a single [SARIF 2.1.0] log per compilation, with one result
+//! for every diagnostic. Diagnostics with an error code or a lint name are
+//! associated with a rule of the same name; the rules carry the explanation of
+//! the error code (if any), as `rustc --explain` would print it.
+//!
+//! Unlike the JSON emitter, which prints every diagnostic as soon as it's
+//! emitted, a SARIF log is a single JSON document. The results are therefore
+//! collected in a [`SarifLog`], and the log is only written once the last
+//! emitter adding to it is dropped. There's a single stderr, so all the
+//! diagnostic contexts that print SARIF to it at the same time (the early ones
+//! of the driver, and the one of the session) add to the same log.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::collections::BTreeMap;
+use std::error::Report;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, Weak};
+
+use derive_setters::Setters;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
+use rustc_error_messages::FluentArgs;
+use rustc_lint_defs::Applicability;
+use rustc_span::Span;
+use rustc_span::source_map::SourceMap;
+use serde::Serialize;
+
+use crate::diagnostic::IsLint;
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{Translate, to_fluent_args};
+use crate::{CodeSuggestion, FluentBundle, LazyFallbackBundle, Level, MultiSpan, Suggestions};
+
+#[cfg(test)]
+mod tests;
+
+/// The base that relative paths in the log are resolved against.
+const SRCROOT: &str = "%SRCROOT%";
+
+/// The log that is currently being printed to stderr, if any. It's not kept
+/// alive from here, so that it's written once its last emitter is dropped.
+static STDERR_LOG: Mutex<Weak<Mutex<LogContents>>> = Mutex::new(Weak::new());
+
+/// The rules and results of a compilation session, which are written as a
+/// single SARIF log once the last handle to them is dropped.
+///
+/// Every `DiagCtxt` of a session has its own emitter, but they all add their
+/// results to the same log, so that a compilation produces one log.
+#[derive(Clone)]
+pub struct SarifLog(Arc<Mutex<LogContents>>);
+
+impl SarifLog {
+    pub fn new(dst: Box<dyn Write + Send>) -> SarifLog {
+        SarifLog(Arc::new(Mutex::new(LogContents {
+            dst: IntoDynSyncSend(dst),
+            write_empty_log: false,
+            rules: FxIndexMap::default(),
+            results: Vec::new(),
+        })))
+    }
+
+    /// Returns the log that is printed to stderr, which is shared by all the
+    /// diagnostic contexts alive at the same time. A new log is started once
+    /// the previous one has been written.
+    pub fn stderr() -> SarifLog {
+        let mut current = STDERR_LOG.lock().unwrap();
+        if let Some(contents) = current.upgrade() {
+            return SarifLog(contents);
+        }
+        let log = SarifLog::new(Box::new(io::BufWriter::new(io::stderr())));
+        *current = Arc::downgrade(&log.0);
+        log
+    }
+
+    /// Makes the log be written even if no diagnostic is emitted at all. A
+    /// compilation session always produces a log, while the early diagnostic
+    /// contexts only do if they report something.
+    pub fn write_empty_log(&self) {
+        self.0.lock().unwrap().write_empty_log = true;
+    }
+}
+
+struct LogContents {
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    /// Whether to write a log if no diagnostic was emitted at all.
+    write_empty_log: bool,
+    rules: FxIndexMap<String, ReportingDescriptor>,
+    results: Vec<SarifResult>,
+}
+
+impl LogContents {
+    fn emit_log(&mut self) -> io::Result<()> {
+        let original_uri_base_ids = std::env::current_dir().ok().map(|dir| {
+            let mut uri = path_to_uri(&dir.to_string_lossy());
+            if !uri.ends_with('/') {
+                uri.push('/');
+            }
+            BTreeMap::from([(SRCROOT, ArtifactLocation { uri, uri_base_id: None })])
+        });
+        let log = SarifLog {
+            schema: "https://json.schemastore.org/sarif-2.1.0.json",
+            version: "2.1.0",
+            runs: [Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                original_uri_base_ids,
+                column_kind: "unicodeCodePoints",
+                results: &self.results,
+            }],
+        };
+        serde_json::to_writer_pretty(&mut *self.dst, &log)?;
+        self.dst.write_all(b"\n")?;
+        self.dst.flush()
+    }
+
+    /// Returns the index of the rule the diagnostic belongs to, adding the rule
+    /// to the log if this is its first result.
+    fn rule_index(&mut self, diag: &crate::DiagInner, registry: &Registry) -> usize {
+        let rule = if let Some(code) = diag.code {
+            let explanation = registry.try_find_description(code).ok();
+            ReportingDescriptor {
+                id: code.to_string(),
+                // The first paragraph of an explanation summarizes the error.
+                short_description: explanation.map(|explanation| Message {
+                    text: explanation.split("\n\n").next().unwrap_or_default().trim().to_string(),
+                }),
+                help: explanation.map(|explanation| MultiformatMessage {
+                    text: explanation.to_string(),
+                    markdown: explanation.to_string(),
+                }),
+                help_uri: Some(format!("https://doc.rust-lang.org/error_codes/{code}.html")),
+            }
+        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
+            ReportingDescriptor::without_description(name.clone())
+        } else {
+            // Everything else is grouped by its level, so that every result has a rule.
+            ReportingDescriptor::without_description(sarif_level(diag.level).to_string())
+        };
+        let entry = self.rules.entry(rule.id.clone());
+        let index = entry.index();
+        entry.or_insert(rule);
+        index
+    }
+}
+
+impl Drop for LogContents {
+    fn drop(&mut self) {
+        if self.results.is_empty() && !self.write_empty_log {
+            return;
+        }
+        if let Err(e) = self.emit_log() {
+            // Don't turn a panic (like a fatal error) into an abort.
+            if !std::thread::panicking() {
+                panic!("failed to print diagnostics: {e:?}");
+            }
+        }
+    }
+}
+
+#[derive(Setters)]
+pub struct SarifEmitter {
+    #[setters(skip)]
+    log: SarifLog,
+    #[setters(skip)]
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    #[setters(skip)]
+    fallback_bundle: LazyFallbackBundle,
+}
+
+impl SarifEmitter {
+    pub fn new(
+        log: SarifLog,
+        sm: Lrc<SourceMap>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter { log, sm, fluent_bundle: None, fallback_bundle }
+    }
+
+    /// Converts a span to the file it's in and the region it covers, unless
+    /// the span doesn't point to any source.
+    fn location(&self, span: Span) -> Option<(ArtifactLocation, Region)> {
+        if span.is_dummy() {
+            return None;
+        }
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let file_name = self.sm.filename_for_diagnostics(&start.file.name).to_string();
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        let uri = path_to_uri(&file_name);
+        let uri_base_id = (!is_absolute_uri(&uri)).then_some(SRCROOT);
+        let region = Region {
+            start_line: start.line,
+            start_column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+            byte_offset: byte_start,
+            byte_length: byte_end.saturating_sub(byte_start),
+        };
+        Some((ArtifactLocation { uri, uri_base_id }, region))
+    }
+
+    /// Converts the labelled spans of a diagnostic to locations. Returns the
+    /// primary locations and the secondary locations separately.
+    fn locations(
+        &self,
+        msp: &MultiSpan,
+        args: &FluentArgs<'_>,
+        default_message: Option<&str>,
+    ) -> (Vec<Location>, Vec<Location>) {
+        let mut primary = Vec::new();
+        let mut secondary = Vec::new();
+        for span_label in msp.span_labels() {
+            let Some((artifact_location, region)) = self.location(span_label.span) else {
+                continue;
+            };
+            let label = span_label
+                .label
+                .as_ref()
+                .map(|m| self.translate_message(m, args).map_err(Report::new).unwrap().to_string())
+                .or_else(|| default_message.map(str::to_string));
+            let location = Location {
+                id: None,
+                physical_location: PhysicalLocation { artifact_location, region },
+                message: label.map(|text| Message { text }),
+            };
+            if span_label.is_primary {
+                primary.push(location);
+            } else {
+                secondary.push(location);
+            }
+        }
+        (primary, secondary)
+    }
+
+    /// Converts a suggestion to one fix for every alternative it offers.
+    fn fixes(&self, suggestion: &CodeSuggestion, description: &str) -> Vec<Fix> {
+        suggestion
+            .substitutions
+            .iter()
+            .filter_map(|substitution| {
+                let mut artifact_changes: Vec<ArtifactChange> = Vec::new();
+                for part in &substitution.parts {
+                    let (artifact_location, deleted_region) = self.location(part.span)?;
+                    let replacement = Replacement {
+                        deleted_region,
+                        inserted_content: ArtifactContent { text: part.snippet.clone() },
+                    };
+                    match artifact_changes
+                        .iter_mut()
+                        .find(|change| change.artifact_location == artifact_location)
+                    {
+                        Some(change) => change.replacements.push(replacement),
+                        None => artifact_changes.push(ArtifactChange {
+                            artifact_location,
+                            replacements: vec![replacement],
+                        }),
+                    }
+                }
+                Some(Fix {
+                    description: Message { text: description.to_string() },
+                    artifact_changes,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&FluentBundle> {
+        self.fluent_bundle.as_deref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: crate::DiagInner, registry: &Registry) {
+        // Notes like "aborting due to 2 previous errors" only summarize the
+        // other results.
+        if diag.level.is_failure_note() {
+            return;
+        }
+
+        let args = to_fluent_args(diag.args.iter());
+
+        let mut text = self.translate_messages(&diag.messages, &args).to_string();
+        let (locations, mut related_locations) = self.locations(&diag.span, &args, None);
+
+        // Sub-diagnostics that point somewhere become related locations, all
+        // others are appended to the message.
+        for child in &diag.children {
+            let level = child.level.to_str();
+            let message = self.translate_messages(&child.messages, &args);
+            let message = format!("{level}: {message}");
+            let (primary, secondary) = self.locations(&child.span, &args, Some(&message));
+            if primary.is_empty() {
+                text.push('\n');
+                text.push_str(&message);
+            }
+            related_locations.extend(primary);
+            related_locations.extend(secondary);
+        }
+
+        let suggestions: &[CodeSuggestion] = match &diag.suggestions {
+            Suggestions::Enabled(suggestions) => &suggestions[..],
+            Suggestions::Sealed(suggestions) => &suggestions[..],
+            Suggestions::Disabled => &[],
+        };
+        let mut fixes = Vec::new();
+        for suggestion in suggestions {
+            let message =
+                self.translate_message(&suggestion.msg, &args).map_err(Report::new).unwrap();
+            // Only suggestions that can be applied blindly are offered as
+            // fixes, the others are merely mentioned.
+            if suggestion.applicability == Applicability::MachineApplicable {
+                fixes.extend(self.fixes(suggestion, &message));
+            } else {
+                text.push_str("\nhelp: ");
+                text.push_str(&message);
+            }
+        }
+
+        for (id, location) in related_locations.iter_mut().enumerate() {
+            location.id = Some(id);
+        }
+
+        let mut log = self.log.0.lock().unwrap();
+        let rule_index = log.rule_index(&diag, registry);
+        let rule_id = log.rules.get_index(rule_index).unwrap().0.clone();
+        log.results.push(SarifResult {
+            rule_id,
+            rule_index,
+            level: sarif_level(diag.level),
+            message: Message { text },
+            locations,
+            related_locations,
+            fixes,
+        });
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        // The explanations are part of the rules instead.
+        false
+    }
+}
+
+/// Maps a diagnostic level to the level of a SARIF result.
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error => "error",
+        Level::ForceWarning(_) | Level::Warning => "warning",
+        Level::Note | Level::OnceNote | Level::Help | Level::OnceHelp | Level::FailureNote => {
+            "note"
+        }
+        Level::Allow | Level::Expect(_) => "none",
+    }
+}
+
+/// Converts a file path to a URI reference. Absolute paths become `file:` URIs,
+/// relative paths stay relative to the directory the compiler was run in.
+fn path_to_uri(path: &str) -> String {
+    let path = if cfg!(windows) { path.replace('\\', "/") } else { path.to_string() };
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+
+    let mut uri = String::with_capacity(path.len());
+    if has_drive {
+        uri.push_str("file:///");
+    } else if path.starts_with('/') {
+        uri.push_str("file://");
+    }
+    for (i, &byte) in bytes.iter().enumerate() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            b':' if has_drive && i == 1 => uri.push(':'),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+fn is_absolute_uri(uri: &str) -> bool {
+    uri.starts_with("file:")
+}
+
+// The following data types are provided just for serialisation. They are a
+// subset of the SARIF object model, named after the corresponding objects.
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_uri_base_ids: Option<BTreeMap<&'static str, ArtifactLocation>>,
+    /// How the columns of regions are counted: the compiler counts characters.
+    column_kind: &'static str,
+    results: &'a [SarifResult],
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: ToolComponent<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<&'a ReportingDescriptor>,
+}
+
+/// A rule, i.e. an error code or a lint.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<MultiformatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+}
+
+impl ReportingDescriptor {
+    fn without_description(id: String) -> ReportingDescriptor {
+        ReportingDescriptor { id, short_description: None, help: None, help_uri: None }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    /// "error", "warning", "note" or "none".
+    level: &'static str,
+    message: Message,
+    /// The primary spans of the diagnostic.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Location>,
+    /// The secondary spans of the diagnostic, and the spans of its sub-diagnostics.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    /// Machine applicable suggestions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct MultiformatMessage {
+    text: String,
+    markdown: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
@@ -0,0 +1,158 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rustc_span::BytePos;
+use rustc_span::source_map::FilePathMapping;
+use serde_json::Value;
+
+use super::*;
+use crate::DiagCtxt;
+use crate::codes::E0308;
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` with a `DiagCtxt` emitting SARIF for a `test.rs` file containing
+/// `code`, and returns the resulting log.
+fn with_sarif_log(code: &str, f: impl FnOnce(&DiagCtxt)) -> Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let log = SarifLog::new(Box::new(Shared { data: output.clone() }));
+        log.write_empty_log();
+        let se = SarifEmitter::new(log, sm, fallback_bundle);
+        let registry = Registry::new(&[(E0308, "Mismatched types.\n\nLonger explanation.\n")]);
+        let dcx = DiagCtxt::new(Box::new(se)).with_registry(registry);
+        f(&dcx);
+        // The log is written once the emitter is dropped.
+        drop(dcx);
+
+        let bytes = output.lock().unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn empty_log() {
+    let log = with_sarif_log("", |_| {});
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(log["runs"].as_array().unwrap().len(), 1);
+    assert_eq!(log["runs"][0]["tool"]["driver"]["name"], "rustc");
+    assert_eq!(log["runs"][0]["results"], Value::Array(vec![]));
+}
+
+#[test]
+fn results_and_rules() {
+    let log = with_sarif_log("let x: u8 = 'a';\n", |dcx| {
+        dcx.handle()
+            .struct_span_err(span(12, 15), "mismatched types")
+            .with_code(E0308)
+            .with_span_label(span(12, 15), "expected `u8`, found `char`")
+            .with_span_label(span(7, 9), "expected due to this")
+            .with_span_suggestion(
+                span(12, 15),
+                "use a byte literal instead",
+                "b'a'",
+                Applicability::MachineApplicable,
+            )
+            .emit();
+        dcx.handle().struct_span_warn(span(4, 5), "unused variable").with_note("a note").emit();
+    });
+    let run = &log["runs"][0];
+
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0]["id"], "E0308");
+    assert_eq!(rules[0]["shortDescription"]["text"], "Mismatched types.");
+    assert_eq!(rules[0]["helpUri"], "https://doc.rust-lang.org/error_codes/E0308.html");
+    assert_eq!(rules[1]["id"], "warning");
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+
+    let error = &results[0];
+    assert_eq!(error["ruleId"], "E0308");
+    assert_eq!(error["ruleIndex"], 0);
+    assert_eq!(error["level"], "error");
+    assert_eq!(error["message"]["text"], "mismatched types");
+    let location = &error["locations"][0];
+    assert_eq!(location["message"]["text"], "expected `u8`, found `char`");
+    assert_eq!(location["physicalLocation"]["artifactLocation"]["uri"], "test.rs");
+    assert_eq!(location["physicalLocation"]["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+    let region = &location["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 1);
+    assert_eq!(region["startColumn"], 13);
+    assert_eq!(region["endColumn"], 16);
+    assert_eq!(region["byteOffset"], 12);
+    assert_eq!(region["byteLength"], 3);
+    assert_eq!(error["relatedLocations"][0]["message"]["text"], "expected due to this");
+    let fix = &error["fixes"][0];
+    assert_eq!(fix["description"]["text"], "use a byte literal instead");
+    let replacement = &fix["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["insertedContent"]["text"], "b'a'");
+    assert_eq!(replacement["deletedRegion"]["byteOffset"], 12);
+
+    let warning = &results[1];
+    assert_eq!(warning["ruleId"], "warning");
+    assert_eq!(warning["level"], "warning");
+    assert_eq!(warning["message"]["text"], "unused variable\nnote: a note");
+}
+
+#[test]
+fn shared_log() {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let log = SarifLog::new(Box::new(Shared { data: output.clone() }));
+
+        // Like an early diagnostic context followed by the session's.
+        let early_dcx = DiagCtxt::new(Box::new(SarifEmitter::new(
+            log.clone(),
+            Lrc::clone(&sm),
+            Lrc::clone(&fallback_bundle),
+        )));
+        early_dcx.handle().warn("early warning");
+        drop(early_dcx);
+        let dcx = DiagCtxt::new(Box::new(SarifEmitter::new(log, sm, fallback_bundle)));
+        assert!(output.lock().unwrap().is_empty());
+        dcx.handle().struct_err("an error").emit();
+        drop(dcx);
+
+        // Both results end up in a single log.
+        let bytes = output.lock().unwrap();
+        let log: Value = serde_json::from_slice(&bytes).unwrap();
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["message"]["text"], "early warning");
+        assert_eq!(results[1]["message"]["text"], "an error");
+    })
+}
+
+#[test]
+fn uris() {
+    assert_eq!(path_to_uri("src/lib.rs"), "src/lib.rs");
+    assert_eq!(path_to_uri("src/a b#c.rs"), "src/a%20b%23c.rs");
+    assert_eq!(path_to_uri("/home/me/src/main.rs"), "file:///home/me/src/main.rs");
+    assert_eq!(path_to_uri("C:/src/main.rs"), "file:///C:/src/main.rs");
+}
@@ -27,7 +27,7 @@
 use rustc_span::symbol::sym;
 use tracing::trace;
 
-use crate::util;
+use crate::{passes, util};
 
 pub type Result<T> = result::Result<T, ErrorGuaranteed>;
 
@@ -499,6 +499,11 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
             // We must run `finish_diagnostics` in both cases.
             let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&compiler)));
 
+            // Codegen has been joined by now, so the report can include its time.
+            if res.is_ok() {
+                passes::write_function_times(&compiler.sess);
+            }
+
             compiler.sess.finish_diagnostics();
 
             // If error diagnostics have been emitted, we can't return an
@@ -1,5 +1,6 @@
 use std::any::Any;
 use std::ffi::OsString;
+use std::fs::File;
 use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, LazyLock};
@@ -7,7 +8,9 @@
 
 use rustc_ast as ast;
 use rustc_codegen_ssa::traits::CodegenBackend;
+use rustc_data_structures::fx::{FxIndexMap, IndexEntry};
 use rustc_data_structures::parallel;
+use rustc_data_structures::profiling::{FunctionTime, FunctionTimeCategory};
 use rustc_data_structures::steal::Steal;
 use rustc_data_structures::sync::{AppendOnlyIndexVec, FreezeLock, Lrc, OnceLock, WorkerLocal};
 use rustc_expand::base::{ExtCtxt, LintStoreExpand};
@@ -27,8 +30,10 @@
 use rustc_passes::{abi_test, input_stats, layout_test};
 use rustc_resolve::Resolver;
 use rustc_session::code_stats::VTableSizeInfo;
-use rustc_session::config::{CrateType, Input, OutFileName, OutputFilenames, OutputType};
-use rustc_session::cstore::Untracked;
+use rustc_session::config::{
+    CrateType, Input, OutFileName, OutputFilenames, OutputType, PrintKind,
+};
+use rustc_session::cstore::{CrateDepKind, Untracked};
 use rustc_session::output::{collect_crate_types, filename_for_input, find_crate_name};
 use rustc_session::search_paths::PathKind;
 use rustc_session::{Limit, Session};
@@ -677,6 +682,82 @@ pub fn write_dep_info(tcx: TyCtxt<'_>) {
     }
 }
 
+/// Prints the crates which were loaded into the current session for `--print=crate-graph`.
+pub fn print_crate_graph(tcx: TyCtxt<'_>) {
+    #[derive(serde::Serialize)]
+    struct Source {
+        dylib: Option<String>,
+        rlib: Option<String>,
+        rmeta: Option<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Crate {
+        name: String,
+        stable_crate_id: String,
+        hash: String,
+        dep_kind: &'static str,
+        source: Source,
+        edition: String,
+        cfg: Vec<String>,
+    }
+
+    let sess = tcx.sess;
+    let Some(print) = sess.opts.prints.iter().find(|print| print.kind == PrintKind::CrateGraph)
+    else {
+        return;
+    };
+
+    // Make sure that name resolution and macro expansion loaded all of the crates, including
+    // the injected ones like the panic runtime.
+    let _ = tcx.resolver_for_lowering();
+
+    let path = |path: &Option<(PathBuf, PathKind)>| {
+        path.as_ref().map(|(path, _)| path.display().to_string())
+    };
+    let cnums: Vec<_> = CStore::from_tcx(tcx).crates_untracked().collect();
+    let crates: Vec<_> = cnums
+        .into_iter()
+        .map(|cnum| {
+            let source = tcx.used_crate_source(cnum);
+            let dep_kind = match tcx.dep_kind(cnum) {
+                CrateDepKind::MacrosOnly => "macros-only",
+                CrateDepKind::Implicit => "implicit",
+                CrateDepKind::Explicit => "explicit",
+            };
+            let (edition, mut cfg) = {
+                let cstore = CStore::from_tcx(tcx);
+                let cfg: Vec<_> = cstore
+                    .crate_cfg_untracked(cnum)
+                    .map(|(name, value)| match value {
+                        Some(value) => format!("{name}=\"{value}\""),
+                        None => name.to_string(),
+                    })
+                    .collect();
+                (cstore.crate_edition_untracked(cnum), cfg)
+            };
+            cfg.sort();
+            Crate {
+                name: tcx.crate_name(cnum).to_string(),
+                stable_crate_id: format!("{:016x}", tcx.stable_crate_id(cnum).as_u64()),
+                hash: tcx.crate_hash(cnum).to_string(),
+                dep_kind,
+                source: Source {
+                    dylib: path(&source.dylib),
+                    rlib: path(&source.rlib),
+                    rmeta: path(&source.rmeta),
+                },
+                edition: edition.to_string(),
+                cfg,
+            }
+        })
+        .collect();
+
+    let mut content = serde_json::to_string_pretty(&crates).unwrap();
+    content.push('\n');
+    print.out.overwrite(&content, sess);
+}
+
 pub static DEFAULT_QUERY_PROVIDERS: LazyLock<Providers> = LazyLock::new(|| {
     let providers = &mut Providers::default();
     providers.analysis = analysis;
@@ -1130,7 +1211,10 @@ pub(crate) fn start_codegen<'tcx>(
     // This must run after monomorphization so that all generic types
     // have been instantiated.
     if tcx.sess.opts.unstable_opts.print_type_sizes {
-        tcx.sess.code_stats.print_type_sizes();
+        let opts = &tcx.sess.opts.unstable_opts;
+        tcx.sess
+            .code_stats
+            .print_type_sizes(opts.print_type_sizes_format, &opts.print_type_sizes_filter);
     }
 
     if tcx.sess.opts.unstable_opts.print_vtable_sizes {
@@ -1142,6 +1226,92 @@ pub(crate) fn start_codegen<'tcx>(
     codegen
 }
 
+/// Writes the report of `-Z self-profile-events=function-times`. This must run after codegen
+/// was joined, so that the time spent in LLVM is known.
+pub(crate) fn write_function_times(sess: &Session) {
+    sess.prof.with_profiler(|profiler| {
+        let Some(function_times) = profiler.function_times() else { return };
+        let path = function_times.report_path();
+        if let Err(error) = write_function_times_report(path, function_times.attributed_times()) {
+            sess.dcx().emit_warn(errors::FailedWritingFile { path, error });
+        }
+    });
+}
+
+fn write_function_times_report(path: &Path, times: Vec<FunctionTime>) -> io::Result<()> {
+    #[derive(Default, serde::Serialize)]
+    struct Function {
+        name: String,
+        instances: usize,
+        total: f64,
+        typeck: f64,
+        borrowck: f64,
+        mir_opt: f64,
+        mono: f64,
+        codegen: f64,
+    }
+
+    #[derive(Default, serde::Serialize)]
+    struct Instance {
+        name: String,
+        function: String,
+        total: f64,
+        mono: f64,
+        codegen: f64,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Report {
+        functions: Vec<Function>,
+        instances: Vec<Instance>,
+    }
+
+    let mut functions: FxIndexMap<String, Function> = Default::default();
+    let mut instances: FxIndexMap<String, Instance> = Default::default();
+    for time in times {
+        let secs = time.self_time.as_secs_f64();
+        let function = functions
+            .entry(time.def_path.clone())
+            .or_insert_with_key(|name| Function { name: name.clone(), ..Default::default() });
+        function.total += secs;
+        match time.category {
+            FunctionTimeCategory::Typeck => function.typeck += secs,
+            FunctionTimeCategory::Borrowck => function.borrowck += secs,
+            FunctionTimeCategory::MirOpt => function.mir_opt += secs,
+            FunctionTimeCategory::Mono => function.mono += secs,
+            FunctionTimeCategory::Codegen => function.codegen += secs,
+        }
+
+        let Some(name) = time.instance else { continue };
+        let instance = match instances.entry(name) {
+            IndexEntry::Occupied(entry) => entry.into_mut(),
+            IndexEntry::Vacant(entry) => {
+                function.instances += 1;
+                let name = entry.key().clone();
+                entry.insert(Instance { name, function: time.def_path, ..Default::default() })
+            }
+        };
+        instance.total += secs;
+        match time.category {
+            FunctionTimeCategory::Mono => instance.mono += secs,
+            FunctionTimeCategory::Codegen => instance.codegen += secs,
+            FunctionTimeCategory::Typeck
+            | FunctionTimeCategory::Borrowck
+            | FunctionTimeCategory::MirOpt => {}
+        }
+    }
+
+    // Sort from the slowest to the fastest to compile.
+    let mut functions: Vec<_> = functions.into_values().collect();
+    functions.sort_by(|a, b| b.total.total_cmp(&a.total));
+    let mut instances: Vec<_> = instances.into_values().collect();
+    instances.sort_by(|a, b| b.total.total_cmp(&a.total));
+
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut file, &Report { functions, instances })?;
+    file.flush()
+}
+
 fn get_recursion_limit(krate_attrs: &[ast::Attribute], sess: &Session) -> Limit {
     if let Some(attr) = krate_attrs
         .iter()
@@ -67,6 +67,9 @@ pub struct CStore {
 
     /// Unused externs of the crate
     unused_externs: Vec<Symbol>,
+
+    /// The old and new versions of the crate compared by `-Z api-diff`.
+    api_diff_crates: Option<(CrateNum, CrateNum)>,
 }
 
 impl std::fmt::Debug for CStore {
@@ -277,6 +280,11 @@ pub(crate) fn has_alloc_error_handler(&self) -> bool {
         self.has_alloc_error_handler
     }
 
+    /// Returns the crates passed to `-Z api-diff`, once they were loaded.
+    pub fn api_diff_crates(&self) -> Option<(CrateNum, CrateNum)> {
+        self.api_diff_crates
+    }
+
     pub fn report_unused_deps(&self, tcx: TyCtxt<'_>) {
         let json_unused_externs = tcx.sess.opts.json_unused_externs;
 
@@ -311,6 +319,7 @@ pub fn new(metadata_loader: Box<MetadataLoaderDyn>) -> CStore {
             has_global_allocator: false,
             has_alloc_error_handler: false,
             unused_externs: Vec::new(),
+            api_diff_crates: None,
         }
     }
 }
@@ -935,6 +944,19 @@ fn inject_forced_externs(&mut self) {
         }
     }
 
+    fn inject_api_diff_crates(&mut self) {
+        let Some((old, new)) = &self.sess.opts.unstable_opts.api_diff else {
+            return;
+        };
+        // Like forced externs, the compared crates are loaded even if the current crate
+        // doesn't refer to them.
+        let old = self.resolve_crate(Symbol::intern(old), DUMMY_SP, CrateDepKind::Explicit);
+        let new = self.resolve_crate(Symbol::intern(new), DUMMY_SP, CrateDepKind::Explicit);
+        if let (Some(old), Some(new)) = (old, new) {
+            self.cstore.api_diff_crates = Some((old, new));
+        }
+    }
+
     fn inject_dependency_if(
         &mut self,
         krate: CrateNum,
@@ -1050,6 +1072,7 @@ fn report_future_incompatible_deps(&self, krate: &ast::Crate) {
 
     pub fn postprocess(&mut self, krate: &ast::Crate) {
         self.inject_forced_externs();
+        self.inject_api_diff_crates();
         self.inject_profiler_runtime();
         self.inject_allocator_crate(krate);
         self.inject_panic_runtime(krate);
@@ -1481,6 +1481,11 @@ fn get_dylib_dependency_formats<'tcx>(
         )
     }
 
+    /// Decodes the `cfg` options the crate was compiled with on the command line.
+    fn get_crate_cfg(self) -> impl Iterator<Item = (Symbol, Option<Symbol>)> + 'a {
+        self.root.cfg.decode(self)
+    }
+
     fn get_missing_lang_items<'tcx>(self, tcx: TyCtxt<'tcx>) -> &'tcx [LangItem] {
         tcx.arena.alloc_from_iter(self.root.lang_items_missing.decode(self))
     }
@@ -18,6 +18,7 @@
 use rustc_session::cstore::{CrateStore, ExternCrate};
 use rustc_session::{Session, StableCrateId};
 use rustc_span::Span;
+use rustc_span::edition::Edition;
 use rustc_span::hygiene::ExpnId;
 use rustc_span::symbol::{Symbol, kw};
 
@@ -619,6 +620,23 @@ pub fn num_def_ids_untracked(&self, cnum: CrateNum) -> usize {
         self.get_crate_data(cnum).num_def_ids()
     }
 
+    /// Iterates over all the crates loaded into the current session.
+    pub fn crates_untracked(&self) -> impl Iterator<Item = CrateNum> + '_ {
+        self.iter_crate_data().map(|(cnum, _)| cnum)
+    }
+
+    pub fn crate_edition_untracked(&self, cnum: CrateNum) -> Edition {
+        self.get_crate_data(cnum).root.edition
+    }
+
+    /// Returns the `cfg` options the given crate was compiled with on the command line.
+    pub fn crate_cfg_untracked(
+        &self,
+        cnum: CrateNum,
+    ) -> impl Iterator<Item = (Symbol, Option<Symbol>)> + '_ {
+        self.get_crate_data(cnum).get_crate_cfg()
+    }
+
     pub fn get_proc_macro_quoted_span_untracked(
         &self,
         cnum: CrateNum,
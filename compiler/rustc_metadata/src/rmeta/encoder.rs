@@ -25,7 +25,7 @@
 use rustc_middle::util::common::to_readable_str;
 use rustc_middle::{bug, span_bug};
 use rustc_serialize::{Decodable, Decoder, Encodable, Encoder, opaque};
-use rustc_session::config::{CrateType, OptLevel};
+use rustc_session::config::{self, Cfg, CrateType, OptLevel};
 use rustc_span::hygiene::HygieneEncodeContext;
 use rustc_span::symbol::sym;
 use rustc_span::{
@@ -610,6 +610,8 @@ macro_rules! stat {
         let (crate_deps, dylib_dependency_formats) =
             stat!("dep", || (self.encode_crate_deps(), self.encode_dylib_dependency_formats()));
 
+        let cfg = stat!("cfg", || self.encode_cfg());
+
         let lib_features = stat!("lib-features", || self.encode_lib_features());
 
         let stability_implications =
@@ -722,6 +724,7 @@ macro_rules! stat {
                 symbol_mangling_version: tcx.sess.opts.get_symbol_mangling_version(),
 
                 crate_deps,
+                cfg,
                 dylib_dependency_formats,
                 lib_features,
                 stability_implications,
@@ -2006,6 +2009,16 @@ fn encode_crate_deps(&mut self) -> LazyArray<CrateDep> {
         self.lazy_array(deps.iter().map(|(_, dep)| dep))
     }
 
+    fn encode_cfg(&mut self) -> LazyArray<(Symbol, Option<Symbol>)> {
+        // Only the `cfg`s passed with `--cfg` are recorded. Those rustc sets itself follow from
+        // the target and the other options, and the `target_feature`s alone can number hundreds.
+        let sess = self.tcx.sess;
+        let builtin_cfg = config::build_configuration(sess, Cfg::default());
+        self.lazy_array(sess.psess.config.iter().copied().filter(|&(name, value)| {
+            name != sym::target_feature && !builtin_cfg.contains(&(name, value))
+        }))
+    }
+
     fn encode_lib_features(&mut self) -> LazyArray<(Symbol, FeatureStability)> {
         empty_proc_macro!(self);
         let tcx = self.tcx;
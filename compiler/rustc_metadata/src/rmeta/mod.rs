@@ -56,7 +56,7 @@ pub(crate) fn rustc_version(cfg_version: &'static str) -> String {
 /// Metadata encoding version.
 /// N.B., increment this if you change the format of metadata such that
 /// the rustc version can't be found to compare with `rustc_version()`.
-const METADATA_VERSION: u8 = 9;
+const METADATA_VERSION: u8 = 10;
 
 /// Metadata header which includes `METADATA_VERSION`.
 ///
@@ -256,6 +256,9 @@ pub(crate) struct CrateRoot {
     has_default_lib_allocator: bool,
 
     crate_deps: LazyArray<CrateDep>,
+    /// The `cfg` options the crate was compiled with on the command line, as used by
+    /// `--print=crate-graph`.
+    cfg: LazyArray<(Symbol, Option<Symbol>)>,
     dylib_dependency_formats: LazyArray<Option<LinkagePreference>>,
     lib_features: LazyArray<(Symbol, FeatureStability)>,
     stability_implications: LazyArray<(Symbol, Symbol)>,
@@ -195,6 +195,8 @@ mod simplify_branches : SimplifyConstCondition {
     mod validate : Validator;
 }
 
+pub use validate::validate_body;
+
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
 
 pub fn provide(providers: &mut Providers) {
@@ -1,5 +1,7 @@
 //! Validates the MIR to ensure that invariants are upheld.
 
+use std::cell::RefCell;
+
 use rustc_abi::{ExternAbi, FIRST_VARIANT, Size};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::LangItem;
@@ -33,6 +35,25 @@ pub(super) struct Validator {
 
 impl<'tcx> crate::MirPass<'tcx> for Validator {
     fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        self.check_body(tcx, body, None);
+    }
+}
+
+/// Validates a body that doesn't come out of the MIR pipeline, e.g. one that was modified by a
+/// tool, and returns the invariants it breaks instead of ICEing.
+pub fn validate_body<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> Vec<(Location, String)> {
+    let failures = RefCell::new(Vec::new());
+    Validator { when: "validate_body".to_string() }.check_body(tcx, body, Some(&failures));
+    failures.into_inner()
+}
+
+impl Validator {
+    fn check_body<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        body: &Body<'tcx>,
+        failures: Option<&RefCell<Vec<(Location, String)>>>,
+    ) {
         // FIXME(JakobDegen): These bodies never instantiated in codegend anyway, so it's not
         // terribly important that they pass the validator. However, I think other passes might
         // still see them, in which case they might be surprised. It would probably be better if we
@@ -70,6 +91,7 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
             reachable_blocks: traversal::reachable_as_bitset(body),
             value_cache: FxHashSet::default(),
             can_unwind,
+            failures,
         };
         cfg_checker.visit_body(body);
         cfg_checker.check_cleanup_control_flow();
@@ -102,11 +124,17 @@ struct CfgChecker<'a, 'tcx> {
     // If `false`, then the MIR must not contain `UnwindAction::Continue` or
     // `TerminatorKind::Resume`.
     can_unwind: bool,
+    /// If set, failures are collected here instead of causing an ICE.
+    failures: Option<&'a RefCell<Vec<(Location, String)>>>,
 }
 
 impl<'a, 'tcx> CfgChecker<'a, 'tcx> {
     #[track_caller]
     fn fail(&self, location: Location, msg: impl AsRef<str>) {
+        if let Some(failures) = self.failures {
+            failures.borrow_mut().push((location, msg.as_ref().to_string()));
+            return;
+        }
         // We might see broken MIR when other errors have already occurred.
         assert!(
             self.tcx.dcx().has_errors().is_some(),
@@ -14,6 +14,9 @@ monomorphize_abi_error_unsupported_vector_type_def =
   this function definition uses a SIMD vector type that is not currently supported with the chosen ABI
   .label = function defined here
 
+monomorphize_couldnt_dump_mono_bloat =
+    unexpected error occurred while dumping the monomorphization bloat report: {$error}
+
 monomorphize_couldnt_dump_mono_stats =
     unexpected error occurred while dumping monomorphization stats: {$error}
 
@@ -205,6 +205,7 @@
 //! this is not implemented however: a mono item will be produced
 //! regardless of whether it is actually needed or not.
 
+use std::collections::VecDeque;
 use std::path::PathBuf;
 
 use rustc_data_structures::fx::FxIndexMap;
@@ -256,16 +257,19 @@ struct SharedState<'tcx> {
 }
 
 pub(crate) struct UsageMap<'tcx> {
-    // Maps every mono item to the mono items used by it.
-    used_map: UnordMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
+    // The items the collection started from.
+    roots: Vec<MonoItem<'tcx>>,
+
+    // Maps every mono item to the mono items used by it, along with the span of the use.
+    used_map: UnordMap<MonoItem<'tcx>, Vec<Spanned<MonoItem<'tcx>>>>,
 
     // Maps every mono item to the mono items that use it.
     user_map: UnordMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
 }
 
 impl<'tcx> UsageMap<'tcx> {
-    fn new() -> UsageMap<'tcx> {
-        UsageMap { used_map: Default::default(), user_map: Default::default() }
+    fn new(roots: Vec<MonoItem<'tcx>>) -> UsageMap<'tcx> {
+        UsageMap { roots, used_map: Default::default(), user_map: Default::default() }
     }
 
     fn record_used<'a>(&mut self, user_item: MonoItem<'tcx>, used_items: &'a MonoItems<'tcx>)
@@ -276,13 +280,42 @@ fn record_used<'a>(&mut self, user_item: MonoItem<'tcx>, used_items: &'a MonoIte
             self.user_map.entry(used_item).or_default().push(user_item);
         }
 
-        assert!(self.used_map.insert(user_item, used_items.items().collect()).is_none());
+        let used_items = used_items.items.iter().map(|(&item, &span)| respan(span, item)).collect();
+        assert!(self.used_map.insert(user_item, used_items).is_none());
     }
 
     pub(crate) fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
         self.user_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
     }
 
+    /// Returns the span where `user_item` uses `used_item`, e.g. the call site.
+    pub(crate) fn get_use_span(
+        &self,
+        user_item: MonoItem<'tcx>,
+        used_item: MonoItem<'tcx>,
+    ) -> Option<Span> {
+        let used_items = self.used_map.get(&user_item)?;
+        used_items.iter().find(|used| used.node == used_item).map(|used| used.span)
+    }
+
+    /// Returns a shortest chain of uses which leads from a root to each item, by mapping every
+    /// item to the item before it in its chain, along with the span of the use. Roots are not
+    /// part of the returned map.
+    pub(crate) fn shortest_use_chains(&self) -> UnordMap<MonoItem<'tcx>, Spanned<MonoItem<'tcx>>> {
+        let mut chains = UnordMap::default();
+        let mut visited: UnordSet<_> = self.roots.iter().copied().collect();
+        let mut queue: VecDeque<_> = self.roots.iter().copied().collect();
+        while let Some(item) = queue.pop_front() {
+            for used in self.used_map.get(&item).into_iter().flatten() {
+                if visited.insert(used.node) {
+                    chains.insert(used.node, respan(used.span, item));
+                    queue.push_back(used.node);
+                }
+            }
+        }
+        chains
+    }
+
     /// Internally iterate over all inlined items used by `item`.
     pub(crate) fn for_each_inlined_used_item<F>(
         &self,
@@ -294,9 +327,9 @@ pub(crate) fn for_each_inlined_used_item<F>(
     {
         let used_items = self.used_map.get(&item).unwrap();
         for used_item in used_items.iter() {
-            let is_inlined = used_item.instantiation_mode(tcx) == InstantiationMode::LocalCopy;
+            let is_inlined = used_item.node.instantiation_mode(tcx) == InstantiationMode::LocalCopy;
             if is_inlined {
-                f(*used_item);
+                f(used_item.node);
             }
         }
     }
@@ -1609,7 +1642,7 @@ pub(crate) fn collect_crate_mono_items<'tcx>(
     let mut state = SharedState {
         visited: MTLock::new(UnordSet::default()),
         mentioned: MTLock::new(UnordSet::default()),
-        usage_map: MTLock::new(UsageMap::new()),
+        usage_map: MTLock::new(UsageMap::new(roots.clone())),
     };
     let recursion_limit = tcx.recursion_limit();
 
@@ -43,6 +43,12 @@ pub(crate) struct SymbolAlreadyDefined {
     pub symbol: String,
 }
 
+#[derive(Diagnostic)]
+#[diag(monomorphize_couldnt_dump_mono_bloat)]
+pub(crate) struct CouldntDumpMonoBloat {
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(monomorphize_couldnt_dump_mono_stats)]
 pub(crate) struct CouldntDumpMonoStats {
@@ -117,12 +117,15 @@
 use rustc_middle::util::Providers;
 use rustc_session::CodegenUnits;
 use rustc_session::config::{DumpMonoStatsFormat, SwitchWithOptPath};
+use rustc_span::Span;
 use rustc_span::symbol::Symbol;
 use rustc_target::spec::SymbolVisibility;
 use tracing::debug;
 
 use crate::collector::{self, MonoItemCollectionStrategy, UsageMap};
-use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode};
+use crate::errors::{
+    CouldntDumpMonoBloat, CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode,
+};
 
 struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -1184,6 +1187,15 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
         }
     }
 
+    // Output the generic instantiations along with the uses that required them
+    if let SwitchWithOptPath::Enabled(ref path) = tcx.sess.opts.unstable_opts.dump_mono_bloat {
+        if let Err(err) =
+            dump_mono_bloat(tcx, &items, &usage_map, path, tcx.crate_name(LOCAL_CRATE))
+        {
+            tcx.dcx().emit_fatal(CouldntDumpMonoBloat { error: err.to_string() });
+        }
+    }
+
     if tcx.sess.opts.unstable_opts.print_mono_items.is_some() {
         let mut item_to_cgus: UnordMap<_, Vec<_>> = Default::default();
 
@@ -1315,6 +1327,117 @@ struct MonoItem {
     Ok(())
 }
 
+/// Outputs the instantiations of generic functions, grouped by their definition, to a file in the
+/// given output directory. For each instantiation, the report includes its size estimate, the
+/// items using it, and a shortest chain of uses which leads to it from a collection root.
+fn dump_mono_bloat<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+    output_directory: &Option<PathBuf>,
+    crate_name: Symbol,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    let filename = format!("{crate_name}.mono_bloat.json");
+    let output_path = output_directory.join(&filename);
+    let file = File::create_buffered(&output_path)?;
+
+    #[derive(serde::Serialize)]
+    struct Use {
+        item: String,
+        span: Option<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Instance {
+        name: String,
+        size_estimate: usize,
+        users: Vec<Use>,
+        chain: Vec<Use>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Definition {
+        name: String,
+        instantiation_count: usize,
+        total_estimate: usize,
+        instances: Vec<Instance>,
+    }
+
+    let item_name = |item: MonoItem<'tcx>| with_no_trimmed_paths!(item.to_string());
+    let span_name = |span: Span| {
+        (!span.is_dummy()).then(|| tcx.sess.source_map().span_to_embeddable_string(span))
+    };
+    let chains = usage_map.shortest_use_chains();
+
+    // Gather instantiated generic functions grouped by def_id
+    let mut items_per_def_id: FxIndexMap<_, Vec<_>> = Default::default();
+    for &item in items.iter().filter(|item| item.is_generic_fn()) {
+        items_per_def_id.entry(item.def_id()).or_default().push(item);
+    }
+
+    let mut definitions: Vec<_> = items_per_def_id
+        .into_iter()
+        .map(|(def_id, items)| {
+            let mut instances: Vec<_> = items
+                .into_iter()
+                .map(|item| {
+                    let mut users: Vec<_> = usage_map
+                        .get_user_items(item)
+                        .iter()
+                        .map(|&user| Use {
+                            item: item_name(user),
+                            span: usage_map.get_use_span(user, item).and_then(span_name),
+                        })
+                        .collect();
+                    users.sort_by(|a, b| a.item.cmp(&b.item));
+
+                    // Walk the chain backwards from the item to its root
+                    let mut chain = Vec::new();
+                    let mut current = item;
+                    while let Some(user) = chains.get(&current) {
+                        chain.push(Use { item: item_name(current), span: span_name(user.span) });
+                        current = user.node;
+                    }
+                    chain.push(Use { item: item_name(current), span: None });
+                    chain.reverse();
+
+                    Instance {
+                        name: item_name(item),
+                        size_estimate: item.size_estimate(tcx),
+                        users,
+                        chain,
+                    }
+                })
+                .collect();
+            instances.sort_by(|a, b| {
+                b.size_estimate.cmp(&a.size_estimate).then_with(|| a.name.cmp(&b.name))
+            });
+
+            Definition {
+                name: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+                instantiation_count: instances.len(),
+                total_estimate: instances.iter().map(|instance| instance.size_estimate).sum(),
+                instances,
+            }
+        })
+        .collect();
+
+    // Output definitions sorted by total instantiated size, from heaviest to lightest
+    definitions
+        .sort_by(|a, b| b.total_estimate.cmp(&a.total_estimate).then_with(|| a.name.cmp(&b.name)));
+
+    serde_json::to_writer(file, &definitions)?;
+
+    Ok(())
+}
+
 pub(crate) fn provide(providers: &mut Providers) {
     providers.collect_and_partition_mono_items = collect_and_partition_mono_items;
 
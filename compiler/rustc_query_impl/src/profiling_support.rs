@@ -3,10 +3,15 @@
 
 use measureme::{StringComponent, StringId};
 use rustc_data_structures::fx::FxHashMap;
-use rustc_data_structures::profiling::SelfProfiler;
-use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LOCAL_CRATE, LocalDefId};
+use rustc_data_structures::profiling::{
+    CodegenUnitItem, FunctionTime, FunctionTimeCategory, FunctionTimes, SelfProfiler,
+};
+use rustc_hir::def_id::{CrateNum, DefId, DefIdSet, DefIndex, LOCAL_CRATE, LocalDefId};
 use rustc_hir::definitions::DefPathData;
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::mir::mono::{CodegenUnit, MonoItem};
+use rustc_middle::query::erase::restore;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{Instance, TyCtxt};
 use rustc_query_system::query::QueryCache;
 
 pub(crate) struct QueryKeyStringCache {
@@ -259,4 +264,101 @@ pub fn alloc_self_profile_query_strings(tcx: TyCtxt<'_>) {
     for alloc in super::ALLOC_SELF_PROFILE_QUERY_STRINGS.iter() {
         alloc(tcx, &mut string_cache)
     }
+
+    tcx.prof.with_profiler(|profiler| {
+        if let Some(function_times) = profiler.function_times() {
+            record_function_times(tcx, function_times);
+        }
+    });
+}
+
+/// Attributes the time of the queries which work on a single function to that function,
+/// and records the items of the codegen units, for `-Z self-profile-events=function-times`.
+fn record_function_times(tcx: TyCtxt<'_>, function_times: &FunctionTimes) {
+    let caches = &tcx.query_system.caches;
+
+    record_function_times_for_query_cache(
+        tcx,
+        function_times,
+        FunctionTimeCategory::Typeck,
+        &caches.typeck,
+        |def_id| Some((def_id.to_def_id(), None)),
+    );
+    record_function_times_for_query_cache(
+        tcx,
+        function_times,
+        FunctionTimeCategory::Borrowck,
+        &caches.mir_borrowck,
+        |def_id| Some((def_id.to_def_id(), None)),
+    );
+    // The MIR of other crates is only decoded, not optimized.
+    record_function_times_for_query_cache(
+        tcx,
+        function_times,
+        FunctionTimeCategory::MirOpt,
+        &caches.optimized_mir,
+        |def_id| def_id.is_local().then_some((*def_id, None)),
+    );
+    record_function_times_for_query_cache(
+        tcx,
+        function_times,
+        FunctionTimeCategory::Mono,
+        &caches.items_of_instance,
+        |&(instance, _)| Some((instance.def_id(), Some(instance))),
+    );
+
+    // Don't partition the mono items just for the report, e.g. when only checking the crate.
+    if let Some((value, _)) = caches.collect_and_partition_mono_items.lookup(&()) {
+        let (_, codegen_units) = restore::<(&DefIdSet, &[CodegenUnit<'_>])>(value);
+        for cgu in codegen_units {
+            let items = cgu
+                .items()
+                .iter()
+                .map(|(item, data)| {
+                    let instance = match *item {
+                        MonoItem::Fn(instance) => with_no_trimmed_paths!(instance.to_string()),
+                        MonoItem::Static(_) | MonoItem::GlobalAsm(_) => {
+                            with_no_trimmed_paths!(item.to_string())
+                        }
+                    };
+                    CodegenUnitItem {
+                        def_path: with_no_trimmed_paths!(tcx.def_path_str(item.def_id())),
+                        instance,
+                        size_estimate: data.size_estimate,
+                    }
+                })
+                .collect();
+            function_times.record_codegen_unit(cgu.name().to_string(), items);
+        }
+    }
+}
+
+fn record_function_times_for_query_cache<'tcx, C>(
+    tcx: TyCtxt<'tcx>,
+    function_times: &FunctionTimes,
+    category: FunctionTimeCategory,
+    query_cache: &C,
+    function: impl Fn(&C::Key) -> Option<(DefId, Option<Instance<'tcx>>)>,
+) where
+    C: QueryCache,
+{
+    // Printing the functions might invoke queries, so copy the keys out of the cache first,
+    // like `alloc_self_profile_query_strings_for_query_cache` does.
+    let mut query_keys_and_indices = Vec::new();
+    query_cache.iter(&mut |k, _, i| query_keys_and_indices.push((*k, i)));
+
+    for (query_key, dep_node_index) in query_keys_and_indices {
+        let Some(self_time) = function_times.query_self_time(dep_node_index.into()) else {
+            continue;
+        };
+        let Some((def_id, instance)) = function(&query_key) else {
+            continue;
+        };
+        function_times.record(FunctionTime {
+            def_path: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+            instance: instance.map(|instance| with_no_trimmed_paths!(instance.to_string())),
+            category,
+            self_time,
+        });
+    }
 }
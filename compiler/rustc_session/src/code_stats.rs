@@ -6,6 +6,8 @@
 use rustc_span::Symbol;
 use rustc_span::def_id::DefId;
 
+use crate::config::PrintTypeSizesFormat;
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
     pub name: Option<Symbol>,
@@ -51,6 +53,32 @@ pub struct FieldInfo {
     pub type_name: Option<Symbol>,
 }
 
+/// How the discriminant of an enum is stored.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum DiscrEncoding {
+    /// In a separate tag field.
+    Direct,
+    /// In otherwise invalid values of a field of one of the variants.
+    Niche,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DiscrInfo {
+    pub encoding: DiscrEncoding,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// The largest niche of a type, i.e. the invalid values of one of its fields
+/// which an enclosing enum can use to store its discriminant.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    /// Number of invalid values that are available.
+    pub available: u128,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum DataTypeKind {
     Struct,
@@ -68,6 +96,8 @@ pub struct TypeSizeInfo {
     pub overall_size: u64,
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
+    pub discr: Option<DiscrInfo>,
+    pub largest_niche: Option<NicheInfo>,
     pub variants: Vec<VariantInfo>,
 }
 
@@ -105,6 +135,8 @@ pub fn record_type_size<S: ToString>(
         overall_size: Size,
         packed: bool,
         opt_discr_size: Option<Size>,
+        discr: Option<DiscrInfo>,
+        largest_niche: Option<NicheInfo>,
         mut variants: Vec<VariantInfo>,
     ) {
         // Sort variants so the largest ones are shown first. A stable sort is
@@ -122,6 +154,8 @@ pub fn record_type_size<S: ToString>(
             overall_size: overall_size.bytes(),
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
+            discr,
+            largest_niche,
             variants,
         };
         self.type_sizes.borrow_mut().insert(info);
@@ -135,17 +169,29 @@ pub fn record_vtable_size(&self, trait_did: DefId, trait_name: &str, info: VTabl
         );
     }
 
-    pub fn print_type_sizes(&self) {
+    /// Prints the recorded type sizes, restricted to the types whose description contains
+    /// one of the strings in `filter`, unless it is empty.
+    pub fn print_type_sizes(&self, format: PrintTypeSizesFormat, filter: &[String]) {
         let type_sizes = self.type_sizes.borrow();
         // We will soon sort, so the initial order does not matter.
         #[allow(rustc::potential_query_instability)]
-        let mut sorted: Vec<_> = type_sizes.iter().collect();
+        let mut sorted: Vec<_> = type_sizes
+            .iter()
+            .filter(|info| {
+                filter.is_empty() || filter.iter().any(|f| info.type_description.contains(f))
+            })
+            .collect();
 
         // Primary sort: large-to-small.
         // Secondary sort: description (dictionary order)
         sorted.sort_by_key(|info| (cmp::Reverse(info.overall_size), &info.type_description));
 
         for info in sorted {
+            if format == PrintTypeSizesFormat::Json {
+                print_type_size_json(info);
+                continue;
+            }
+
             let TypeSizeInfo { type_description, overall_size, align, kind, variants, .. } = info;
             println!(
                 "print-type-size type: `{type_description}`: {overall_size} bytes, alignment: {align} bytes"
@@ -268,3 +314,130 @@ pub fn print_vtable_sizes(&self, crate_name: Symbol) {
         }
     }
 }
+
+/// Prints the layout of a single type as one line of JSON.
+///
+/// Like in the text format, variant sizes exclude the discriminant, which is reported
+/// on its own. Padding is reported as a separate number for each field.
+fn print_type_size_json(info: &TypeSizeInfo) {
+    #[derive(serde::Serialize)]
+    struct Type<'a> {
+        #[serde(rename = "type")]
+        description: &'a str,
+        kind: &'static str,
+        size: u64,
+        align: u64,
+        packed: bool,
+        discriminant: Option<Discriminant>,
+        largest_niche: Option<Niche>,
+        variants: Vec<Variant>,
+        end_padding: u64,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Discriminant {
+        encoding: &'static str,
+        offset: u64,
+        size: u64,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Niche {
+        offset: u64,
+        size: u64,
+        available: u128,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Variant {
+        name: Option<String>,
+        /// The size of the variant, without the discriminant.
+        size: u64,
+        /// Whether `size` is only a lower bound, because the variant is unsized.
+        min_size: bool,
+        align: u64,
+        fields: Vec<Field>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Field {
+        kind: String,
+        name: String,
+        #[serde(rename = "type")]
+        type_name: Option<String>,
+        offset: u64,
+        size: u64,
+        align: u64,
+        /// Padding between the previous field, or the discriminant, and this one.
+        padding: u64,
+    }
+
+    let discr_size = info.opt_discr_size.unwrap_or(0);
+    let mut max_variant_size = discr_size;
+    let variants = info
+        .variants
+        .iter()
+        .map(|variant| {
+            max_variant_size = cmp::max(max_variant_size, variant.size);
+
+            // Like the text format, compute the padding in order of increasing offset.
+            let mut fields = variant.fields.clone();
+            fields.sort_by_key(|f| (f.offset, f.size));
+            let mut min_offset = discr_size;
+            let fields = fields
+                .into_iter()
+                .map(|field| {
+                    let padding = field.offset.saturating_sub(min_offset);
+                    min_offset = field.offset + field.size;
+                    Field {
+                        kind: field.kind.to_string(),
+                        name: field.name.to_string(),
+                        type_name: field.type_name.map(|name| name.to_string()),
+                        offset: field.offset,
+                        size: field.size,
+                        align: field.align,
+                        padding,
+                    }
+                })
+                .collect();
+
+            Variant {
+                name: variant.name.map(|name| name.to_string()),
+                size: variant.size - discr_size,
+                min_size: variant.kind == SizeKind::Min,
+                align: variant.align,
+                fields,
+            }
+        })
+        .collect();
+
+    let ty = Type {
+        description: &info.type_description,
+        kind: match info.kind {
+            DataTypeKind::Struct => "struct",
+            DataTypeKind::Union => "union",
+            DataTypeKind::Enum => "enum",
+            DataTypeKind::Closure => "closure",
+            DataTypeKind::Coroutine => "coroutine",
+        },
+        size: info.overall_size,
+        align: info.align,
+        packed: info.packed,
+        discriminant: info.discr.map(|discr| Discriminant {
+            encoding: match discr.encoding {
+                DiscrEncoding::Direct => "direct",
+                DiscrEncoding::Niche => "niche",
+            },
+            offset: discr.offset,
+            size: discr.size,
+        }),
+        largest_niche: info.largest_niche.map(|niche| Niche {
+            offset: niche.offset,
+            size: niche.size,
+            available: niche.available,
+        }),
+        variants,
+        end_padding: info.overall_size.saturating_sub(max_variant_size),
+    };
+    println!("{}", serde_json::to_string(&ty).unwrap());
+}
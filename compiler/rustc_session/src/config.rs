@@ -639,6 +639,8 @@ pub enum ErrorOutputType {
         json_rendered: HumanReadableErrorType,
         color_config: ColorConfig,
     },
+    /// A single SARIF log, as consumed by code scanning tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -825,6 +827,7 @@ pub enum PrintKind {
     Sysroot,
     TargetLibdir,
     CrateName,
+    CrateGraph,
     Cfg,
     CheckCfg,
     CallingConventions,
@@ -1784,10 +1787,17 @@ pub fn parse_error_format(
             Some("pretty-json") => {
                 ErrorOutputType::Json { pretty: true, json_rendered, color_config: json_color }
             }
+            Some("sarif") => ErrorOutputType::Sarif,
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short, color),
             Some("human-unicode") => {
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Unicode, color)
             }
+            Some("human-github") => {
+                ErrorOutputType::HumanReadable(HumanReadableErrorType::GitHubActions, color)
+            }
+            Some("human-gitlab") => {
+                ErrorOutputType::HumanReadable(HumanReadableErrorType::GitLabCodeQuality, color)
+            }
             Some(arg) => {
                 early_dcx.abort_if_error_and_set_error_format(ErrorOutputType::HumanReadable(
                     HumanReadableErrorType::Default,
@@ -1795,7 +1805,8 @@ pub fn parse_error_format(
                 ));
                 early_dcx.early_fatal(format!(
                     "argument for `--error-format` must be `human`, `human-annotate-rs`, \
-                    `human-unicode`, `json`, `pretty-json` or `short` (instead was `{arg}`)"
+                    `human-unicode`, `human-github`, `human-gitlab`, `json`, `pretty-json`, \
+                    `sarif` or `short` (instead was `{arg}`)"
                 ))
             }
         }
@@ -1855,9 +1866,12 @@ fn check_error_format_stability(
     }
     let format = match format {
         ErrorOutputType::Json { pretty: true, .. } => "pretty-json",
+        ErrorOutputType::Sarif => "sarif",
         ErrorOutputType::HumanReadable(format, _) => match format {
             HumanReadableErrorType::AnnotateSnippet => "human-annotate-rs",
             HumanReadableErrorType::Unicode => "human-unicode",
+            HumanReadableErrorType::GitHubActions => "human-github",
+            HumanReadableErrorType::GitLabCodeQuality => "human-gitlab",
             _ => return,
         },
         _ => return,
@@ -1973,6 +1987,7 @@ fn collect_print_requests(
         ("cfg", PrintKind::Cfg),
         ("check-cfg", PrintKind::CheckCfg),
         ("code-models", PrintKind::CodeModels),
+        ("crate-graph", PrintKind::CrateGraph),
         ("crate-name", PrintKind::CrateName),
         ("deployment-target", PrintKind::DeploymentTarget),
         ("file-names", PrintKind::FileNames),
@@ -2032,6 +2047,16 @@ fn collect_print_requests(
                     );
                 }
             }
+            Some((_, PrintKind::CrateGraph)) => {
+                if unstable_opts.unstable_options {
+                    PrintKind::CrateGraph
+                } else {
+                    early_dcx.early_fatal(
+                        "the `-Z unstable-options` flag must also be passed to \
+                         enable the crate-graph print option",
+                    );
+                }
+            }
             Some(&(_, print_kind)) => print_kind,
             None => {
                 let prints =
@@ -3184,6 +3209,15 @@ pub fn extension(self) -> &'static str {
     }
 }
 
+/// Which format to use for `-Z print-type-sizes`
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum PrintTypeSizesFormat {
+    /// Human-readable lines prefixed with `print-type-size`
+    Text,
+    /// One JSON object per type and line
+    Json,
+}
+
 /// `-Z patchable-function-entry` representation - how many nops to put before and after function
 /// entry.
 #[derive(Clone, Copy, PartialEq, Hash, Debug, Default)]
@@ -372,6 +372,7 @@ mod desc {
         "a comma-separated list of strings, with elements beginning with + or -";
     pub(crate) const parse_comma_list: &str = "a comma-separated list of strings";
     pub(crate) const parse_opt_comma_list: &str = parse_comma_list;
+    pub(crate) const parse_api_diff: &str = "two crate names separated by a comma";
     pub(crate) const parse_number: &str = "a number";
     pub(crate) const parse_opt_number: &str = parse_number;
     pub(crate) const parse_frame_pointer: &str = "one of `true`/`yes`/`on`, `false`/`no`/`off`, or (with -Zunstable-options) `non-leaf` or `always`";
@@ -397,6 +398,7 @@ mod desc {
     pub(crate) const parse_strip: &str = "either `none`, `debuginfo`, or `symbols`";
     pub(crate) const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavorCli::one_of();
     pub(crate) const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
+    pub(crate) const parse_print_type_sizes_format: &str = "`text` (default) or `json`";
     pub(crate) const parse_instrument_coverage: &str = parse_bool;
     pub(crate) const parse_coverage_options: &str =
         "`block` | `branch` | `condition` | `mcdc` | `no-mir-spans`";
@@ -661,6 +663,16 @@ pub(crate) fn parse_opt_comma_list(slot: &mut Option<Vec<String>>, v: Option<&st
         }
     }
 
+    pub(crate) fn parse_api_diff(slot: &mut Option<(String, String)>, v: Option<&str>) -> bool {
+        match v.and_then(|s| s.split_once(',')) {
+            Some((old, new)) if !old.is_empty() && !new.is_empty() && !new.contains(',') => {
+                *slot = Some((old.to_string(), new.to_string()));
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub(crate) fn parse_threads(slot: &mut usize, v: Option<&str>) -> bool {
         let ret = match v.and_then(|s| s.parse().ok()) {
             Some(0) => {
@@ -1000,6 +1012,24 @@ pub(crate) fn parse_dump_mono_stats(slot: &mut DumpMonoStatsFormat, v: Option<&s
         }
     }
 
+    pub(crate) fn parse_print_type_sizes_format(
+        slot: &mut PrintTypeSizesFormat,
+        v: Option<&str>,
+    ) -> bool {
+        match v {
+            None => true,
+            Some("text") => {
+                *slot = PrintTypeSizesFormat::Text;
+                true
+            }
+            Some("json") => {
+                *slot = PrintTypeSizesFormat::Json;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
     pub(crate) fn parse_instrument_coverage(
         slot: &mut InstrumentCoverage,
         v: Option<&str>,
@@ -1683,6 +1713,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "only allow the listed language features to be enabled in code (comma separated)"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata (default: no)"),
+    api_diff: Option<(String, String)> = (None, parse_api_diff, [UNTRACKED],
+        "compare the public API of the crates passed as `--extern OLD` and `--extern NEW`, \
+        print the changes as JSON, and stop (format: `OLD,NEW`)"),
     assert_incr_state: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "assert that the incremental cache is in given state: \
          either `loaded` or `not-loaded`."),
@@ -1750,6 +1783,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "exclude the pass number when dumping MIR (used in tests) (default: no)"),
     dump_mir_graphviz: bool = (false, parse_bool, [UNTRACKED],
         "in addition to `.mir` files, create graphviz `.dot` files (default: no)"),
+    dump_mono_bloat: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "output the instantiations of generic functions along with the uses that required them"),
     dump_mono_stats: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
         parse_switch_with_opt_path, [UNTRACKED],
         "output statistics about monomorphization collection"),
@@ -1798,6 +1834,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "whether each function should go in its own section"),
     future_incompat_test: bool = (false, parse_bool, [UNTRACKED],
         "forces all lints to be future incompatible, used for internal testing (default: no)"),
+    gitlab_code_quality_report: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "the GitLab code quality report that `--error-format=human-gitlab` adds issues to \
+        (default: `gl-code-quality-report.json`)"),
     graphviz_dark_mode: bool = (false, parse_bool, [UNTRACKED],
         "use dark-themed colors in graphviz output (default: no)"),
     graphviz_font: String = ("Courier, monospace".to_string(), parse_string, [UNTRACKED],
@@ -1935,6 +1974,8 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "run LLVM in non-parallel mode (while keeping codegen-units and ThinLTO)"),
     no_profiler_runtime: bool = (false, parse_no_value, [TRACKED],
         "prevent automatic injection of the profiler_builtins crate"),
+    no_steal_thir: bool = (false, parse_bool, [UNTRACKED],
+        "don't steal the THIR when you're done with it; useful for rustc drivers (default: no)"),
     no_trait_vptr: bool = (false, parse_no_value, [TRACKED],
         "disable generation of trait vptr in vtable for upcasting"),
     no_unique_section_names: bool = (false, parse_bool, [TRACKED],
@@ -1983,6 +2024,12 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
          Note that this overwrites the effect `-Clink-dead-code` has on collection!"),
     print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
         "print layout information for each type encountered (default: no)"),
+    print_type_sizes_filter: Vec<String> = (Vec::new(), parse_comma_list, [UNTRACKED],
+        "only print layout information for types whose name contains one of these \
+        comma-separated strings, for -Z print-type-sizes (default: all types)"),
+    print_type_sizes_format: PrintTypeSizesFormat = (PrintTypeSizesFormat::Text,
+        parse_print_type_sizes_format, [UNTRACKED],
+        "the format to use for -Z print-type-sizes (`text` (default) or `json`)"),
     print_vtable_sizes: bool = (false, parse_bool, [UNTRACKED],
         "print size comparison between old and new vtable layouts (default: no)"),
     proc_macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
@@ -2048,7 +2095,8 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "specify the events recorded by the self profiler;
         for example: `-Z self-profile-events=default,query-keys`
         all options: none, all, default, generic-activity, query-provider, query-cache-hit
-                     query-blocked, incr-cache-load, incr-result-hashing, query-keys, function-args, args, llvm, artifact-sizes"),
+                     query-blocked, incr-cache-load, incr-result-hashing, query-keys, function-args, args, llvm, artifact-sizes,
+                     function-times"),
     share_generics: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "make the current crate share its generic instantiations"),
     shell_argfiles: bool = (false, parse_bool, [UNTRACKED],
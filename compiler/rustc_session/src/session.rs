@@ -10,14 +10,16 @@
 use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
 use rustc_data_structures::profiling::{SelfProfiler, SelfProfilerRef};
 use rustc_data_structures::sync::{
-    DynSend, DynSync, Lock, Lrc, MappedReadGuard, ReadGuard, RwLock,
+    DynSend, DynSync, Lock, Lrc, MappedReadGuard, OnceLock, ReadGuard, RwLock,
 };
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitter;
+use rustc_errors::ci::CiAnnotations;
 use rustc_errors::codes::*;
 use rustc_errors::emitter::{
     DynEmitter, HumanEmitter, HumanReadableErrorType, OutputTheme, stderr_destination,
 };
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::sarif::{SarifEmitter, SarifLog};
 use rustc_errors::{
     Diag, DiagCtxt, DiagCtxtHandle, DiagMessage, Diagnostic, ErrorGuaranteed, FatalAbort,
     FluentBundle, LazyFallbackBundle, TerminalUrl, fallback_fluent_bundle,
@@ -35,7 +37,9 @@
 };
 
 use crate::code_stats::CodeStats;
-pub use crate::code_stats::{DataTypeKind, FieldInfo, FieldKind, SizeKind, VariantInfo};
+pub use crate::code_stats::{
+    DataTypeKind, DiscrEncoding, DiscrInfo, FieldInfo, FieldKind, NicheInfo, SizeKind, VariantInfo,
+};
 use crate::config::{
     self, CoverageLevel, CrateType, DebugInfo, ErrorOutputType, FunctionReturn, Input,
     InstrumentCoverage, OptLevel, OutFileName, OutputType, RemapPathScopeComponents,
@@ -156,6 +160,10 @@ pub struct Session {
     /// This only ever stores a `LintStore` but we don't want a dependency on that type here.
     pub lint_store: Option<Lrc<dyn LintStoreMarker>>,
 
+    /// Data of the tool driving the compiler, for the queries it overrides, which can only
+    /// reach it through the session. We don't want a dependency on the tool's types here.
+    pub driver_data: OnceLock<Box<dyn Any + Send + Sync>>,
+
     /// Cap lint level specified by a driver specifically.
     pub driver_lint_caps: FxHashMap<lint::LintId, lint::Level>,
 
@@ -920,7 +928,11 @@ fn default_emitter(
                     })
                     .ignored_directories_in_source_blocks(
                         sopts.unstable_opts.ignore_directory_in_diagnostics_source_blocks.clone(),
-                    );
+                    )
+                    .ci_annotations(CiAnnotations::for_error_format(
+                        kind,
+                        sopts.unstable_opts.gitlab_code_quality_report.clone(),
+                    ));
                 Box::new(emitter.ui_testing(sopts.unstable_opts.ui_testing))
             }
         }
@@ -943,6 +955,15 @@ fn default_emitter(
             .track_diagnostics(track_diagnostics)
             .terminal_url(terminal_url),
         ),
+        config::ErrorOutputType::Sarif => {
+            // The results of the early diagnostic contexts that are still alive go into the
+            // same log, which is written once the session is over.
+            let sarif_log = SarifLog::stderr();
+            sarif_log.write_empty_log();
+            Box::new(
+                SarifEmitter::new(sarif_log, source_map, fallback_bundle).fluent_bundle(bundle),
+            )
+        }
     }
 }
 
@@ -1065,6 +1086,7 @@ pub fn build_session(
         prof,
         code_stats: Default::default(),
         lint_store: None,
+        driver_data: OnceLock::new(),
         driver_lint_caps,
         ctfe_backtrace,
         miri_unleashed_features: Lock::new(Default::default()),
@@ -1444,6 +1466,11 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
                 color_config,
             ))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::new(
+            SarifLog::stderr(),
+            Lrc::new(SourceMap::new(FilePathMapping::empty())),
+            fallback_bundle,
+        )),
     };
     emitter
 }
@@ -10,7 +10,12 @@
 use stable_mir::abi::Layout;
 use stable_mir::mir::alloc::AllocId;
 use stable_mir::mir::mono::{Instance, MonoItem, StaticDef};
-use stable_mir::mir::{BinOp, Mutability, Place, ProjectionElem, Safety, UnOp};
+use stable_mir::mir::{
+    AggregateKind, AssertMessage, BinOp, BorrowKind, CastKind, ConstOperand, CoroutineDesugaring,
+    CoroutineKind, CoroutineSource, FakeBorrowKind, MutBorrowKind, Mutability,
+    NonDivergingIntrinsic, NullOp, Operand, Place, PointerCoercion, ProjectionElem, RetagKind,
+    Safety, UnOp, VarDebugInfo, VarDebugInfoContents, VarDebugInfoFragment,
+};
 use stable_mir::ty::{
     Abi, AdtDef, Binder, BoundRegionKind, BoundTyKind, BoundVariableKind, ClosureKind, DynKind,
     ExistentialPredicate, ExistentialProjection, ExistentialTraitRef, FloatTy, FnSig,
@@ -602,6 +607,323 @@ fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::
     }
 }
 
+impl RustcInternal for Operand {
+    type T<'tcx> = rustc_middle::mir::Operand<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            Operand::Copy(place) => rustc_middle::mir::Operand::Copy(place.internal(tables, tcx)),
+            Operand::Move(place) => rustc_middle::mir::Operand::Move(place.internal(tables, tcx)),
+            Operand::Constant(constant) => {
+                rustc_middle::mir::Operand::Constant(Box::new(constant.internal(tables, tcx)))
+            }
+        }
+    }
+}
+
+impl RustcInternal for ConstOperand {
+    type T<'tcx> = rustc_middle::mir::ConstOperand<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::ConstOperand {
+            span: self.span.internal(tables, tcx),
+            user_ty: self.user_ty.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+            const_: self.const_.internal(tables, tcx),
+        }
+    }
+}
+
+impl RustcInternal for AggregateKind {
+    type T<'tcx> = rustc_middle::mir::AggregateKind<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            AggregateKind::Array(ty) => {
+                rustc_middle::mir::AggregateKind::Array(ty.internal(tables, tcx))
+            }
+            AggregateKind::Tuple => rustc_middle::mir::AggregateKind::Tuple,
+            AggregateKind::Adt(def, variant, args, user_ty, field) => {
+                rustc_middle::mir::AggregateKind::Adt(
+                    def.0.internal(tables, tcx),
+                    variant.internal(tables, tcx),
+                    args.internal(tables, tcx),
+                    user_ty.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+                    field.map(rustc_abi::FieldIdx::from_usize),
+                )
+            }
+            AggregateKind::Closure(def, args) => rustc_middle::mir::AggregateKind::Closure(
+                def.0.internal(tables, tcx),
+                args.internal(tables, tcx),
+            ),
+            AggregateKind::Coroutine(def, args, _movability) => {
+                rustc_middle::mir::AggregateKind::Coroutine(
+                    def.0.internal(tables, tcx),
+                    args.internal(tables, tcx),
+                )
+            }
+            AggregateKind::CoroutineClosure(def, args) => {
+                rustc_middle::mir::AggregateKind::CoroutineClosure(
+                    def.0.internal(tables, tcx),
+                    args.internal(tables, tcx),
+                )
+            }
+            AggregateKind::RawPtr(ty, mutability) => rustc_middle::mir::AggregateKind::RawPtr(
+                ty.internal(tables, tcx),
+                mutability.internal(tables, tcx),
+            ),
+        }
+    }
+}
+
+impl RustcInternal for BorrowKind {
+    type T<'tcx> = rustc_middle::mir::BorrowKind;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            BorrowKind::Shared => rustc_middle::mir::BorrowKind::Shared,
+            BorrowKind::Fake(kind) => {
+                rustc_middle::mir::BorrowKind::Fake(kind.internal(tables, tcx))
+            }
+            BorrowKind::Mut { kind } => {
+                rustc_middle::mir::BorrowKind::Mut { kind: kind.internal(tables, tcx) }
+            }
+        }
+    }
+}
+
+impl RustcInternal for MutBorrowKind {
+    type T<'tcx> = rustc_middle::mir::MutBorrowKind;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            MutBorrowKind::Default => rustc_middle::mir::MutBorrowKind::Default,
+            MutBorrowKind::TwoPhaseBorrow => rustc_middle::mir::MutBorrowKind::TwoPhaseBorrow,
+            MutBorrowKind::ClosureCapture => rustc_middle::mir::MutBorrowKind::ClosureCapture,
+        }
+    }
+}
+
+impl RustcInternal for FakeBorrowKind {
+    type T<'tcx> = rustc_middle::mir::FakeBorrowKind;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            FakeBorrowKind::Deep => rustc_middle::mir::FakeBorrowKind::Deep,
+            FakeBorrowKind::Shallow => rustc_middle::mir::FakeBorrowKind::Shallow,
+        }
+    }
+}
+
+impl RustcInternal for CastKind {
+    type T<'tcx> = rustc_middle::mir::CastKind;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::CoercionSource;
+        use rustc_middle::ty::adjustment::PointerCoercion as InternalPointerCoercion;
+        // The source of a coercion isn't kept in StableMIR. It only matters for borrowck, which
+        // already ran on any body that can be converted back.
+        match self {
+            CastKind::PointerExposeAddress => rustc_middle::mir::CastKind::PointerExposeProvenance,
+            CastKind::PointerWithExposedProvenance => {
+                rustc_middle::mir::CastKind::PointerWithExposedProvenance
+            }
+            CastKind::PointerCoercion(coercion) => rustc_middle::mir::CastKind::PointerCoercion(
+                coercion.internal(tables, tcx),
+                CoercionSource::Implicit,
+            ),
+            CastKind::DynStar => rustc_middle::mir::CastKind::PointerCoercion(
+                InternalPointerCoercion::DynStar,
+                CoercionSource::Implicit,
+            ),
+            CastKind::IntToInt => rustc_middle::mir::CastKind::IntToInt,
+            CastKind::FloatToInt => rustc_middle::mir::CastKind::FloatToInt,
+            CastKind::FloatToFloat => rustc_middle::mir::CastKind::FloatToFloat,
+            CastKind::IntToFloat => rustc_middle::mir::CastKind::IntToFloat,
+            CastKind::PtrToPtr => rustc_middle::mir::CastKind::PtrToPtr,
+            CastKind::FnPtrToPtr => rustc_middle::mir::CastKind::FnPtrToPtr,
+            CastKind::Transmute => rustc_middle::mir::CastKind::Transmute,
+        }
+    }
+}
+
+impl RustcInternal for PointerCoercion {
+    type T<'tcx> = rustc_middle::ty::adjustment::PointerCoercion;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::ty::adjustment::PointerCoercion as InternalPointerCoercion;
+        match self {
+            PointerCoercion::ReifyFnPointer => InternalPointerCoercion::ReifyFnPointer,
+            PointerCoercion::UnsafeFnPointer => InternalPointerCoercion::UnsafeFnPointer,
+            PointerCoercion::ClosureFnPointer(safety) => {
+                InternalPointerCoercion::ClosureFnPointer(safety.internal(tables, tcx))
+            }
+            PointerCoercion::MutToConstPointer => InternalPointerCoercion::MutToConstPointer,
+            PointerCoercion::ArrayToPointer => InternalPointerCoercion::ArrayToPointer,
+            PointerCoercion::Unsize => InternalPointerCoercion::Unsize,
+        }
+    }
+}
+
+impl RustcInternal for NullOp {
+    type T<'tcx> = rustc_middle::mir::NullOp<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            NullOp::SizeOf => rustc_middle::mir::NullOp::SizeOf,
+            NullOp::AlignOf => rustc_middle::mir::NullOp::AlignOf,
+            NullOp::OffsetOf(indices) => rustc_middle::mir::NullOp::OffsetOf(
+                tcx.mk_offset_of_from_iter(indices.iter().map(|(variant, field)| {
+                    (variant.internal(tables, tcx), rustc_abi::FieldIdx::from_usize(*field))
+                })),
+            ),
+            NullOp::UbChecks => rustc_middle::mir::NullOp::UbChecks,
+        }
+    }
+}
+
+impl RustcInternal for RetagKind {
+    type T<'tcx> = rustc_middle::mir::RetagKind;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            RetagKind::FnEntry => rustc_middle::mir::RetagKind::FnEntry,
+            RetagKind::TwoPhase => rustc_middle::mir::RetagKind::TwoPhase,
+            RetagKind::Raw => rustc_middle::mir::RetagKind::Raw,
+            RetagKind::Default => rustc_middle::mir::RetagKind::Default,
+        }
+    }
+}
+
+impl RustcInternal for NonDivergingIntrinsic {
+    type T<'tcx> = rustc_middle::mir::NonDivergingIntrinsic<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            NonDivergingIntrinsic::Assume(op) => {
+                rustc_middle::mir::NonDivergingIntrinsic::Assume(op.internal(tables, tcx))
+            }
+            NonDivergingIntrinsic::CopyNonOverlapping(copy) => {
+                rustc_middle::mir::NonDivergingIntrinsic::CopyNonOverlapping(
+                    rustc_middle::mir::CopyNonOverlapping {
+                        src: copy.src.internal(tables, tcx),
+                        dst: copy.dst.internal(tables, tcx),
+                        count: copy.count.internal(tables, tcx),
+                    },
+                )
+            }
+        }
+    }
+}
+
+impl RustcInternal for AssertMessage {
+    type T<'tcx> = rustc_middle::mir::AssertMessage<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::AssertKind;
+        match self {
+            AssertMessage::BoundsCheck { len, index } => AssertKind::BoundsCheck {
+                len: len.internal(tables, tcx),
+                index: index.internal(tables, tcx),
+            },
+            AssertMessage::Overflow(bin_op, left, right) => AssertKind::Overflow(
+                bin_op.internal(tables, tcx),
+                left.internal(tables, tcx),
+                right.internal(tables, tcx),
+            ),
+            AssertMessage::OverflowNeg(op) => AssertKind::OverflowNeg(op.internal(tables, tcx)),
+            AssertMessage::DivisionByZero(op) => {
+                AssertKind::DivisionByZero(op.internal(tables, tcx))
+            }
+            AssertMessage::RemainderByZero(op) => {
+                AssertKind::RemainderByZero(op.internal(tables, tcx))
+            }
+            AssertMessage::ResumedAfterReturn(coroutine) => {
+                AssertKind::ResumedAfterReturn(coroutine.internal(tables, tcx))
+            }
+            AssertMessage::ResumedAfterPanic(coroutine) => {
+                AssertKind::ResumedAfterPanic(coroutine.internal(tables, tcx))
+            }
+            AssertMessage::MisalignedPointerDereference { required, found } => {
+                AssertKind::MisalignedPointerDereference {
+                    required: required.internal(tables, tcx),
+                    found: found.internal(tables, tcx),
+                }
+            }
+        }
+    }
+}
+
+impl RustcInternal for CoroutineKind {
+    type T<'tcx> = rustc_hir::CoroutineKind;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            CoroutineKind::Desugared(desugaring, source) => {
+                let desugaring = match desugaring {
+                    CoroutineDesugaring::Async => rustc_hir::CoroutineDesugaring::Async,
+                    CoroutineDesugaring::Gen => rustc_hir::CoroutineDesugaring::Gen,
+                    CoroutineDesugaring::AsyncGen => rustc_hir::CoroutineDesugaring::AsyncGen,
+                };
+                let source = match source {
+                    CoroutineSource::Block => rustc_hir::CoroutineSource::Block,
+                    CoroutineSource::Closure => rustc_hir::CoroutineSource::Closure,
+                    CoroutineSource::Fn => rustc_hir::CoroutineSource::Fn,
+                };
+                rustc_hir::CoroutineKind::Desugared(desugaring, source)
+            }
+            CoroutineKind::Coroutine(movability) => {
+                rustc_hir::CoroutineKind::Coroutine(movability.internal(tables, tcx))
+            }
+        }
+    }
+}
+
+impl RustcInternal for VarDebugInfo {
+    type T<'tcx> = rustc_middle::mir::VarDebugInfo<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::VarDebugInfo {
+            name: Symbol::intern(&self.name),
+            source_info: rustc_middle::mir::SourceInfo {
+                span: self.source_info.span.internal(tables, tcx),
+                scope: rustc_middle::mir::SourceScope::from_u32(self.source_info.scope),
+            },
+            composite: self
+                .composite
+                .as_ref()
+                .map(|fragment| Box::new(fragment.internal(tables, tcx))),
+            value: self.value.internal(tables, tcx),
+            argument_index: self.argument_index,
+        }
+    }
+}
+
+impl RustcInternal for VarDebugInfoFragment {
+    type T<'tcx> = rustc_middle::mir::VarDebugInfoFragment<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::VarDebugInfoFragment {
+            ty: self.ty.internal(tables, tcx),
+            projection: self.projection.internal(tables, tcx),
+        }
+    }
+}
+
+impl RustcInternal for VarDebugInfoContents {
+    type T<'tcx> = rustc_middle::mir::VarDebugInfoContents<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            VarDebugInfoContents::Place(place) => {
+                rustc_middle::mir::VarDebugInfoContents::Place(place.internal(tables, tcx))
+            }
+            VarDebugInfoContents::Const(constant) => {
+                rustc_middle::mir::VarDebugInfoContents::Const(constant.internal(tables, tcx))
+            }
+        }
+    }
+}
+
 impl<T> RustcInternal for &T
 where
     T: RustcInternal,
@@ -25,6 +25,9 @@
 
 mod internal;
 pub mod pretty;
+mod transform;
+
+pub use transform::{BodyTransform, override_queries, register_body_transform};
 
 /// Convert an internal Rust compiler item into its stable counterpart, if one exists.
 ///
@@ -209,11 +212,8 @@ pub(crate) fn with_tables<R>(f: impl for<'tcx> FnOnce(&mut Tables<'tcx>) -> R) -
     })
 }
 
-pub fn run<F, T>(tcx: TyCtxt<'_>, f: F) -> Result<T, Error>
-where
-    F: FnOnce() -> T,
-{
-    let tables = TablesWrapper(RefCell::new(Tables {
+fn new_tables(tcx: TyCtxt<'_>) -> TablesWrapper<'_> {
+    TablesWrapper(RefCell::new(Tables {
         tcx,
         def_ids: IndexMap::default(),
         alloc_ids: IndexMap::default(),
@@ -223,10 +223,29 @@ pub fn run<F, T>(tcx: TyCtxt<'_>, f: F) -> Result<T, Error>
         ty_consts: IndexMap::default(),
         mir_consts: IndexMap::default(),
         layouts: IndexMap::default(),
-    }));
+    }))
+}
+
+pub fn run<F, T>(tcx: TyCtxt<'_>, f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> T,
+{
+    let tables = new_tables(tcx);
     stable_mir::compiler_interface::run(&tables, || init(&tables, f))
 }
 
+/// Runs `f` in a new StableMIR context, even if StableMIR is already running.
+///
+/// Items of the outer context are meaningless in the new one, and vice versa.
+pub(crate) fn run_nested<F, T>(tcx: TyCtxt<'_>, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let tables = new_tables(tcx);
+    let ptr = &tables as *const _ as *const ();
+    stable_mir::compiler_interface::run_nested(&tables, || TLV.set(&Cell::new(ptr), f))
+}
+
 /// Instantiate and run the compiler with the provided arguments and callback.
 ///
 /// The callback will be invoked after the compiler ran all its analyses, but before code generation.
@@ -273,6 +292,9 @@ pub fn run<F, T>(tcx: TyCtxt<'_>, f: F) -> Result<T, Error>
 /// #   assert_eq!(result, Err(CompilerError::Skipped))
 /// # }
 /// ```
+///
+/// A transformation of the MIR of the crate's functions can be registered with
+/// [register_body_transform] before the compiler is run. It only applies to that run.
 #[macro_export]
 macro_rules! run {
     ($args:expr, $callback_fn:ident) => {
@@ -331,6 +353,7 @@ pub struct StableMir<B = (), C = (), F = fn($(optional!($with_tcx TyCtxt))?) ->
             args: Vec<String>,
             callback: Option<F>,
             result: Option<ControlFlow<B, C>>,
+            body_transform: Option<rustc_internal::BodyTransform>,
         }
 
         impl<B, C, F> StableMir<B, C, F>
@@ -341,7 +364,12 @@ impl<B, C, F> StableMir<B, C, F>
         {
             /// Creates a new `StableMir` instance, with given test_function and arguments.
             pub fn new(args: Vec<String>, callback: F) -> Self {
-                StableMir { args, callback: Some(callback), result: None }
+                StableMir {
+                    args,
+                    callback: Some(callback),
+                    result: None,
+                    body_transform: rustc_internal::BodyTransform::take_registered(),
+                }
             }
 
             /// Runs the compiler against given target and tests it with `test_function`
@@ -374,6 +402,27 @@ impl<B, C, F> Callbacks for StableMir<B, C, F>
             C: Send,
             F: FnOnce($(optional!($with_tcx TyCtxt))?) -> ControlFlow<B, C> + Send,
         {
+            /// Called before creating the compiler instance, to apply the body transformation,
+            /// if any.
+            fn config(&mut self, config: &mut interface::Config) {
+                if self.body_transform.is_some() {
+                    config.override_queries = Some(rustc_internal::override_queries);
+                }
+            }
+
+            /// Called after expansion, before any MIR is built. Installs the body
+            /// transformation for this session.
+            fn after_expansion<'tcx>(
+                &mut self,
+                _compiler: &interface::Compiler,
+                tcx: TyCtxt<'tcx>,
+            ) -> Compilation {
+                if let Some(body_transform) = self.body_transform.take() {
+                    body_transform.install(tcx);
+                }
+                Compilation::Continue
+            }
+
             /// Called after analysis. Return value instructs the compiler whether to
             /// continue the compilation afterwards (defaults to `Compilation::Continue`)
             fn after_analysis<'tcx>(
@@ -0,0 +1,515 @@
+//! Support for tools that modify the MIR of the crate being compiled.
+//!
+//! A body transformation is applied when the compiler computes the optimized MIR of a function,
+//! so the modified body is used by codegen and by the crates that depend on this one. Other MIR
+//! is computed separately, and isn't modified: the MIR that const-eval uses (`mir_for_ctfe`),
+//! and the MIR of shims, e.g. drop glue.
+
+use std::cell::RefCell;
+use std::sync::{Arc, OnceLock};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::LocalDefId;
+use rustc_index::IndexVec;
+use rustc_middle::mir::visit::Visitor;
+use rustc_middle::mir::{self, Location};
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::util::Providers;
+use rustc_session::Session;
+use rustc_span::source_map::Spanned;
+use stable_mir::mir::{
+    Body, FakeReadCause, Rvalue, Statement, StatementKind, Terminator, TerminatorKind, UnwindAction,
+};
+use stable_mir::{CrateItem, Error};
+
+use super::{RustcInternal, run_nested, with_tables};
+use crate::rustc_smir::{Stable, Tables};
+
+type Transform = dyn Fn(CrateItem, &Body) -> Option<Body> + Send + Sync;
+
+thread_local! {
+    /// The transformation for the next compiler run that this thread starts.
+    static REGISTERED_TRANSFORM: RefCell<Option<Arc<Transform>>> = const { RefCell::new(None) };
+}
+
+/// The transformation of a session, stored in its [`Session::driver_data`].
+struct SessionTransform {
+    /// The provider which computes the bodies that are transformed.
+    default_optimized_mir: for<'tcx> fn(TyCtxt<'tcx>, LocalDefId) -> &'tcx mir::Body<'tcx>,
+    /// Set once the transformation is installed, before any MIR is built.
+    transform: OnceLock<Arc<Transform>>,
+}
+
+/// Registers a transformation of the optimized MIR of the functions of the local crate.
+///
+/// The transformation is called with every function and its body, and returns the new body, or
+/// `None` to keep the body as is. It's an error if the new body doesn't pass the MIR validator.
+/// Only the optimized MIR is transformed, which is what codegen and the crates that depend on
+/// this one use. The MIR used by const-eval and the MIR of shims, e.g. drop glue, are not.
+///
+/// The transformation is applied by the next compiler run that this thread starts with
+/// [run!](crate::run) or [run_with_tcx!](crate::run_with_tcx), and only by that one. It runs in
+/// a StableMIR context of its own, so it must not use StableMIR items that were created outside
+/// of it, e.g. in the callback of `run!`.
+pub fn register_body_transform<F>(transform: F)
+where
+    F: Fn(CrateItem, &Body) -> Option<Body> + Send + Sync + 'static,
+{
+    REGISTERED_TRANSFORM.with(|registered| *registered.borrow_mut() = Some(Arc::new(transform)));
+}
+
+/// A transformation registered with [register_body_transform], taken by the compiler run that
+/// applies it. This is used by `run!` and `run_with_tcx!`.
+#[doc(hidden)]
+pub struct BodyTransform(Arc<Transform>);
+
+impl BodyTransform {
+    /// Takes the transformation registered by this thread, if any.
+    pub fn take_registered() -> Option<BodyTransform> {
+        REGISTERED_TRANSFORM.with(|registered| registered.borrow_mut().take()).map(BodyTransform)
+    }
+
+    /// Applies the transformation to the bodies of the session of `tcx`. The queries must have
+    /// been overridden with [override_queries].
+    pub fn install(self, tcx: TyCtxt<'_>) {
+        if session_transform(tcx.sess).transform.set(self.0).is_err() {
+            panic!("a body transformation is already installed");
+        }
+    }
+}
+
+fn session_transform(sess: &Session) -> &SessionTransform {
+    sess.driver_data
+        .get()
+        .and_then(|data| data.downcast_ref())
+        .expect("the queries weren't overridden to apply a body transformation")
+}
+
+/// Overrides the queries needed to apply a [`BodyTransform`]. This is used by `run!` and
+/// `run_with_tcx!` if a transformation was registered.
+pub fn override_queries(sess: &Session, providers: &mut Providers) {
+    let transform = SessionTransform {
+        default_optimized_mir: providers.optimized_mir,
+        transform: OnceLock::new(),
+    };
+    if sess.driver_data.set(Box::new(transform)).is_err() {
+        panic!("the session already has driver data");
+    }
+    providers.optimized_mir = optimized_mir;
+}
+
+fn optimized_mir(tcx: TyCtxt<'_>, def_id: LocalDefId) -> &mir::Body<'_> {
+    let session_transform = session_transform(tcx.sess);
+    let body = (session_transform.default_optimized_mir)(tcx, def_id);
+    let Some(transform) = session_transform.transform.get() else {
+        return body;
+    };
+    // The compiler may need this body while StableMIR is answering a request, e.g. to inline
+    // it, so the transformation gets a new context.
+    let new_body = run_nested(tcx, || {
+        let (item, stable_body) =
+            with_tables(|tables| (tables.crate_item(def_id.to_def_id()), body.stable(tables)));
+        let new_body = transform(item, &stable_body)?;
+        Some(with_tables(|tables| internal_body(tables, tcx, body, &stable_body, &new_body)))
+    });
+    match new_body {
+        None => body,
+        Some(Ok(new_body)) => {
+            let failures = rustc_mir_transform::validate_body(tcx, &new_body);
+            if failures.is_empty() {
+                tcx.arena.alloc(new_body)
+            } else {
+                report_invalid_body(tcx, def_id, failures);
+                body
+            }
+        }
+        Some(Err(error)) => {
+            report_invalid_body(tcx, def_id, vec![(Location::START, error.to_string())]);
+            body
+        }
+    }
+}
+
+#[allow(rustc::diagnostic_outside_of_impl)]
+#[allow(rustc::untranslatable_diagnostic)]
+fn report_invalid_body(tcx: TyCtxt<'_>, def_id: LocalDefId, failures: Vec<(Location, String)>) {
+    let mut diag = tcx.dcx().struct_span_err(
+        tcx.def_span(def_id),
+        format!(
+            "invalid MIR for `{}` returned by the body transformation",
+            tcx.def_path_str(def_id)
+        ),
+    );
+    for (location, failure) in failures {
+        diag.note(format!("at {location:?}: {failure}"));
+    }
+    diag.emit();
+}
+
+/// Converts a body returned by a transformation of `original` back to an internal one.
+///
+/// StableMIR doesn't have everything an internal body consists of, so what isn't part of the
+/// stable body (e.g. source scopes and user type annotations) is taken from `original`.
+fn internal_body<'tcx>(
+    tables: &mut Tables<'_>,
+    tcx: TyCtxt<'tcx>,
+    original: &mir::Body<'tcx>,
+    stable_original: &Body,
+    new: &Body,
+) -> Result<mir::Body<'tcx>, Error> {
+    if new.arg_locals().len() != original.arg_count
+        || new.spread_arg() != original.spread_arg.map(|local| local.as_usize())
+    {
+        return Err(Error::new("the arguments of a function can't be changed".to_string()));
+    }
+    if new.blocks.is_empty() {
+        return Err(Error::new("the body has no blocks".to_string()));
+    }
+    // The compiler assumes that the control flow graph is well-formed, even before validation.
+    for (index, block) in new.blocks.iter().enumerate() {
+        let successors = block.terminator.successors();
+        if let Some(target) = successors.into_iter().find(|target| *target >= new.blocks.len()) {
+            return Err(Error::new(format!("block {index} jumps to the missing block {target}")));
+        }
+    }
+
+    let mut converter = BodyConverter {
+        tables,
+        tcx,
+        is_cleanup: cleanup_blocks(new),
+        scopes: FxHashMap::default(),
+        opaque_statements: Vec::new(),
+        opaque_terminators: Vec::new(),
+    };
+    // Statements and terminators that StableMIR only has an opaque representation of can't be
+    // converted, so they are taken from the original body if they weren't modified.
+    for (block, stable_block) in original.basic_blocks.iter().zip(&stable_original.blocks) {
+        for (statement, stable_statement) in block.statements.iter().zip(&stable_block.statements) {
+            converter.scopes.insert(statement.source_info.span, statement.source_info.scope);
+            if is_opaque_statement(&stable_statement.kind) {
+                converter.opaque_statements.push((stable_statement, statement));
+            }
+        }
+        let terminator = block.terminator();
+        converter.scopes.insert(terminator.source_info.span, terminator.source_info.scope);
+        if let TerminatorKind::InlineAsm { .. } = stable_block.terminator.kind {
+            converter.opaque_terminators.push((&stable_block.terminator, terminator));
+        }
+    }
+
+    let basic_blocks = new
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| {
+            let is_cleanup = converter.is_cleanup[index];
+            Ok(mir::BasicBlockData {
+                statements: block
+                    .statements
+                    .iter()
+                    .map(|statement| converter.statement(statement))
+                    .collect::<Result<_, Error>>()?,
+                terminator: Some(converter.terminator(&block.terminator, is_cleanup)?),
+                is_cleanup,
+            })
+        })
+        .collect::<Result<IndexVec<_, _>, Error>>()?;
+
+    let local_decls = new
+        .locals()
+        .iter()
+        .enumerate()
+        .map(|(index, decl)| {
+            let ty = decl.ty.internal(converter.tables, tcx);
+            let source_info = converter.source_info(decl.span);
+            let mut local_decl = match original.local_decls.get(mir::Local::from_usize(index)) {
+                Some(local_decl) => local_decl.clone(),
+                None => mir::LocalDecl::with_source_info(ty, source_info),
+            };
+            local_decl.ty = ty;
+            local_decl.source_info.span = source_info.span;
+            local_decl.mutability = decl.mutability.internal(converter.tables, tcx);
+            local_decl
+        })
+        .collect();
+
+    let mut body = original.clone();
+    body.basic_blocks = mir::BasicBlocks::new(basic_blocks);
+    body.local_decls = local_decls;
+    body.var_debug_info = new.var_debug_info.internal(converter.tables, tcx);
+    body.span = new.span.internal(converter.tables, tcx);
+
+    let mut required_consts = RequiredConsts(Vec::new());
+    for (block, data) in mir::traversal::reverse_postorder(&body) {
+        required_consts.visit_basic_block_data(block, data);
+    }
+    body.required_consts = Some(required_consts.0);
+    Ok(body)
+}
+
+/// Returns which blocks are cleanup blocks, i.e. the blocks that are reachable from an unwind
+/// edge.
+fn cleanup_blocks(body: &Body) -> Vec<bool> {
+    let mut is_cleanup = vec![false; body.blocks.len()];
+    let mut stack: Vec<_> = body
+        .blocks
+        .iter()
+        .filter_map(|block| match block.terminator.kind.unwind() {
+            Some(UnwindAction::Cleanup(target)) => Some(*target),
+            _ => None,
+        })
+        .collect();
+    while let Some(block) = stack.pop() {
+        if !is_cleanup[block] {
+            is_cleanup[block] = true;
+            stack.extend(body.blocks[block].terminator.successors());
+        }
+    }
+    is_cleanup
+}
+
+fn is_opaque_statement(kind: &StatementKind) -> bool {
+    match kind {
+        StatementKind::FakeRead(cause, _) => {
+            matches!(cause, FakeReadCause::ForMatchedPlace(_) | FakeReadCause::ForLet(_))
+        }
+        StatementKind::AscribeUserType { .. } | StatementKind::Coverage(_) => true,
+        _ => false,
+    }
+}
+
+struct BodyConverter<'a, 'b, 'tcx> {
+    tables: &'a mut Tables<'b>,
+    tcx: TyCtxt<'tcx>,
+    is_cleanup: Vec<bool>,
+    /// The scopes of the statements and terminators of the original body, by span.
+    scopes: FxHashMap<rustc_span::Span, mir::SourceScope>,
+    opaque_statements: Vec<(&'a Statement, &'a mir::Statement<'tcx>)>,
+    opaque_terminators: Vec<(&'a Terminator, &'a mir::Terminator<'tcx>)>,
+}
+
+impl<'a, 'b, 'tcx> BodyConverter<'a, 'b, 'tcx> {
+    /// New statements get the scope of the original statements with the same span, e.g. those
+    /// of the call they instrument.
+    fn source_info(&mut self, span: stable_mir::ty::Span) -> mir::SourceInfo {
+        let span = span.internal(self.tables, self.tcx);
+        let scope = self.scopes.get(&span).copied().unwrap_or(mir::OUTERMOST_SOURCE_SCOPE);
+        mir::SourceInfo { span, scope }
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<mir::Statement<'tcx>, Error> {
+        if is_opaque_statement(&statement.kind) {
+            return self
+                .opaque_statements
+                .iter()
+                .find(|(stable, _)| *stable == statement)
+                .map(|(_, internal)| (*internal).clone())
+                .ok_or_else(|| {
+                    Error::new(format!("`{:?}` statements can't be created", statement.kind))
+                });
+        }
+
+        let tables = &mut *self.tables;
+        let tcx = self.tcx;
+        let kind = match &statement.kind {
+            StatementKind::Assign(place, rvalue) => mir::StatementKind::Assign(Box::new((
+                place.internal(tables, tcx),
+                rvalue_internal(rvalue, tables, tcx)?,
+            ))),
+            StatementKind::FakeRead(cause, place) => {
+                let cause = match cause {
+                    FakeReadCause::ForMatchGuard => mir::FakeReadCause::ForMatchGuard,
+                    FakeReadCause::ForGuardBinding => mir::FakeReadCause::ForGuardBinding,
+                    FakeReadCause::ForIndex => mir::FakeReadCause::ForIndex,
+                    FakeReadCause::ForMatchedPlace(_) | FakeReadCause::ForLet(_) => {
+                        unreachable!("opaque statements are taken from the original body")
+                    }
+                };
+                mir::StatementKind::FakeRead(Box::new((cause, place.internal(tables, tcx))))
+            }
+            StatementKind::SetDiscriminant { place, variant_index } => {
+                mir::StatementKind::SetDiscriminant {
+                    place: Box::new(place.internal(tables, tcx)),
+                    variant_index: variant_index.internal(tables, tcx),
+                }
+            }
+            StatementKind::Deinit(place) => {
+                mir::StatementKind::Deinit(Box::new(place.internal(tables, tcx)))
+            }
+            StatementKind::StorageLive(local) => {
+                mir::StatementKind::StorageLive(mir::Local::from_usize(*local))
+            }
+            StatementKind::StorageDead(local) => {
+                mir::StatementKind::StorageDead(mir::Local::from_usize(*local))
+            }
+            StatementKind::Retag(kind, place) => mir::StatementKind::Retag(
+                kind.internal(tables, tcx),
+                Box::new(place.internal(tables, tcx)),
+            ),
+            StatementKind::PlaceMention(place) => {
+                mir::StatementKind::PlaceMention(Box::new(place.internal(tables, tcx)))
+            }
+            StatementKind::Intrinsic(intrinsic) => {
+                mir::StatementKind::Intrinsic(Box::new(intrinsic.internal(tables, tcx)))
+            }
+            StatementKind::ConstEvalCounter => mir::StatementKind::ConstEvalCounter,
+            StatementKind::Nop => mir::StatementKind::Nop,
+            StatementKind::AscribeUserType { .. } | StatementKind::Coverage(_) => {
+                unreachable!("opaque statements are taken from the original body")
+            }
+        };
+        Ok(mir::Statement { source_info: self.source_info(statement.span), kind })
+    }
+
+    fn terminator(
+        &mut self,
+        terminator: &Terminator,
+        is_cleanup: bool,
+    ) -> Result<mir::Terminator<'tcx>, Error> {
+        let source_info = self.source_info(terminator.span);
+        let tables = &mut *self.tables;
+        let tcx = self.tcx;
+        let terminate_reason = if is_cleanup {
+            mir::UnwindTerminateReason::InCleanup
+        } else {
+            mir::UnwindTerminateReason::Abi
+        };
+        let unwind = |unwind: &UnwindAction| match unwind {
+            UnwindAction::Continue => mir::UnwindAction::Continue,
+            UnwindAction::Unreachable => mir::UnwindAction::Unreachable,
+            UnwindAction::Terminate => mir::UnwindAction::Terminate(terminate_reason),
+            UnwindAction::Cleanup(target) => {
+                mir::UnwindAction::Cleanup(mir::BasicBlock::from_usize(*target))
+            }
+        };
+        let kind = match &terminator.kind {
+            TerminatorKind::Goto { target } => {
+                mir::TerminatorKind::Goto { target: mir::BasicBlock::from_usize(*target) }
+            }
+            TerminatorKind::SwitchInt { discr, targets } => mir::TerminatorKind::SwitchInt {
+                discr: discr.internal(tables, tcx),
+                targets: mir::SwitchTargets::new(
+                    targets
+                        .branches()
+                        .map(|(value, target)| (value, mir::BasicBlock::from_usize(target))),
+                    mir::BasicBlock::from_usize(targets.otherwise()),
+                ),
+            },
+            TerminatorKind::Resume => mir::TerminatorKind::UnwindResume,
+            TerminatorKind::Abort => mir::TerminatorKind::UnwindTerminate(terminate_reason),
+            TerminatorKind::Return => mir::TerminatorKind::Return,
+            TerminatorKind::Unreachable => mir::TerminatorKind::Unreachable,
+            TerminatorKind::Drop { place, target, unwind: drop_unwind } => {
+                mir::TerminatorKind::Drop {
+                    place: place.internal(tables, tcx),
+                    target: mir::BasicBlock::from_usize(*target),
+                    unwind: unwind(drop_unwind),
+                    replace: false,
+                }
+            }
+            TerminatorKind::Call { func, args, destination, target, unwind: call_unwind } => {
+                mir::TerminatorKind::Call {
+                    func: func.internal(tables, tcx),
+                    args: args
+                        .iter()
+                        .map(|arg| Spanned {
+                            node: arg.internal(tables, tcx),
+                            span: source_info.span,
+                        })
+                        .collect(),
+                    destination: destination.internal(tables, tcx),
+                    target: target.map(mir::BasicBlock::from_usize),
+                    unwind: unwind(call_unwind),
+                    call_source: mir::CallSource::Normal,
+                    fn_span: source_info.span,
+                }
+            }
+            TerminatorKind::Assert { cond, expected, msg, target, unwind: assert_unwind } => {
+                mir::TerminatorKind::Assert {
+                    cond: cond.internal(tables, tcx),
+                    expected: *expected,
+                    msg: Box::new(msg.internal(tables, tcx)),
+                    target: mir::BasicBlock::from_usize(*target),
+                    unwind: unwind(assert_unwind),
+                }
+            }
+            TerminatorKind::InlineAsm { .. } => {
+                return self
+                    .opaque_terminators
+                    .iter()
+                    .find(|(stable, _)| *stable == terminator)
+                    .map(|(_, internal)| (*internal).clone())
+                    .ok_or_else(|| Error::new("inline assembly can't be modified".to_string()));
+            }
+        };
+        Ok(mir::Terminator { source_info, kind })
+    }
+}
+
+fn rvalue_internal<'tcx>(
+    rvalue: &Rvalue,
+    tables: &mut Tables<'_>,
+    tcx: TyCtxt<'tcx>,
+) -> Result<mir::Rvalue<'tcx>, Error> {
+    Ok(match rvalue {
+        Rvalue::AddressOf(mutability, place) => {
+            mir::Rvalue::RawPtr(mutability.internal(tables, tcx), place.internal(tables, tcx))
+        }
+        Rvalue::Aggregate(kind, operands) => mir::Rvalue::Aggregate(
+            Box::new(kind.internal(tables, tcx)),
+            IndexVec::from_raw(operands.internal(tables, tcx)),
+        ),
+        Rvalue::BinaryOp(bin_op, left, right) => mir::Rvalue::BinaryOp(
+            bin_op.internal(tables, tcx),
+            Box::new((left.internal(tables, tcx), right.internal(tables, tcx))),
+        ),
+        Rvalue::Cast(kind, operand, ty) => mir::Rvalue::Cast(
+            kind.internal(tables, tcx),
+            operand.internal(tables, tcx),
+            ty.internal(tables, tcx),
+        ),
+        Rvalue::CheckedBinaryOp(bin_op, left, right) => {
+            let Some(bin_op) = bin_op.internal(tables, tcx).wrapping_to_overflowing() else {
+                return Err(Error::new(format!("`{bin_op:?}` can't be checked for overflow")));
+            };
+            mir::Rvalue::BinaryOp(
+                bin_op,
+                Box::new((left.internal(tables, tcx), right.internal(tables, tcx))),
+            )
+        }
+        Rvalue::CopyForDeref(place) => mir::Rvalue::CopyForDeref(place.internal(tables, tcx)),
+        Rvalue::Discriminant(place) => mir::Rvalue::Discriminant(place.internal(tables, tcx)),
+        Rvalue::Len(place) => mir::Rvalue::Len(place.internal(tables, tcx)),
+        Rvalue::Ref(region, kind, place) => mir::Rvalue::Ref(
+            region.internal(tables, tcx),
+            kind.internal(tables, tcx),
+            place.internal(tables, tcx),
+        ),
+        Rvalue::Repeat(operand, count) => {
+            mir::Rvalue::Repeat(operand.internal(tables, tcx), count.internal(tables, tcx))
+        }
+        Rvalue::ShallowInitBox(operand, ty) => {
+            mir::Rvalue::ShallowInitBox(operand.internal(tables, tcx), ty.internal(tables, tcx))
+        }
+        Rvalue::ThreadLocalRef(item) => mir::Rvalue::ThreadLocalRef(item.internal(tables, tcx)),
+        Rvalue::NullaryOp(null_op, ty) => {
+            mir::Rvalue::NullaryOp(null_op.internal(tables, tcx), ty.internal(tables, tcx))
+        }
+        Rvalue::UnaryOp(un_op, operand) => {
+            mir::Rvalue::UnaryOp(un_op.internal(tables, tcx), operand.internal(tables, tcx))
+        }
+        Rvalue::Use(operand) => mir::Rvalue::Use(operand.internal(tables, tcx)),
+    })
+}
+
+/// Collects the constants that have to be evaluated for the body to be valid, like
+/// `RequiredConstsVisitor` in `rustc_mir_transform`.
+struct RequiredConsts<'tcx>(Vec<mir::ConstOperand<'tcx>>);
+
+impl<'tcx> Visitor<'tcx> for RequiredConsts<'tcx> {
+    fn visit_const_operand(&mut self, constant: &mir::ConstOperand<'tcx>, _: Location) {
+        if constant.const_.is_required_const() {
+            self.0.push(*constant);
+        }
+    }
+}
@@ -10,6 +10,7 @@
 
 use rustc_abi::HasDataLayout;
 use rustc_hir::LangItem;
+use rustc_middle::traits::ObligationCause;
 use rustc_middle::ty::layout::{
     FnAbiOf, FnAbiOfHelpers, HasTyCtxt, HasTypingEnv, LayoutOf, LayoutOfHelpers,
 };
@@ -19,6 +20,9 @@
 };
 use rustc_middle::{mir, ty};
 use rustc_span::def_id::LOCAL_CRATE;
+use rustc_trait_selection::infer::TyCtxtInferExt;
+use rustc_trait_selection::traits::query::evaluate_obligation::InferCtxtExt;
+use rustc_trait_selection::traits::{ObligationCtxt, PredicateObligation, SelectionContext};
 use stable_mir::abi::{FnAbi, Layout, LayoutShape};
 use stable_mir::compiler_interface::Context;
 use stable_mir::mir::alloc::GlobalAlloc;
@@ -27,8 +31,8 @@
 use stable_mir::target::{MachineInfo, MachineSize};
 use stable_mir::ty::{
     AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, FieldDef, FnDef, ForeignDef,
-    ForeignItemKind, GenericArgs, IntrinsicDef, LineInfo, MirConst, PolyFnSig, RigidTy, Span, Ty,
-    TyConst, TyKind, UintTy, VariantDef,
+    ForeignItemKind, GenericArgs, ImplSource, IntrinsicDef, LineInfo, MirConst, PolyFnSig, RigidTy,
+    Solution, Span, Ty, TyConst, TyKind, UintTy, VariantDef,
 };
 use stable_mir::{Crate, CrateDef, CrateItem, CrateNum, DefId, Error, Filename, ItemKind, Symbol};
 
@@ -71,6 +75,37 @@ fn has_body(&self, def: DefId) -> bool {
         tables.item_has_body(def_id)
     }
 
+    fn thir_body(&self, item: DefId) -> Result<stable_mir::thir::Thir, Error> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let def_id = item.internal(&mut *tables, tcx);
+        let Some(local_def_id) = def_id.as_local() else {
+            return Err(Error::new(format!("Item `{}` is not local", tcx.def_path_str(def_id))));
+        };
+        if tcx.hir().maybe_body_owned_by(local_def_id).is_none() {
+            return Err(Error::new(format!("Item `{}` has no body", tcx.def_path_str(def_id))));
+        }
+        let (thir, value) = tcx.thir_body(local_def_id).map_err(|_| {
+            Error::new(format!("Failed to build the THIR of `{}`", tcx.def_path_str(def_id)))
+        })?;
+        if thir.is_stolen() {
+            return Err(Error::new(format!(
+                "The THIR of `{}` was dropped once its MIR was checked, run the compiler with \
+                 `-Zno-steal-thir` to keep it",
+                tcx.def_path_str(def_id)
+            )));
+        }
+        let thir = thir.borrow();
+        Ok(stable_mir::thir::Thir {
+            params: thir.params.iter().map(|param| param.stable(&mut *tables)).collect(),
+            exprs: thir.exprs.iter().map(|expr| expr.stable(&mut *tables)).collect(),
+            stmts: thir.stmts.iter().map(|stmt| stmt.stable(&mut *tables)).collect(),
+            arms: thir.arms.iter().map(|arm| arm.stable(&mut *tables)).collect(),
+            blocks: thir.blocks.iter().map(|block| block.stable(&mut *tables)).collect(),
+            value: value.as_usize(),
+        })
+    }
+
     fn foreign_modules(&self, crate_num: CrateNum) -> Vec<stable_mir::ty::ForeignModuleDef> {
         let mut tables = self.0.borrow_mut();
         let tcx = tables.tcx;
@@ -211,6 +246,66 @@ fn explicit_predicates_of(
         }
     }
 
+    fn implements_trait(
+        &self,
+        trait_ref: &stable_mir::ty::TraitRef,
+        scope: Option<DefId>,
+    ) -> Solution<ImplSource> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let trait_ref = trait_ref.internal(&mut *tables, tcx);
+        let typing_env = typing_env(tcx, scope.map(|def_id| tables[def_id]));
+        let (infcx, param_env) =
+            tcx.infer_ctxt().ignoring_regions().build_with_typing_env(typing_env);
+        let obligation =
+            PredicateObligation::new(tcx, ObligationCause::dummy(), param_env, trait_ref);
+        if !infcx.predicate_may_hold(&obligation) {
+            return Solution::No;
+        }
+        if !infcx.predicate_must_hold_modulo_regions(&obligation) {
+            return Solution::Ambiguous;
+        }
+        match SelectionContext::new(&infcx).select(&obligation.with(tcx, trait_ref)) {
+            Ok(Some(impl_source)) => Solution::Yes(impl_source.stable(&mut *tables)),
+            Ok(None) | Err(_) => Solution::Ambiguous,
+        }
+    }
+
+    fn normalize(&self, ty: Ty, scope: Option<DefId>) -> Result<Ty, Error> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let ty = ty.internal(&mut *tables, tcx);
+        let typing_env = typing_env(tcx, scope.map(|def_id| tables[def_id]));
+        tcx.try_normalize_erasing_regions(typing_env, ty)
+            .map(|ty| ty.stable(&mut *tables))
+            .map_err(|err| Error::new(format!("Cannot normalize `{ty}`: {err:?}")))
+    }
+
+    fn is_subtype(&self, sub: Ty, sup: Ty, scope: Option<DefId>) -> Solution {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let sub = sub.internal(&mut *tables, tcx);
+        let sup = sup.internal(&mut *tables, tcx);
+        let typing_env = typing_env(tcx, scope.map(|def_id| tables[def_id]));
+        let (infcx, param_env) =
+            tcx.infer_ctxt().ignoring_regions().build_with_typing_env(typing_env);
+        let ocx = ObligationCtxt::new(&infcx);
+        let cause = ObligationCause::dummy();
+        let sub = ocx.normalize(&cause, param_env, sub);
+        let sup = ocx.normalize(&cause, param_env, sup);
+        if ocx.sub(&cause, param_env, sub, sup).is_err() {
+            return Solution::No;
+        }
+        let errors = ocx.select_all_or_error();
+        if errors.is_empty() {
+            Solution::Yes(())
+        } else if errors.iter().any(|error| error.is_true_error()) {
+            Solution::No
+        } else {
+            Solution::Ambiguous
+        }
+    }
+
     fn local_crate(&self) -> stable_mir::Crate {
         let tables = self.0.borrow();
         smir_crate(tables.tcx, LOCAL_CRATE)
@@ -865,3 +960,12 @@ fn data_layout(&self) -> &rustc_abi::TargetDataLayout {
         self.tcx.data_layout()
     }
 }
+
+/// The typing environment of the trait queries: the where-clauses of `scope` if any, and no
+/// where-clauses otherwise.
+fn typing_env(tcx: TyCtxt<'_>, scope: Option<rustc_span::def_id::DefId>) -> ty::TypingEnv<'_> {
+    match scope {
+        Some(def_id) => ty::TypingEnv::post_analysis(tcx, def_id),
+        None => ty::TypingEnv::fully_monomorphized(),
+    }
+}
@@ -7,6 +7,7 @@
 mod abi;
 mod error;
 mod mir;
+mod thir;
 mod ty;
 
 pub(crate) use ty::mir_const_from_ty_const;
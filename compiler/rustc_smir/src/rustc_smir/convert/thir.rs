@@ -0,0 +1,419 @@
+//! Conversion of internal Rust compiler `thir` items to stable ones.
+
+use rustc_middle::mir::CoercionSource;
+use rustc_middle::{mir, thir, ty};
+use stable_mir::opaque;
+use stable_mir::thir::VarId;
+
+use crate::rustc_smir::{Stable, Tables};
+
+impl<'tcx> Stable<'tcx> for thir::Param<'tcx> {
+    type T = stable_mir::thir::Param;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        stable_mir::thir::Param {
+            pat: self.pat.as_ref().map(|pat| pat.stable(tables)),
+            ty: self.ty.stable(tables),
+            ty_span: self.ty_span.map(|span| span.stable(tables)),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::Block {
+    type T = stable_mir::thir::Block;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        stable_mir::thir::Block {
+            span: self.span.stable(tables),
+            stmts: self.stmts.iter().map(|stmt| stmt.as_usize()).collect(),
+            expr: self.expr.map(|expr| expr.as_usize()),
+            safety_mode: self.safety_mode.stable(tables),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::BlockSafety {
+    type T = stable_mir::thir::BlockSafety;
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        match self {
+            thir::BlockSafety::Safe => stable_mir::thir::BlockSafety::Safe,
+            thir::BlockSafety::BuiltinUnsafe => stable_mir::thir::BlockSafety::BuiltinUnsafe,
+            thir::BlockSafety::ExplicitUnsafe(_) => stable_mir::thir::BlockSafety::ExplicitUnsafe,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::Stmt<'tcx> {
+    type T = stable_mir::thir::Stmt;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        let kind = match &self.kind {
+            thir::StmtKind::Expr { scope: _, expr } => {
+                stable_mir::thir::StmtKind::Expr { expr: expr.as_usize() }
+            }
+            thir::StmtKind::Let { pattern, initializer, else_block, span, .. } => {
+                stable_mir::thir::StmtKind::Let {
+                    pattern: pattern.stable(tables),
+                    initializer: initializer.map(|expr| expr.as_usize()),
+                    else_block: else_block.map(|block| block.as_usize()),
+                    span: span.stable(tables),
+                }
+            }
+        };
+        stable_mir::thir::Stmt { kind }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::Expr<'tcx> {
+    type T = stable_mir::thir::Expr;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        stable_mir::thir::Expr {
+            kind: self.kind.stable(tables),
+            ty: self.ty.stable(tables),
+            span: self.span.stable(tables),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::ExprKind<'tcx> {
+    type T = stable_mir::thir::ExprKind;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        use rustc_middle::thir::ExprKind::*;
+        use stable_mir::thir::ExprKind;
+        match self {
+            Scope { region_scope: _, lint_level: _, value } => {
+                ExprKind::Scope { value: value.as_usize() }
+            }
+            Box { value } => ExprKind::Box { value: value.as_usize() },
+            If { if_then_scope: _, cond, then, else_opt } => ExprKind::If {
+                cond: cond.as_usize(),
+                then: then.as_usize(),
+                else_opt: else_opt.map(|expr| expr.as_usize()),
+            },
+            Call { ty, fun, args, from_hir_call, fn_span } => ExprKind::Call {
+                ty: ty.stable(tables),
+                fun: fun.as_usize(),
+                args: expr_ids(args),
+                from_hir_call: *from_hir_call,
+                fn_span: fn_span.stable(tables),
+            },
+            Deref { arg } => ExprKind::Deref { arg: arg.as_usize() },
+            Binary { op, lhs, rhs } => {
+                ExprKind::Binary { op: op.stable(tables), lhs: lhs.as_usize(), rhs: rhs.as_usize() }
+            }
+            LogicalOp { op, lhs, rhs } => ExprKind::LogicalOp {
+                op: op.stable(tables),
+                lhs: lhs.as_usize(),
+                rhs: rhs.as_usize(),
+            },
+            Unary { op, arg } => ExprKind::Unary { op: op.stable(tables), arg: arg.as_usize() },
+            Cast { source } => ExprKind::Cast { source: source.as_usize() },
+            Use { source } => ExprKind::Use { source: source.as_usize() },
+            NeverToAny { source } => ExprKind::NeverToAny { source: source.as_usize() },
+            PointerCoercion { cast, source, is_from_as_cast } => {
+                let coercion_source = if *is_from_as_cast {
+                    CoercionSource::AsCast
+                } else {
+                    CoercionSource::Implicit
+                };
+                ExprKind::PointerCoercion {
+                    cast: mir::CastKind::PointerCoercion(*cast, coercion_source).stable(tables),
+                    source: source.as_usize(),
+                    is_from_as_cast: *is_from_as_cast,
+                }
+            }
+            Loop { body } => ExprKind::Loop { body: body.as_usize() },
+            Let { expr, pat } => ExprKind::Let { expr: expr.as_usize(), pat: pat.stable(tables) },
+            Match { scrutinee, scrutinee_hir_id: _, arms, match_source } => ExprKind::Match {
+                scrutinee: scrutinee.as_usize(),
+                arms: arms.iter().map(|arm| arm.as_usize()).collect(),
+                match_source: match_source.stable(tables),
+            },
+            Block { block } => ExprKind::Block { block: block.as_usize() },
+            Assign { lhs, rhs } => ExprKind::Assign { lhs: lhs.as_usize(), rhs: rhs.as_usize() },
+            AssignOp { op, lhs, rhs } => ExprKind::AssignOp {
+                op: op.stable(tables),
+                lhs: lhs.as_usize(),
+                rhs: rhs.as_usize(),
+            },
+            Field { lhs, variant_index, name } => ExprKind::Field {
+                lhs: lhs.as_usize(),
+                variant_index: variant_index.stable(tables),
+                name: name.stable(tables),
+            },
+            Index { lhs, index } => {
+                ExprKind::Index { lhs: lhs.as_usize(), index: index.as_usize() }
+            }
+            VarRef { id } => ExprKind::VarRef { id: id.stable(tables) },
+            UpvarRef { closure_def_id, var_hir_id } => ExprKind::UpvarRef {
+                closure: tables.closure_def(*closure_def_id),
+                var: var_hir_id.stable(tables),
+            },
+            Borrow { borrow_kind, arg } => {
+                ExprKind::Borrow { borrow_kind: borrow_kind.stable(tables), arg: arg.as_usize() }
+            }
+            RawBorrow { mutability, arg } => {
+                ExprKind::RawBorrow { mutability: mutability.stable(tables), arg: arg.as_usize() }
+            }
+            Break { label: _, value } => {
+                ExprKind::Break { value: value.map(|expr| expr.as_usize()) }
+            }
+            Continue { label: _ } => ExprKind::Continue,
+            Return { value } => ExprKind::Return { value: value.map(|expr| expr.as_usize()) },
+            Become { value } => ExprKind::Become { value: value.as_usize() },
+            ConstBlock { did, args } => {
+                ExprKind::ConstBlock { def: tables.const_def(*did), args: args.stable(tables) }
+            }
+            Repeat { value, count } => {
+                ExprKind::Repeat { value: value.as_usize(), count: count.stable(tables) }
+            }
+            Array { fields } => ExprKind::Array { fields: expr_ids(fields) },
+            Tuple { fields } => ExprKind::Tuple { fields: expr_ids(fields) },
+            Adt(adt_expr) => ExprKind::Adt(adt_expr.stable(tables)),
+            PlaceTypeAscription { source, .. } => {
+                ExprKind::PlaceTypeAscription { source: source.as_usize() }
+            }
+            ValueTypeAscription { source, .. } => {
+                ExprKind::ValueTypeAscription { source: source.as_usize() }
+            }
+            Closure(closure_expr) => ExprKind::Closure(closure_expr.stable(tables)),
+            Literal { lit, neg } => ExprKind::Literal { lit: opaque(&lit.node), neg: *neg },
+            NonHirLiteral { lit, user_ty: _ } => ExprKind::NonHirLiteral { lit: opaque(lit) },
+            ZstLiteral { user_ty: _ } => ExprKind::ZstLiteral,
+            NamedConst { def_id, args, user_ty: _ } => {
+                ExprKind::NamedConst { def: tables.const_def(*def_id), args: args.stable(tables) }
+            }
+            ConstParam { param, def_id: _ } => ExprKind::ConstParam { param: param.stable(tables) },
+            StaticRef { alloc_id: _, ty, def_id } => {
+                ExprKind::StaticRef { def: tables.static_def(*def_id), ty: ty.stable(tables) }
+            }
+            InlineAsm(inline_asm) => ExprKind::InlineAsm(opaque(inline_asm)),
+            OffsetOf { container, fields } => ExprKind::OffsetOf {
+                container: container.stable(tables),
+                fields: fields
+                    .iter()
+                    .map(|(variant, field)| (variant.stable(tables), field.stable(tables)))
+                    .collect(),
+            },
+            ThreadLocalRef(def_id) => ExprKind::ThreadLocalRef(tables.static_def(*def_id)),
+            Yield { value } => ExprKind::Yield { value: value.as_usize() },
+        }
+    }
+}
+
+fn expr_ids(ids: &[thir::ExprId]) -> Vec<stable_mir::thir::ExprId> {
+    ids.iter().map(|id| id.as_usize()).collect()
+}
+
+impl<'tcx> Stable<'tcx> for thir::LogicalOp {
+    type T = stable_mir::thir::LogicalOp;
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        match self {
+            thir::LogicalOp::And => stable_mir::thir::LogicalOp::And,
+            thir::LogicalOp::Or => stable_mir::thir::LogicalOp::Or,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for rustc_hir::MatchSource {
+    type T = stable_mir::thir::MatchSource;
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        use rustc_hir::MatchSource::*;
+        match self {
+            Normal => stable_mir::thir::MatchSource::Normal,
+            Postfix => stable_mir::thir::MatchSource::Postfix,
+            ForLoopDesugar => stable_mir::thir::MatchSource::ForLoopDesugar,
+            TryDesugar(_) => stable_mir::thir::MatchSource::TryDesugar,
+            AwaitDesugar => stable_mir::thir::MatchSource::AwaitDesugar,
+            FormatArgs => stable_mir::thir::MatchSource::FormatArgs,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::LocalVarId {
+    type T = VarId;
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        // Closures are in the same HIR owner as their parent, so the local id is enough to
+        // identify a variable of a body.
+        VarId(self.0.local_id.as_usize())
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::AdtExpr<'tcx> {
+    type T = stable_mir::thir::AdtExpr;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        stable_mir::thir::AdtExpr {
+            adt_def: tables.adt_def(self.adt_def.did()),
+            variant_index: self.variant_index.stable(tables),
+            args: self.args.stable(tables),
+            fields: self
+                .fields
+                .iter()
+                .map(|field| stable_mir::thir::FieldExpr {
+                    name: field.name.stable(tables),
+                    expr: field.expr.as_usize(),
+                })
+                .collect(),
+            base: match &self.base {
+                thir::AdtExprBase::None => stable_mir::thir::AdtExprBase::None,
+                thir::AdtExprBase::Base(fru_info) => {
+                    stable_mir::thir::AdtExprBase::Base(fru_info.base.as_usize())
+                }
+                thir::AdtExprBase::DefaultFields(_) => stable_mir::thir::AdtExprBase::DefaultFields,
+            },
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::ClosureExpr<'tcx> {
+    type T = stable_mir::thir::ClosureExpr;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        let tcx = tables.tcx;
+        stable_mir::thir::ClosureExpr {
+            def: tables.closure_def(self.closure_id.to_def_id()),
+            upvars: self.upvars.iter().map(|upvar| upvar.as_usize()).collect(),
+            captures: tcx
+                .closure_captures(self.closure_id)
+                .iter()
+                .map(|captured| stable_mir::thir::Capture {
+                    name: captured.to_string(tcx),
+                    var: VarId(captured.get_root_variable().local_id.as_usize()),
+                    kind: captured.info.capture_kind.stable(tables),
+                    mutability: captured.mutability.stable(tables),
+                    span: captured.get_path_span(tcx).stable(tables),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::UpvarCapture {
+    type T = stable_mir::thir::CaptureKind;
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        use stable_mir::thir::{CaptureBorrowKind, CaptureKind};
+        match self {
+            ty::UpvarCapture::ByValue => CaptureKind::ByValue,
+            ty::UpvarCapture::ByRef(kind) => CaptureKind::ByRef(match kind {
+                ty::BorrowKind::Immutable => CaptureBorrowKind::Immutable,
+                ty::BorrowKind::UniqueImmutable => CaptureBorrowKind::UniqueImmutable,
+                ty::BorrowKind::Mutable => CaptureBorrowKind::Mutable,
+            }),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::Arm<'tcx> {
+    type T = stable_mir::thir::Arm;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        stable_mir::thir::Arm {
+            pattern: self.pattern.stable(tables),
+            guard: self.guard.map(|expr| expr.as_usize()),
+            body: self.body.as_usize(),
+            span: self.span.stable(tables),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::Pat<'tcx> {
+    type T = stable_mir::thir::Pat;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        stable_mir::thir::Pat {
+            ty: self.ty.stable(tables),
+            span: self.span.stable(tables),
+            kind: self.kind.stable(tables),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::PatKind<'tcx> {
+    type T = stable_mir::thir::PatKind;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        use rustc_middle::thir::PatKind::*;
+        use stable_mir::thir::PatKind;
+        match self {
+            Wild => PatKind::Wild,
+            AscribeUserType { ascription: _, subpattern } => {
+                PatKind::AscribeUserType { subpattern: Box::new(subpattern.stable(tables)) }
+            }
+            Binding { name, mode, var, ty, subpattern, is_primary } => PatKind::Binding {
+                name: name.stable(tables),
+                mode: mode.stable(tables),
+                var: var.stable(tables),
+                ty: ty.stable(tables),
+                subpattern: subpattern.as_ref().map(|pat| Box::new(pat.stable(tables))),
+                is_primary: *is_primary,
+            },
+            Variant { adt_def, args, variant_index, subpatterns } => PatKind::Variant {
+                adt_def: tables.adt_def(adt_def.did()),
+                args: args.stable(tables),
+                variant_index: variant_index.stable(tables),
+                subpatterns: subpatterns.iter().map(|pat| pat.stable(tables)).collect(),
+            },
+            Leaf { subpatterns } => PatKind::Leaf {
+                subpatterns: subpatterns.iter().map(|pat| pat.stable(tables)).collect(),
+            },
+            Deref { subpattern } => {
+                PatKind::Deref { subpattern: Box::new(subpattern.stable(tables)) }
+            }
+            DerefPattern { subpattern, mutability } => PatKind::DerefPattern {
+                subpattern: Box::new(subpattern.stable(tables)),
+                mutability: mutability.stable(tables),
+            },
+            Constant { value } => PatKind::Constant { value: value.stable(tables) },
+            ExpandedConstant { def_id, is_inline, subpattern } => PatKind::ExpandedConstant {
+                def: tables.const_def(*def_id),
+                is_inline: *is_inline,
+                subpattern: Box::new(subpattern.stable(tables)),
+            },
+            Range(range) => {
+                let mut bound = |bound: &thir::PatRangeBoundary<'tcx>| match bound {
+                    thir::PatRangeBoundary::Finite(value) => Some(value.stable(tables)),
+                    thir::PatRangeBoundary::NegInfinity | thir::PatRangeBoundary::PosInfinity => {
+                        None
+                    }
+                };
+                PatKind::Range {
+                    lo: bound(&range.lo),
+                    hi: bound(&range.hi),
+                    include_end: matches!(range.end, rustc_hir::RangeEnd::Included),
+                }
+            }
+            Slice { prefix, slice, suffix } => PatKind::Slice {
+                prefix: prefix.iter().map(|pat| pat.stable(tables)).collect(),
+                slice: slice.as_ref().map(|pat| Box::new(pat.stable(tables))),
+                suffix: suffix.iter().map(|pat| pat.stable(tables)).collect(),
+            },
+            Array { prefix, slice, suffix } => PatKind::Array {
+                prefix: prefix.iter().map(|pat| pat.stable(tables)).collect(),
+                slice: slice.as_ref().map(|pat| Box::new(pat.stable(tables))),
+                suffix: suffix.iter().map(|pat| pat.stable(tables)).collect(),
+            },
+            Or { pats } => {
+                PatKind::Or { pats: pats.iter().map(|pat| pat.stable(tables)).collect() }
+            }
+            Never => PatKind::Never,
+            Error(_) => PatKind::Error,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for thir::FieldPat<'tcx> {
+    type T = stable_mir::thir::FieldPat;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        stable_mir::thir::FieldPat {
+            field: self.field.stable(tables),
+            pattern: self.pattern.stable(tables),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for rustc_hir::BindingMode {
+    type T = stable_mir::thir::BindingMode;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        let rustc_hir::BindingMode(by_ref, mutability) = self;
+        stable_mir::thir::BindingMode {
+            by_ref: match by_ref {
+                rustc_hir::ByRef::Yes(mutability) => Some(mutability.stable(tables)),
+                rustc_hir::ByRef::No => None,
+            },
+            mutability: mutability.stable(tables),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 //! Conversion of internal Rust compiler `ty` items to stable ones.
 
 use rustc_middle::ty::Ty;
-use rustc_middle::{mir, ty};
+use rustc_middle::{mir, traits, ty};
 use stable_mir::ty::{
     AdtKind, FloatTy, GenericArgs, GenericParamDef, IntTy, Region, RigidTy, TyKind, UintTy,
 };
@@ -568,6 +568,21 @@ fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
     }
 }
 
+impl<'tcx, N> Stable<'tcx> for traits::ImplSource<'tcx, N> {
+    type T = stable_mir::ty::ImplSource;
+    fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
+        use stable_mir::ty::ImplSource;
+
+        match self {
+            traits::ImplSource::UserDefined(data) => {
+                ImplSource::UserDefined(tables.impl_def(data.impl_def_id))
+            }
+            traits::ImplSource::Param(_) => ImplSource::Param,
+            traits::ImplSource::Builtin(..) => ImplSource::Builtin,
+        }
+    }
+}
+
 impl<'tcx> Stable<'tcx> for ty::Generics {
     type T = stable_mir::ty::Generics;
 
@@ -22,7 +22,9 @@
     self, AdtDef, CoroutineArgsExt, EarlyBinder, GenericArgsRef, PseudoCanonicalInput, Ty, TyCtxt,
     TypeVisitableExt,
 };
-use rustc_session::{DataTypeKind, FieldInfo, FieldKind, SizeKind, VariantInfo};
+use rustc_session::{
+    DataTypeKind, DiscrEncoding, DiscrInfo, FieldInfo, FieldKind, NicheInfo, SizeKind, VariantInfo,
+};
 use rustc_span::sym;
 use rustc_span::symbol::Symbol;
 use tracing::{debug, instrument, trace};
@@ -1031,6 +1033,22 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx>, layout: TyAndLayout<'tc
     // (delay format until we actually need it)
     let record = |kind, packed, opt_discr_size, variants| {
         let type_desc = with_no_trimmed_paths!(format!("{}", layout.ty));
+        let discr = match layout.variants {
+            Variants::Single { .. } => None,
+            Variants::Multiple { tag, ref tag_encoding, tag_field, .. } => Some(DiscrInfo {
+                encoding: match tag_encoding {
+                    TagEncoding::Direct => DiscrEncoding::Direct,
+                    TagEncoding::Niche { .. } => DiscrEncoding::Niche,
+                },
+                offset: layout.fields.offset(tag_field).bytes(),
+                size: tag.size(cx).bytes(),
+            }),
+        };
+        let largest_niche = layout.largest_niche.map(|niche| NicheInfo {
+            offset: niche.offset.bytes(),
+            size: niche.value.size(cx).bytes(),
+            available: niche.available(cx),
+        });
         cx.tcx().sess.code_stats.record_type_size(
             kind,
             type_desc,
@@ -1038,6 +1056,8 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx>, layout: TyAndLayout<'tc
             layout.size,
             packed,
             opt_discr_size,
+            discr,
+            largest_niche,
             variants,
         );
     };
@@ -14,12 +14,12 @@
 use crate::ty::{
     AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, FieldDef, FnDef, ForeignDef,
     ForeignItemKind, ForeignModule, ForeignModuleDef, GenericArgs, GenericPredicates, Generics,
-    ImplDef, ImplTrait, IntrinsicDef, LineInfo, MirConst, PolyFnSig, RigidTy, Span, TraitDecl,
-    TraitDef, Ty, TyConst, TyConstId, TyKind, UintTy, VariantDef,
+    ImplDef, ImplSource, ImplTrait, IntrinsicDef, LineInfo, MirConst, PolyFnSig, RigidTy, Solution,
+    Span, TraitDecl, TraitDef, TraitRef, Ty, TyConst, TyConstId, TyKind, UintTy, VariantDef,
 };
 use crate::{
     Crate, CrateItem, CrateItems, CrateNum, DefId, Error, Filename, ImplTraitDecls, ItemKind,
-    Symbol, TraitDecls, mir,
+    Symbol, TraitDecls, mir, thir,
 };
 
 /// This trait defines the interface between stable_mir and the Rust compiler.
@@ -33,6 +33,8 @@ pub trait Context {
     fn mir_body(&self, item: DefId) -> mir::Body;
     /// Check whether the body of a function is available.
     fn has_body(&self, item: DefId) -> bool;
+    /// Retrieve the typed HIR of a local item.
+    fn thir_body(&self, item: DefId) -> Result<thir::Thir, Error>;
     fn foreign_modules(&self, crate_num: CrateNum) -> Vec<ForeignModuleDef>;
 
     /// Retrieve all functions defined in this crate.
@@ -51,6 +53,12 @@ pub trait Context {
     fn generics_of(&self, def_id: DefId) -> Generics;
     fn predicates_of(&self, def_id: DefId) -> GenericPredicates;
     fn explicit_predicates_of(&self, def_id: DefId) -> GenericPredicates;
+    /// Check whether a trait is implemented, assuming that the where-clauses of `scope` hold.
+    fn implements_trait(&self, trait_ref: &TraitRef, scope: Option<DefId>) -> Solution<ImplSource>;
+    /// Normalize a type, assuming that the where-clauses of `scope` hold.
+    fn normalize(&self, ty: Ty, scope: Option<DefId>) -> Result<Ty, Error>;
+    /// Check whether `sub` is a subtype of `sup`, assuming that the where-clauses of `scope` hold.
+    fn is_subtype(&self, sub: Ty, sup: Ty, scope: Option<DefId>) -> Solution;
     /// Get information about the local crate.
     fn local_crate(&self) -> Crate;
     /// Retrieve a list of all external crates.
@@ -266,6 +274,17 @@ pub fn run<F, T>(context: &dyn Context, f: F) -> Result<T, Error>
     }
 }
 
+/// Like [run], but it may be called while StableMIR is already running, e.g. when the compiler
+/// invokes a body transformation while answering a request. The outer context is restored once
+/// `f` returns.
+pub fn run_nested<F, T>(context: &dyn Context, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let ptr: *const () = (&raw const context) as _;
+    TLV.set(&Cell::new(ptr), f)
+}
+
 /// Execute the given function with access the compiler [Context].
 ///
 /// I.e., This function will load the current context and calls a function with it.
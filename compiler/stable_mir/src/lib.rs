@@ -37,6 +37,7 @@
 pub mod error;
 pub mod mir;
 pub mod target;
+pub mod thir;
 pub mod ty;
 pub mod visitor;
 
@@ -139,6 +140,18 @@ pub fn body(&self) -> mir::Body {
         with(|cx| cx.mir_body(self.0))
     }
 
+    /// Return the typed HIR of this item, i.e. its expression tree before it's lowered to MIR.
+    ///
+    /// The compiler drops the typed HIR of a body once it has built and checked its MIR, which
+    /// happens during the analysis. To inspect it after the analysis, e.g. from the callback of
+    /// `run!`, the compiler must be run with `-Zno-steal-thir`.
+    ///
+    /// This returns an error if the item isn't local, if it has no body, or if its typed HIR was
+    /// already dropped because `-Zno-steal-thir` wasn't passed.
+    pub fn thir(&self) -> Result<thir::Thir, Error> {
+        with(|cx| cx.thir_body(self.0))
+    }
+
     pub fn span(&self) -> Span {
         with(|cx| cx.span_of_an_item(self.0))
     }
@@ -95,6 +95,12 @@ pub fn local_decls(&self) -> impl Iterator<Item = (Local, &LocalDecl)> {
         self.locals.iter().enumerate()
     }
 
+    /// Add a new local to this function, e.g. to hold the result of an inserted call.
+    pub fn add_local(&mut self, ty: Ty, span: Span, mutability: Mutability) -> Local {
+        self.locals.push(LocalDecl { ty, span, mutability });
+        self.locals.len() - 1
+    }
+
     /// Emit the body using the provided name for the signature.
     pub fn dump<W: io::Write>(&self, w: &mut W, fn_name: &str) -> io::Result<()> {
         function_body(w, self, fn_name)
@@ -0,0 +1,553 @@
+//! Module that represents the typed HIR (THIR) of a body.
+//!
+//! The THIR is the expression tree of a body after type checking, right before it's lowered to
+//! MIR. Method calls are resolved, overloaded operators are turned into calls, and implicit
+//! operations such as auto-derefs, auto-borrows and coercions are explicit.
+//!
+//! Expressions, statements, match arms and blocks are stored in [Thir] and refer to each other by
+//! index. Use [CrateItem::thir] to retrieve the THIR of an item.
+//!
+//! A MIR local that holds a user variable has the span of the binding that declares it. Use
+//! [Thir::var_bound_at] to find the variable of such a local, and [ExprKind::VarRef] to find the
+//! expressions that use it.
+//!
+//! [CrateItem::thir]: crate::CrateItem::thir
+
+use serde::Serialize;
+
+use crate::mir::mono::StaticDef;
+use crate::mir::{BinOp, BorrowKind, CastKind, FieldIdx, Mutability, UnOp};
+use crate::ty::{
+    AdtDef, ClosureDef, ConstDef, GenericArgs, MirConst, ParamConst, Span, Ty, TyConst, VariantIdx,
+};
+use crate::{Opaque, Symbol};
+
+/// The index of an expression in [Thir::exprs].
+pub type ExprId = usize;
+
+/// The index of a statement in [Thir::stmts].
+pub type StmtId = usize;
+
+/// The index of a match arm in [Thir::arms].
+pub type ArmId = usize;
+
+/// The index of a block in [Thir::blocks].
+pub type BlockId = usize;
+
+/// The THIR of a function, a constant or a closure.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Thir {
+    /// The parameters of the function or closure. This is empty for constants.
+    pub params: Vec<Param>,
+    pub exprs: Vec<Expr>,
+    pub stmts: Vec<Stmt>,
+    pub arms: Vec<Arm>,
+    pub blocks: Vec<Block>,
+    /// The expression that is the value of the body.
+    pub value: ExprId,
+}
+
+impl Thir {
+    /// Returns the variable declared by the binding at `span`, if any.
+    ///
+    /// The MIR local of a user variable has the span of its binding, so this maps such a local
+    /// back to the variable.
+    pub fn var_bound_at(&self, span: Span) -> Option<VarId> {
+        self.bindings().into_iter().find_map(|pat| match pat.kind {
+            PatKind::Binding { var, .. } if pat.span == span => Some(var),
+            _ => None,
+        })
+    }
+
+    /// Returns the pattern of every binding in the body.
+    pub fn bindings(&self) -> Vec<&Pat> {
+        let mut bindings = Vec::new();
+        self.visit_pats(|pat| {
+            if matches!(pat.kind, PatKind::Binding { .. }) {
+                bindings.push(pat);
+            }
+        });
+        bindings
+    }
+
+    /// Calls `f` with every pattern of the body and their sub-patterns.
+    fn visit_pats<'a>(&'a self, mut f: impl FnMut(&'a Pat)) {
+        let params = self.params.iter().filter_map(|param| param.pat.as_ref());
+        let lets = self.stmts.iter().filter_map(|stmt| match &stmt.kind {
+            StmtKind::Let { pattern, .. } => Some(pattern),
+            StmtKind::Expr { .. } => None,
+        });
+        let let_exprs = self.exprs.iter().filter_map(|expr| match &expr.kind {
+            ExprKind::Let { pat, .. } => Some(pat),
+            _ => None,
+        });
+        let arms = self.arms.iter().map(|arm| &arm.pattern);
+        for pat in params.chain(lets).chain(let_exprs).chain(arms) {
+            pat.walk(&mut f);
+        }
+    }
+}
+
+/// Identifies a local variable of a body.
+///
+/// Closures share the identifiers of the body they're defined in, so a variable that is captured
+/// by a closure has the same identifier in the THIR of both bodies.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize)]
+pub struct VarId(pub usize);
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Param {
+    /// The pattern of the parameter. This is `None` for the implicit parameters of closures.
+    pub pat: Option<Pat>,
+    pub ty: Ty,
+    /// The span of the type annotation of the parameter, if there's one.
+    pub ty_span: Option<Span>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Block {
+    pub span: Span,
+    pub stmts: Vec<StmtId>,
+    /// The trailing expression of the block, if any.
+    pub expr: Option<ExprId>,
+    pub safety_mode: BlockSafety,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum BlockSafety {
+    Safe,
+    /// A compiler-generated unsafe block.
+    BuiltinUnsafe,
+    /// An `unsafe` block.
+    ExplicitUnsafe,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Stmt {
+    pub kind: StmtKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum StmtKind {
+    /// An expression with a trailing semicolon.
+    Expr { expr: ExprId },
+    /// A `let` statement.
+    Let {
+        pattern: Pat,
+        initializer: Option<ExprId>,
+        /// The `else` block of a `let ... else` statement.
+        else_block: Option<BlockId>,
+        span: Span,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub ty: Ty,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum ExprKind {
+    /// A region scope introduced by the compiler, which is otherwise the same as `value`.
+    Scope {
+        value: ExprId,
+    },
+    /// A `box <value>` expression.
+    Box {
+        value: ExprId,
+    },
+    If {
+        cond: ExprId,
+        then: ExprId,
+        else_opt: Option<ExprId>,
+    },
+    /// A function call. Method calls and overloaded operators are also turned into calls.
+    Call {
+        /// The type of the function. For calls to a function item or a method, this is a
+        /// [FnDef](crate::ty::RigidTy::FnDef) with the resolved function and its generic
+        /// arguments.
+        ty: Ty,
+        fun: ExprId,
+        args: Vec<ExprId>,
+        /// Whether this is a call in the source, as opposed to an overloaded operator.
+        from_hir_call: bool,
+        /// The span of the function, without the dot and receiver of a method call.
+        fn_span: Span,
+    },
+    /// A dereference, such as `*x`.
+    Deref {
+        arg: ExprId,
+    },
+    /// A binary operation on primitive types. Overloaded operators are [ExprKind::Call]s.
+    Binary {
+        op: BinOp,
+        lhs: ExprId,
+        rhs: ExprId,
+    },
+    /// A short-circuiting `&&` or `||`.
+    LogicalOp {
+        op: LogicalOp,
+        lhs: ExprId,
+        rhs: ExprId,
+    },
+    /// A unary operation on primitive types. Overloaded operators are [ExprKind::Call]s.
+    Unary {
+        op: UnOp,
+        arg: ExprId,
+    },
+    /// A numeric or pointer cast, such as `x as u8`.
+    Cast {
+        source: ExprId,
+    },
+    /// A use of a place or value that doesn't change it, such as a cast to the same type.
+    Use {
+        source: ExprId,
+    },
+    /// Coerces an expression of type `!` to any type.
+    NeverToAny {
+        source: ExprId,
+    },
+    /// A pointer coercion, either implicit or with an `as` cast.
+    PointerCoercion {
+        cast: CastKind,
+        source: ExprId,
+        is_from_as_cast: bool,
+    },
+    /// A `loop`. `while` and `for` loops are turned into `loop`s.
+    Loop {
+        body: ExprId,
+    },
+    /// A `let <pat> = <expr>` in an `if` or `while` condition, or in a match guard.
+    Let {
+        expr: ExprId,
+        pat: Pat,
+    },
+    Match {
+        scrutinee: ExprId,
+        arms: Vec<ArmId>,
+        match_source: MatchSource,
+    },
+    Block {
+        block: BlockId,
+    },
+    Assign {
+        lhs: ExprId,
+        rhs: ExprId,
+    },
+    /// A compound assignment on primitive types, such as `x += 1`.
+    AssignOp {
+        op: BinOp,
+        lhs: ExprId,
+        rhs: ExprId,
+    },
+    /// An access to the field `name` of the variant `variant_index` of `lhs`.
+    Field {
+        lhs: ExprId,
+        variant_index: VariantIdx,
+        name: FieldIdx,
+    },
+    /// An index into a slice or an array. Overloaded indexing is a call to `Index::index`.
+    Index {
+        lhs: ExprId,
+        index: ExprId,
+    },
+    /// A use of a local variable of this body.
+    VarRef {
+        id: VarId,
+    },
+    /// A use of a variable that is captured by the closure `closure`.
+    UpvarRef {
+        closure: ClosureDef,
+        var: VarId,
+    },
+    /// A borrow, such as `&x`, or an auto-borrow.
+    Borrow {
+        borrow_kind: BorrowKind,
+        arg: ExprId,
+    },
+    /// A `&raw const` or `&raw mut` borrow.
+    RawBorrow {
+        mutability: Mutability,
+        arg: ExprId,
+    },
+    Break {
+        value: Option<ExprId>,
+    },
+    Continue,
+    Return {
+        value: Option<ExprId>,
+    },
+    /// An explicit tail call, such as `become f()`.
+    Become {
+        value: ExprId,
+    },
+    /// An inline `const` block.
+    ConstBlock {
+        def: ConstDef,
+        args: GenericArgs,
+    },
+    /// An array repeat expression, such as `[x; 4]`.
+    Repeat {
+        value: ExprId,
+        count: TyConst,
+    },
+    Array {
+        fields: Vec<ExprId>,
+    },
+    Tuple {
+        fields: Vec<ExprId>,
+    },
+    /// A struct literal, an enum variant or a union literal.
+    Adt(AdtExpr),
+    /// A type ascription on a place, such as the pattern of `let x: T = ...`.
+    PlaceTypeAscription {
+        source: ExprId,
+    },
+    /// A type ascription on a value.
+    ValueTypeAscription {
+        source: ExprId,
+    },
+    Closure(ClosureExpr),
+    /// A literal in the source. `neg` is true for a negated literal, such as `-1`.
+    Literal {
+        lit: Opaque,
+        neg: bool,
+    },
+    /// A literal generated by the compiler.
+    NonHirLiteral {
+        lit: Opaque,
+    },
+    /// A zero-sized literal, such as a function item or a unit struct.
+    ZstLiteral,
+    /// A use of a named constant, including associated constants.
+    NamedConst {
+        def: ConstDef,
+        args: GenericArgs,
+    },
+    /// A use of a const generic parameter.
+    ConstParam {
+        param: ParamConst,
+    },
+    /// A reference to a static item.
+    StaticRef {
+        def: StaticDef,
+        ty: Ty,
+    },
+    InlineAsm(Opaque),
+    /// An `offset_of!` expression. `fields` lists the variant and field of each step.
+    OffsetOf {
+        container: Ty,
+        fields: Vec<(VariantIdx, FieldIdx)>,
+    },
+    /// A reference to a `#[thread_local]` static.
+    ThreadLocalRef(StaticDef),
+    /// A `yield` in a coroutine.
+    Yield {
+        value: ExprId,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum LogicalOp {
+    /// The `&&` operator.
+    And,
+    /// The `||` operator.
+    Or,
+}
+
+/// The source of a `match` expression.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum MatchSource {
+    /// A `match _ { .. }`.
+    Normal,
+    /// A `expr.match { .. }`.
+    Postfix,
+    /// A desugared `for _ in _ { .. }` loop.
+    ForLoopDesugar,
+    /// A desugared `?` operator.
+    TryDesugar,
+    /// A desugared `<expr>.await`.
+    AwaitDesugar,
+    /// A desugared `format_args!()`.
+    FormatArgs,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct AdtExpr {
+    pub adt_def: AdtDef,
+    pub variant_index: VariantIdx,
+    pub args: GenericArgs,
+    /// The fields that are explicitly initialized.
+    pub fields: Vec<FieldExpr>,
+    pub base: AdtExprBase,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct FieldExpr {
+    pub name: FieldIdx,
+    pub expr: ExprId,
+}
+
+/// How the fields that are not explicitly initialized get their value.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum AdtExprBase {
+    /// All the fields are explicitly initialized.
+    None,
+    /// The remaining fields are taken from the given expression, such as `..base`.
+    Base(ExprId),
+    /// The remaining fields use their default value, with `..`.
+    DefaultFields,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ClosureExpr {
+    pub def: ClosureDef,
+    /// The values of the captures, in the same order as `captures`.
+    pub upvars: Vec<ExprId>,
+    pub captures: Vec<Capture>,
+}
+
+/// A place captured by a closure.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Capture {
+    /// The captured place as it's written in the source, such as `x` or `x.field`.
+    pub name: String,
+    /// The variable that the captured place is based on.
+    pub var: VarId,
+    pub kind: CaptureKind,
+    /// Whether the captured place can be mutated in the closure.
+    pub mutability: Mutability,
+    /// The span of the use of the captured place in the closure.
+    pub span: Span,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum CaptureKind {
+    ByValue,
+    ByRef(CaptureBorrowKind),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum CaptureBorrowKind {
+    /// A shared borrow.
+    Immutable,
+    /// A unique but immutable borrow, used to mutate through a mutable reference.
+    UniqueImmutable,
+    /// A mutable borrow.
+    Mutable,
+}
+
+/// A `match` arm.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Arm {
+    pub pattern: Pat,
+    pub guard: Option<ExprId>,
+    pub body: ExprId,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Pat {
+    pub ty: Ty,
+    pub span: Span,
+    pub kind: PatKind,
+}
+
+impl Pat {
+    /// Calls `f` with this pattern and all its sub-patterns.
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a Pat)) {
+        f(self);
+        match &self.kind {
+            PatKind::Wild
+            | PatKind::Constant { .. }
+            | PatKind::Range { .. }
+            | PatKind::Never
+            | PatKind::Error => {}
+            PatKind::AscribeUserType { subpattern }
+            | PatKind::Deref { subpattern }
+            | PatKind::DerefPattern { subpattern, .. }
+            | PatKind::ExpandedConstant { subpattern, .. } => subpattern.walk(f),
+            PatKind::Binding { subpattern, .. } => {
+                if let Some(subpattern) = subpattern {
+                    subpattern.walk(f);
+                }
+            }
+            PatKind::Variant { subpatterns, .. } | PatKind::Leaf { subpatterns } => {
+                for field in subpatterns {
+                    field.pattern.walk(f);
+                }
+            }
+            PatKind::Slice { prefix, slice, suffix } | PatKind::Array { prefix, slice, suffix } => {
+                prefix.iter().chain(slice.as_deref()).chain(suffix).for_each(|pat| pat.walk(f));
+            }
+            PatKind::Or { pats } => pats.iter().for_each(|pat| pat.walk(f)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum PatKind {
+    /// A wildcard pattern, `_`.
+    Wild,
+    /// A pattern with a type annotation.
+    AscribeUserType { subpattern: Box<Pat> },
+    /// A binding of the variable `var`, such as `x`, `ref mut x` or `x @ subpattern`.
+    Binding {
+        name: Symbol,
+        mode: BindingMode,
+        var: VarId,
+        /// The type of the variable, which is different from the type of the pattern for
+        /// bindings by reference.
+        ty: Ty,
+        subpattern: Option<Box<Pat>>,
+        /// Whether this is the first binding of `var`. Each alternative of an or-pattern has a
+        /// binding of the same variable.
+        is_primary: bool,
+    },
+    /// A variant of an enum, such as `Some(x)`.
+    Variant {
+        adt_def: AdtDef,
+        args: GenericArgs,
+        variant_index: VariantIdx,
+        subpatterns: Vec<FieldPat>,
+    },
+    /// A struct, a tuple or a union.
+    Leaf { subpatterns: Vec<FieldPat> },
+    /// A `&pat` or `box pat` pattern, or an implicit dereference.
+    Deref { subpattern: Box<Pat> },
+    /// A deref pattern, which dereferences through `Deref::deref`.
+    DerefPattern { subpattern: Box<Pat>, mutability: Mutability },
+    /// A constant that is compared with `PartialEq`, or a literal.
+    Constant { value: MirConst },
+    /// A named or inline constant that was expanded into `subpattern`.
+    ExpandedConstant { def: ConstDef, is_inline: bool, subpattern: Box<Pat> },
+    /// A range pattern. A missing bound is unbounded.
+    Range { lo: Option<MirConst>, hi: Option<MirConst>, include_end: bool },
+    /// A slice pattern, such as `[a, .., z]`.
+    Slice { prefix: Vec<Pat>, slice: Option<Box<Pat>>, suffix: Vec<Pat> },
+    /// A slice pattern matched on an array.
+    Array { prefix: Vec<Pat>, slice: Option<Box<Pat>>, suffix: Vec<Pat> },
+    /// An or-pattern, such as `A | B`.
+    Or { pats: Vec<Pat> },
+    /// A never pattern, `!`.
+    Never,
+    /// A pattern that failed to type check.
+    Error,
+}
+
+/// The pattern of the field `field`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct FieldPat {
+    pub field: FieldIdx,
+    pub pattern: Pat,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct BindingMode {
+    /// Whether the binding is by reference, such as `ref x`, and the mutability of the reference.
+    pub by_ref: Option<Mutability>,
+    /// The mutability of the binding itself, such as `mut x`.
+    pub mutability: Mutability,
+}
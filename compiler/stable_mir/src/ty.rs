@@ -103,6 +103,22 @@ impl Ty {
     pub fn kind(&self) -> TyKind {
         with(|context| context.ty_kind(*self))
     }
+
+    /// Normalize the associated type projections in this type, assuming that the where-clauses of
+    /// `scope` hold. Use `None` for types that don't depend on generic parameters.
+    ///
+    /// Regions are erased in the normalized type.
+    pub fn normalize(&self, scope: Option<DefId>) -> Result<Ty, Error> {
+        with(|cx| cx.normalize(*self, scope))
+    }
+
+    /// Check whether this type is a subtype of `other`, assuming that the where-clauses of `scope`
+    /// hold. Use `None` for types that don't depend on generic parameters.
+    ///
+    /// Regions are ignored, so `&'a T` is a subtype of `&'b T` for any `'a` and `'b`.
+    pub fn is_subtype_of(&self, other: Ty, scope: Option<DefId>) -> Solution {
+        with(|cx| cx.is_subtype(*self, other, scope))
+    }
 }
 
 /// Represents a pattern in the type system
@@ -1402,6 +1418,38 @@ pub fn self_ty(&self) -> Ty {
         };
         self_ty
     }
+
+    /// Check whether the trait is implemented, assuming that the where-clauses of `scope` hold.
+    /// Use `None` for types that don't depend on generic parameters.
+    ///
+    /// If it is, this returns how the trait is implemented. Regions are ignored.
+    pub fn is_implemented(&self, scope: Option<DefId>) -> Solution<ImplSource> {
+        with(|cx| cx.implements_trait(self, scope))
+    }
+}
+
+/// The result of a trait or type-relation query.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum Solution<T = ()> {
+    /// The query holds.
+    Yes(T),
+    /// The query doesn't hold.
+    No,
+    /// The query may hold, but it depends on something unknown, such as the type that a generic
+    /// parameter is instantiated with.
+    Ambiguous,
+}
+
+/// How a trait is implemented for a type.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum ImplSource {
+    /// An `impl` item.
+    UserDefined(ImplDef),
+    /// A where-clause of the scope.
+    Param,
+    /// An implementation provided by the compiler, such as `Sized`, `Copy` for primitive types,
+    /// auto traits, or the traits of closures and trait objects.
+    Builtin,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
@@ -19,6 +19,19 @@
 ))]
 mod tests;
 
+mod atomic_write;
+mod copy_dir;
+mod temp;
+mod walk_dir;
+
+#[unstable(feature = "fs_atomic_write", issue = "none")]
+pub use self::atomic_write::{AtomicWriter, write_atomic};
+#[unstable(feature = "fs_copy_dir", issue = "none")]
+pub use self::copy_dir::{CopyOptions, CopyProgress, copy_dir_all};
+#[unstable(feature = "fs_temp", issue = "none")]
+pub use self::temp::{PersistError, TempDir, TempFile};
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub use self::walk_dir::{WalkDir, WalkDirEntry, WalkDirError, WalkDirIter, walk_dir};
 use crate::ffi::OsString;
 use crate::fmt;
 use crate::io::{self, BorrowedCursor, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
@@ -237,6 +250,54 @@ pub struct DirBuilder {
     recursive: bool,
 }
 
+/// An open directory that filesystem operations can be performed relative to.
+///
+/// All other functions in this module take paths that are resolved from scratch on every call,
+/// so a directory that is renamed or replaced by a symbolic link between two calls can redirect
+/// later operations elsewhere. A `Dir` instead keeps the directory open, and the paths passed to
+/// its methods are resolved relative to that directory, wherever it currently is.
+///
+/// A directory opened with [`Dir::open_beneath`] additionally refuses to resolve paths that
+/// would leave it: absolute paths, `..` components that go above it, and symbolic links are
+/// all rejected.
+///
+/// # Platform-specific behavior
+///
+/// On Unix, this is backed by a directory file descriptor and the `openat`, `mkdirat`,
+/// `unlinkat`, `renameat` and `fstatat` functions. On other platforms, the paths are joined to
+/// the path the directory was opened with, which offers no protection against the directory
+/// being moved, and [`Dir::open_beneath`] is unsupported.
+/// Note that this [may change in the future][changes].
+///
+/// [changes]: io#platform-specific-behavior
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(dirfd)]
+///
+/// use std::fs::Dir;
+/// use std::io::Write;
+///
+/// fn main() -> std::io::Result<()> {
+///     let dir = Dir::open_beneath("/srv/files")?;
+///     dir.create_dir("uploads")?;
+///     let mut file = dir.open_file_with(
+///         "uploads/report.txt",
+///         std::fs::OpenOptions::new().write(true).create_new(true),
+///     )?;
+///     file.write_all(b"hello")?;
+///
+///     // Symbolic links and `..` cannot be used to leave the directory.
+///     assert!(dir.open_file("../etc/passwd").is_err());
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "dirfd", issue = "none")]
+pub struct Dir {
+    inner: fs_imp::Dir,
+}
+
 /// Reads the entire contents of a file into a bytes vector.
 ///
 /// This is a convenience function for using [`File::open`] and [`read_to_end`]
@@ -3041,6 +3102,294 @@ fn as_inner_mut(&mut self) -> &mut fs_imp::DirBuilder {
     }
 }
 
+impl Dir {
+    /// Opens the directory at `path`.
+    ///
+    /// Paths passed to the methods of the returned `Dir` are resolved like any other path,
+    /// except relative to this directory: symbolic links are followed and absolute paths are
+    /// used as they are.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` does not exist or is not a directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let metadata = dir.symlink_metadata("bar.txt")?;
+    ///     println!("{:?}", metadata.file_type());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Dir> {
+        fs_imp::Dir::open(path.as_ref()).map(|inner| Dir { inner })
+    }
+
+    /// Opens the directory at `path`, confining all operations to it.
+    ///
+    /// Paths passed to the methods of the returned `Dir`, and of the directories opened
+    /// through [`Dir::open_dir`], must stay inside of it: absolute paths, `..` components
+    /// going above the directory and symbolic links anywhere in the path are rejected with an
+    /// error. `path` itself is resolved normally.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// This is currently only supported on Unix platforms, where each component of a path is
+    /// opened with `O_NOFOLLOW`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` does not exist or is not a directory, or
+    /// if the platform does not support confining operations to a directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open_beneath("foo")?;
+    ///     assert!(dir.open_file("/etc/passwd").is_err());
+    ///     assert!(dir.open_file("../bar.txt").is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn open_beneath<P: AsRef<Path>>(path: P) -> io::Result<Dir> {
+        fs_imp::Dir::open_beneath(path.as_ref()).map(|inner| Dir { inner })
+    }
+
+    /// Opens the subdirectory at `path` relative to this directory.
+    ///
+    /// A directory opened from a `Dir` created with [`Dir::open_beneath`] is confined in the
+    /// same way.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let sub = dir.open_dir("bar")?;
+    ///     sub.remove_file("baz.txt")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn open_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<Dir> {
+        self.inner.open_dir(path.as_ref()).map(|inner| Dir { inner })
+    }
+
+    /// Opens the file at `path` relative to this directory in read-only mode.
+    ///
+    /// See [`File::open`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    /// use std::io::Read;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let mut contents = String::new();
+    ///     dir.open_file("bar.txt")?.read_to_string(&mut contents)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn open_file<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
+        self.open_file_with(path, OpenOptions::new().read(true))
+    }
+
+    /// Opens the file at `path` relative to this directory with the given options.
+    ///
+    /// See [`OpenOptions::open`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::{Dir, OpenOptions};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let file = dir.open_file_with("bar.txt", OpenOptions::new().append(true))?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn open_file_with<P: AsRef<Path>>(&self, path: P, opts: &OpenOptions) -> io::Result<File> {
+        self.inner.open_file(path.as_ref(), &opts.0).map(|inner| File { inner })
+    }
+
+    /// Creates a new, empty directory at `path` relative to this directory.
+    ///
+    /// See [`create_dir`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     dir.create_dir("bar")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn create_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.create_dir(path.as_ref())
+    }
+
+    /// Removes the file at `path` relative to this directory.
+    ///
+    /// See [`remove_file`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     dir.remove_file("bar.txt")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn remove_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.remove_file(path.as_ref())
+    }
+
+    /// Removes the empty directory at `path` relative to this directory.
+    ///
+    /// See [`remove_dir`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     dir.remove_dir("bar")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn remove_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.remove_dir(path.as_ref())
+    }
+
+    /// Renames `from`, relative to this directory, to `to`, relative to `to_dir`.
+    ///
+    /// `to_dir` may be this directory. See [`rename`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let incoming = Dir::open("incoming")?;
+    ///     let done = Dir::open("done")?;
+    ///     incoming.rename("a.txt", &done, "b.txt")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        from: P,
+        to_dir: &Dir,
+        to: Q,
+    ) -> io::Result<()> {
+        self.inner.rename(from.as_ref(), &to_dir.inner, to.as_ref())
+    }
+
+    /// Queries the metadata of the file at `path` relative to this directory, without
+    /// following a symbolic link at the end of the path.
+    ///
+    /// See [`symlink_metadata`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     let metadata = dir.symlink_metadata("bar")?;
+    ///     println!("{}", metadata.is_symlink());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn symlink_metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<Metadata> {
+        self.inner.symlink_metadata(path.as_ref()).map(Metadata)
+    }
+
+    /// Returns an iterator over the entries within this directory.
+    ///
+    /// The paths of the entries are joined to the path this directory was opened with. To
+    /// list a subdirectory, open it with [`Dir::open_dir`] first. See [`read_dir`] for more
+    /// details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(dirfd)]
+    ///
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("foo")?;
+    ///     for entry in dir.read_dir()? {
+    ///         println!("{:?}", entry?.file_name());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "dirfd", issue = "none")]
+    pub fn read_dir(&self) -> io::Result<ReadDir> {
+        self.inner.read_dir().map(ReadDir)
+    }
+}
+
+#[unstable(feature = "dirfd", issue = "none")]
+impl fmt::Debug for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
 /// Returns `Ok(true)` if the path points at an existing entity.
 ///
 /// This function will traverse symbolic links to query information about the
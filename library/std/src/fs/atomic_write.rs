@@ -0,0 +1,200 @@
+//! Atomic file replacement, see [`AtomicWriter`].
+
+use super::{Permissions, TempFile};
+use crate::io::{self, IoSlice, Write};
+use crate::path::{self, Path, PathBuf};
+use crate::{fmt, fs};
+
+/// A writer which replaces the contents of a file atomically.
+///
+/// The data is written to a temporary file in the same directory as the destination, which
+/// only replaces the destination once [`finish`] is called. Until then, other processes keep
+/// seeing the previous file, and if the writer is dropped without being finished, or the
+/// process crashes, the destination is left untouched and the temporary file is removed.
+///
+/// [`finish`] takes care of the steps which are needed for the new file to survive a system
+/// crash:
+///
+/// 1. The data of the temporary file is flushed to disk with [`sync_all`].
+/// 2. The temporary file is renamed over the destination, like by [`fs::rename`].
+/// 3. On Unix, the directory containing the destination is synced as well, so that the rename
+///    itself is durable.
+///
+/// If the destination already exists, its permissions are copied to the new file. Otherwise,
+/// the new file is only accessible to the current user, like a [`TempFile`], and its
+/// permissions can be changed with [`set_permissions`]. A symbolic link at the destination is
+/// replaced by the new file rather than followed, so its target is left untouched and the new
+/// file doesn't get the permissions of the target.
+///
+/// Writes are not buffered, so wrap the writer in a [`BufWriter`] when making many small
+/// writes, and use [`BufWriter::into_inner`] before calling [`finish`].
+///
+/// [`finish`]: AtomicWriter::finish
+/// [`set_permissions`]: AtomicWriter::set_permissions
+/// [`sync_all`]: fs::File::sync_all
+/// [`BufWriter`]: io::BufWriter
+/// [`BufWriter::into_inner`]: io::BufWriter::into_inner
+///
+/// # Platform-specific behavior
+///
+/// On Windows, the rename is performed with `MoveFileExW`, which fails if the destination is
+/// read-only.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_atomic_write)]
+/// use std::fs::AtomicWriter;
+/// use std::io::Write;
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut writer = AtomicWriter::new("config.toml")?;
+///     writeln!(writer, "[server]")?;
+///     writeln!(writer, "port = 8080")?;
+///     // `config.toml` is only replaced here, once all of the data was written.
+///     writer.finish()?;
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_atomic_write", issue = "none")]
+pub struct AtomicWriter {
+    file: TempFile,
+    path: PathBuf,
+    permissions: Option<Permissions>,
+}
+
+impl AtomicWriter {
+    /// Creates a writer which replaces the file at `path` once it is finished.
+    ///
+    /// `path` itself is not modified until [`AtomicWriter::finish`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the parent directory of `path` does not exist, or a temporary file
+    /// cannot be created in it. An error is also returned if `path` exists, but its metadata
+    /// cannot be read.
+    #[unstable(feature = "fs_atomic_write", issue = "none")]
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<AtomicWriter> {
+        // Make the path absolute, so that changing the current directory does not affect
+        // where the file ends up.
+        let path = path::absolute(path)?;
+        let Some(dir) = path.parent() else {
+            return Err(io::const_error!(
+                io::ErrorKind::InvalidInput,
+                "cannot replace a path without a parent directory",
+            ));
+        };
+        let permissions = match fs::symlink_metadata(&path) {
+            // A symbolic link is replaced, and its own permissions are meaningless.
+            Ok(metadata) if metadata.is_symlink() => None,
+            Ok(metadata) => Some(metadata.permissions()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let file = TempFile::new_in(dir)?;
+        Ok(AtomicWriter { file, path, permissions })
+    }
+
+    /// Returns the path of the file which is replaced when the writer is finished.
+    #[unstable(feature = "fs_atomic_write", issue = "none")]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sets the permissions which the file gets when the writer is finished.
+    ///
+    /// This overrides the permissions which were copied from an existing file.
+    #[unstable(feature = "fs_atomic_write", issue = "none")]
+    pub fn set_permissions(&mut self, perm: Permissions) {
+        self.permissions = Some(perm);
+    }
+
+    /// Replaces the destination with the data written so far.
+    ///
+    /// See the [type-level documentation](AtomicWriter) for the steps this involves.
+    ///
+    /// # Errors
+    ///
+    /// If any step before the rename fails, the destination is left untouched and the
+    /// temporary file is removed. If only syncing the directory fails, the destination has
+    /// already been replaced, but the replacement might not survive a system crash.
+    #[unstable(feature = "fs_atomic_write", issue = "none")]
+    pub fn finish(mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if let Some(permissions) = self.permissions {
+            self.file.as_file().set_permissions(permissions)?;
+        }
+        self.file.as_file().sync_all()?;
+        self.file.persist(&self.path)?;
+        sync_parent_dir(&self.path)
+    }
+}
+
+#[unstable(feature = "fs_atomic_write", issue = "none")]
+impl Write for AtomicWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.file.write_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.file.is_write_vectored()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[unstable(feature = "fs_atomic_write", issue = "none")]
+impl fmt::Debug for AtomicWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicWriter")
+            .field("path", &self.path)
+            .field("file", &self.file)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Atomically replaces the contents of a file with `contents`.
+///
+/// This is a convenience function for using [`AtomicWriter`], with the same guarantees: if the
+/// function fails before the file is replaced, the previous contents are left untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_atomic_write)]
+/// use std::fs;
+///
+/// fn main() -> std::io::Result<()> {
+///     fs::write_atomic("state.json", br#"{"version": 2}"#)?;
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_atomic_write", issue = "none")]
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    fn inner(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut writer = AtomicWriter::new(path)?;
+        writer.write_all(contents)?;
+        writer.finish()
+    }
+    inner(path.as_ref(), contents.as_ref())
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    // `path` is absolute and was just renamed into its parent, so the parent exists.
+    fs::File::open(path.parent().unwrap())?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    // Other platforms provide no way to sync a directory.
+    Ok(())
+}
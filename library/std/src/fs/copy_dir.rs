@@ -0,0 +1,397 @@
+//! Recursive directory copying, see [`copy_dir_all`].
+
+use super::{FileTimes, Metadata, OpenOptions, walk_dir};
+use crate::path::Path;
+use crate::sys::fs as fs_imp;
+use crate::{fmt, fs, io};
+
+/// Recursively copies the contents of the directory `from` into the directory `to`.
+///
+/// This is a shorthand for [`CopyOptions::new`] followed by [`CopyOptions::copy_dir_all`],
+/// which means that:
+///
+/// * `to` is created if it does not exist yet, along with any missing parents. Files that
+///   already exist in `to` are overwritten, and other existing entries are left alone.
+/// * Regular files are copied like by [`copy`], including their permission bits, and cloned
+///   instead on filesystems that support sharing data blocks between files.
+/// * Symbolic links are recreated rather than followed.
+/// * The permissions, access and modification times of directories, and the times of files,
+///   are preserved.
+///
+/// On success, the total number of bytes copied from regular files is returned.
+///
+/// # Errors
+///
+/// The copy stops at the first error, leaving everything that was copied so far in place.
+/// Errors are returned in the following situations, among others:
+///
+/// * `from` is not a directory.
+/// * `to` is `from` itself or is located inside of it.
+/// * The tree contains an entry that is not a regular file, a directory or a symbolic link,
+///   such as a named pipe or a device node.
+/// * Any of the underlying operations fails, see [`read_dir`], [`create_dir`] and [`copy`].
+///
+/// [`copy`]: fs::copy
+/// [`read_dir`]: fs::read_dir
+/// [`create_dir`]: fs::create_dir
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_copy_dir)]
+/// use std::fs;
+///
+/// fn main() -> std::io::Result<()> {
+///     let bytes = fs::copy_dir_all("assets", "target/assets")?;
+///     println!("copied {bytes} bytes");
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_copy_dir", issue = "none")]
+pub fn copy_dir_all<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<u64> {
+    CopyOptions::new().copy_dir_all(from, to)
+}
+
+type ProgressFn = dyn FnMut(&CopyProgress<'_>) + Send + Sync;
+
+/// Options and flags which can be used to configure how a directory tree is copied.
+///
+/// Generally speaking, when using `CopyOptions`, you'll first call [`CopyOptions::new`], then
+/// chain calls to methods to set each option, then call [`CopyOptions::copy_dir_all`]. See
+/// [`copy_dir_all`] for the behavior of the default options.
+///
+/// # Examples
+///
+/// Copying a tree including the targets of its symbolic links, and reporting progress:
+///
+/// ```no_run
+/// #![feature(fs_copy_dir)]
+/// use std::fs::CopyOptions;
+///
+/// fn main() -> std::io::Result<()> {
+///     CopyOptions::new()
+///         .follow_links(true)
+///         .progress(|progress| {
+///             println!("{} ({} bytes)", progress.path().display(), progress.bytes_copied());
+///         })
+///         .copy_dir_all("assets", "target/assets")?;
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_copy_dir", issue = "none")]
+pub struct CopyOptions {
+    follow_links: bool,
+    preserve_times: bool,
+    reflink: bool,
+    progress: Option<Box<ProgressFn>>,
+}
+
+impl CopyOptions {
+    /// Creates a blank new set of options, with the defaults described in [`copy_dir_all`].
+    #[must_use]
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn new() -> CopyOptions {
+        CopyOptions { follow_links: false, preserve_times: true, reflink: true, progress: None }
+    }
+
+    /// Sets whether symbolic links are followed.
+    ///
+    /// By default symbolic links are recreated in the destination with the same target. When
+    /// they are followed, the files and directories they point to are copied in their place
+    /// instead, and a link to one of its own ancestors is reported as an error with
+    /// [`io::ErrorKind::FilesystemLoop`].
+    ///
+    /// The source directory itself is always followed if it is a symbolic link.
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn follow_links(&mut self, follow: bool) -> &mut CopyOptions {
+        self.follow_links = follow;
+        self
+    }
+
+    /// Sets whether the access and modification times of files and directories are copied.
+    ///
+    /// This is enabled by default. The times of recreated symbolic links are never preserved.
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn preserve_times(&mut self, preserve: bool) -> &mut CopyOptions {
+        self.preserve_times = preserve;
+        self
+    }
+
+    /// Sets whether regular files may be cloned instead of copied.
+    ///
+    /// A clone (or reflink) shares the data blocks of the source until either file is modified,
+    /// which makes it nearly instantaneous and avoids using additional space. If the filesystem
+    /// does not support cloning, or the source and destination are on different filesystems,
+    /// the data is copied as usual.
+    ///
+    /// This is enabled by default. Disabling it guarantees a full copy on Linux, while other
+    /// platforms only clone files if [`fs::copy`] already does so.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// This currently uses the `FICLONE` ioctl on Linux and Android. Note that this
+    /// [may change in the future][changes].
+    ///
+    /// [changes]: io#platform-specific-behavior
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn reflink(&mut self, reflink: bool) -> &mut CopyOptions {
+        self.reflink = reflink;
+        self
+    }
+
+    /// Sets a callback that is called after each entry of the tree has been copied.
+    ///
+    /// The callback receives a [`CopyProgress`] describing the entry that was just copied and
+    /// the totals so far. It is not called for the source directory itself.
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn progress<F>(&mut self, progress: F) -> &mut CopyOptions
+    where
+        F: FnMut(&CopyProgress<'_>) + Send + Sync + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Recursively copies the contents of the directory `from` into the directory `to` with
+    /// the options specified by `self`.
+    ///
+    /// On success, the total number of bytes copied from regular files is returned.
+    ///
+    /// # Errors
+    ///
+    /// See [`copy_dir_all`] for the situations in which an error is returned.
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn copy_dir_all<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        from: P,
+        to: Q,
+    ) -> io::Result<u64> {
+        self._copy_dir_all(from.as_ref(), to.as_ref())
+    }
+
+    fn _copy_dir_all(&mut self, from: &Path, to: &Path) -> io::Result<u64> {
+        check_not_nested(from, to)?;
+
+        let mut bytes_copied = 0;
+        let mut entries_copied = 0;
+        // Directories get their permissions and times once their contents are complete, as
+        // copying into them would otherwise update the times or fail for read-only directories.
+        let mut dirs = Vec::new();
+
+        for entry in walk_dir(from).follow_links(self.follow_links) {
+            let entry = entry?;
+            let file_type = entry.file_type();
+            if entry.depth() == 0 {
+                if !file_type.is_dir() {
+                    return Err(io::const_error!(
+                        io::ErrorKind::NotADirectory,
+                        "the source path is not a directory",
+                    ));
+                }
+                fs::create_dir_all(to)?;
+                dirs.push((to.to_path_buf(), entry.metadata()?));
+                continue;
+            }
+            // The paths of walked entries always start with the path the walk started from.
+            let dest = to.join(entry.path().strip_prefix(from).unwrap());
+
+            if file_type.is_dir() {
+                match fs::create_dir(&dest) {
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists && dest.is_dir() => {}
+                    result => result?,
+                }
+                dirs.push((dest, entry.metadata()?));
+            } else if file_type.is_file() {
+                let metadata = entry.metadata()?;
+                bytes_copied += if self.reflink {
+                    fs_imp::copy_reflink(entry.path(), &dest)?
+                } else {
+                    fs_imp::copy(entry.path(), &dest)?
+                };
+                if self.preserve_times {
+                    set_times(&dest, &metadata)?;
+                }
+            } else if file_type.is_symlink() {
+                copy_symlink(entry.path(), &dest)?;
+            } else {
+                return Err(io::const_error!(
+                    io::ErrorKind::InvalidInput,
+                    "the source tree contains an entry that is neither a regular file, \
+                     a directory nor a symbolic link",
+                ));
+            }
+
+            entries_copied += 1;
+            if let Some(progress) = &mut self.progress {
+                progress(&CopyProgress { path: entry.path(), bytes_copied, entries_copied });
+            }
+        }
+
+        for (dest, metadata) in dirs.iter().rev() {
+            if self.preserve_times {
+                set_times(dest, metadata)?;
+            }
+            fs::set_permissions(dest, metadata.permissions())?;
+        }
+        Ok(bytes_copied)
+    }
+}
+
+#[unstable(feature = "fs_copy_dir", issue = "none")]
+impl Default for CopyOptions {
+    fn default() -> CopyOptions {
+        CopyOptions::new()
+    }
+}
+
+#[unstable(feature = "fs_copy_dir", issue = "none")]
+impl fmt::Debug for CopyOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyOptions")
+            .field("follow_links", &self.follow_links)
+            .field("preserve_times", &self.preserve_times)
+            .field("reflink", &self.reflink)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// The state of a copy in progress, passed to the callback set with [`CopyOptions::progress`].
+#[derive(Debug)]
+#[unstable(feature = "fs_copy_dir", issue = "none")]
+pub struct CopyProgress<'a> {
+    path: &'a Path,
+    bytes_copied: u64,
+    entries_copied: u64,
+}
+
+impl CopyProgress<'_> {
+    /// Returns the source path of the entry that was just copied.
+    #[must_use]
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// Returns the total number of bytes copied from regular files so far.
+    #[must_use]
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn bytes_copied(&self) -> u64 {
+        self.bytes_copied
+    }
+
+    /// Returns the number of files, directories and symbolic links copied so far.
+    #[must_use]
+    #[unstable(feature = "fs_copy_dir", issue = "none")]
+    pub fn entries_copied(&self) -> u64 {
+        self.entries_copied
+    }
+}
+
+/// Fails if `to` is `from` or inside of it, which would make the copy recurse into itself.
+fn check_not_nested(from: &Path, to: &Path) -> io::Result<()> {
+    let from = fs::canonicalize(from)?;
+    // `to` may not exist yet, in which case its closest existing ancestor decides.
+    for ancestor in to.ancestors() {
+        let ancestor = if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor };
+        match fs::canonicalize(ancestor) {
+            Ok(ancestor) if ancestor.starts_with(&from) => {
+                return Err(io::const_error!(
+                    io::ErrorKind::InvalidInput,
+                    "cannot copy a directory into itself",
+                ));
+            }
+            Ok(_) => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn set_times(path: &Path, metadata: &Metadata) -> io::Result<()> {
+    let mut times = FileTimes::new();
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    if let Ok(modified) = metadata.modified() {
+        times = times.set_modified(modified);
+    }
+    open_for_times(path)?.set_times(times)
+}
+
+#[cfg(windows)]
+fn open_for_times(path: &Path) -> io::Result<fs::File> {
+    use crate::os::windows::fs::OpenOptionsExt;
+    use crate::sys::c;
+
+    // Only asks for the access that is needed, so read-only files can be opened as well.
+    // `FILE_FLAG_BACKUP_SEMANTICS` allows opening directories.
+    OpenOptions::new()
+        .access_mode(c::FILE_WRITE_ATTRIBUTES)
+        .custom_flags(c::FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+}
+
+#[cfg(not(windows))]
+fn open_for_times(path: &Path) -> io::Result<fs::File> {
+    // Changing the times only requires owning the file, so this works for read-only files and
+    // for directories.
+    OpenOptions::new().read(true).open(path)
+}
+
+/// Recreates the symbolic link `from` at `to`, replacing an existing file or link.
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let target = fs::read_link(from)?;
+    match symlink(from, &target, to) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            remove_link(to)?;
+            symlink(from, &target, to)
+        }
+        result => result,
+    }
+}
+
+/// Removes a file or symbolic link, but not a directory.
+fn remove_link(path: &Path) -> io::Result<()> {
+    // Links to directories are removed like directories on Windows.
+    #[cfg(windows)]
+    {
+        use crate::os::windows::fs::FileTypeExt;
+
+        if fs::symlink_metadata(path)?.file_type().is_symlink_dir() {
+            return fs::remove_dir(path);
+        }
+    }
+    fs::remove_file(path)
+}
+
+#[cfg(unix)]
+fn symlink(_from: &Path, target: &Path, to: &Path) -> io::Result<()> {
+    crate::os::unix::fs::symlink(target, to)
+}
+
+#[cfg(windows)]
+fn symlink(from: &Path, target: &Path, to: &Path) -> io::Result<()> {
+    use crate::os::windows::fs::{FileTypeExt, symlink_dir, symlink_file};
+
+    // Windows distinguishes between links to files and to directories.
+    if fs::symlink_metadata(from)?.file_type().is_symlink_dir() {
+        symlink_dir(target, to)
+    } else {
+        symlink_file(target, to)
+    }
+}
+
+#[cfg(target_os = "wasi")]
+fn symlink(_from: &Path, target: &Path, to: &Path) -> io::Result<()> {
+    crate::os::wasi::fs::symlink_path(target, to)
+}
+
+#[cfg(not(any(unix, windows, target_os = "wasi")))]
+fn symlink(_from: &Path, _target: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::const_error!(
+        io::ErrorKind::Unsupported,
+        "symbolic links are not supported on this platform",
+    ))
+}
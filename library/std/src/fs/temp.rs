@@ -0,0 +1,489 @@
+//! Temporary files and directories, see [`TempFile`] and [`TempDir`].
+
+use super::{DirBuilder, File, OpenOptions};
+use crate::io::{self, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+use crate::path::{self, Path, PathBuf};
+use crate::sys::random::hashmap_random_keys;
+use crate::{env, error, fmt, fs, mem};
+
+/// A temporary file, which is removed when it is dropped.
+///
+/// Temporary files are created securely: they are only accessible to the current user, and
+/// a new file is always created, so that an existing file or a symbolic link planted by
+/// another user cannot be opened instead.
+///
+/// * On Linux, the file is created without a name using `O_TMPFILE` where the kernel and the
+///   filesystem support it. Such a file can never be seen or opened by other processes, and
+///   the system reclaims it even if the process exits without running destructors.
+/// * Otherwise, the file is created exclusively, like with [`OpenOptions::create_new`], under
+///   a random name starting with `.tmp`, and removed when the `TempFile` is dropped.
+///
+/// The file can be moved to its final location with [`persist`], which makes it possible to
+/// write a file completely before any other process can observe it.
+///
+/// [`persist`]: TempFile::persist
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_temp)]
+/// use std::fs::TempFile;
+/// use std::io::Write;
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut file = TempFile::new_in("config")?;
+///     file.write_all(b"verbose = true\n")?;
+///     // Other processes either see the old `settings.toml`, or the complete new one.
+///     file.persist("config/settings.toml")?;
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_temp", issue = "none")]
+pub struct TempFile {
+    // Declared first so that the file is closed before it is removed, which Windows requires.
+    file: File,
+    path: Option<TempPath>,
+}
+
+/// A temporary directory, which is removed along with its contents when it is dropped.
+///
+/// The directory is created under a random name starting with `.tmp`, and is only accessible
+/// to the current user.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_temp)]
+/// use std::fs::{self, TempDir};
+///
+/// fn main() -> std::io::Result<()> {
+///     let dir = TempDir::new()?;
+///     fs::write(dir.path().join("input.txt"), "hello")?;
+///     // ...
+///     dir.close()
+/// }
+/// ```
+#[unstable(feature = "fs_temp", issue = "none")]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+/// An error returned from [`TempFile::persist`], which contains the temporary file that could
+/// not be persisted.
+///
+/// The file is still removed when it is dropped, unless it is retrieved with
+/// [`into_file`](PersistError::into_file) and persisted again.
+#[unstable(feature = "fs_temp", issue = "none")]
+pub struct PersistError {
+    error: io::Error,
+    file: TempFile,
+}
+
+/// A path which is removed when it is dropped, unless it has been taken.
+struct TempPath {
+    path: PathBuf,
+}
+
+impl TempPath {
+    fn take(&mut self) -> PathBuf {
+        mem::take(&mut self.path)
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl TempFile {
+    /// Creates a new temporary file in the directory returned by [`env::temp_dir`].
+    ///
+    /// The file is opened for both reading and writing.
+    ///
+    /// [`persist`](TempFile::persist) can only move the file within the same filesystem, so
+    /// prefer [`TempFile::new_in`] for files that are meant to be persisted.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(fs_temp)]
+    /// use std::fs::TempFile;
+    /// use std::io::{Read, Seek, Write};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = TempFile::new()?;
+    ///     file.write_all(b"scratch data")?;
+    ///     file.rewind()?;
+    ///     let mut data = String::new();
+    ///     file.read_to_string(&mut data)?;
+    ///     assert_eq!(data, "scratch data");
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn new() -> io::Result<TempFile> {
+        TempFile::new_in(env::temp_dir())
+    }
+
+    /// Creates a new temporary file in `dir`.
+    ///
+    /// The file is opened for both reading and writing. On Linux, it has no name until it is
+    /// persisted if possible, see [`TempFile::path`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `dir` does not exist, is not a directory, or a file cannot be
+    /// created in it.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<TempFile> {
+        let dir = path::absolute(dir)?;
+        match open_unnamed(&dir)? {
+            Some(file) => Ok(TempFile { file, path: None }),
+            None => TempFile::new_named_in(dir),
+        }
+    }
+
+    /// Creates a new temporary file in `dir` which always has a name.
+    ///
+    /// Unlike [`TempFile::new_in`], this never creates an unnamed file, so [`TempFile::path`]
+    /// is guaranteed to return the path of the file, for example to pass it to another process.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn new_named_in<P: AsRef<Path>>(dir: P) -> io::Result<TempFile> {
+        let dir = path::absolute(dir)?;
+        let mut opts = OpenOptions::new();
+        opts.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        crate::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+        let (path, file) = with_random_path(&dir, |path| opts.open(path))?;
+        Ok(TempFile { file, path: Some(TempPath { path }) })
+    }
+
+    /// Returns the path of the temporary file, or `None` if it does not have a name.
+    ///
+    /// Files created by [`TempFile::new`] and [`TempFile::new_in`] on Linux usually have no
+    /// name, see the [type-level documentation](TempFile).
+    #[unstable(feature = "fs_temp", issue = "none")]
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref().map(|p| p.path.as_path())
+    }
+
+    /// Returns a reference to the underlying file.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    #[must_use]
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    /// Returns a mutable reference to the underlying file.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    #[must_use]
+    pub fn as_file_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+
+    /// Moves the temporary file to `path`, replacing any file that already exists there, and
+    /// returns the underlying file.
+    ///
+    /// The file appears at `path` atomically: other processes either see the previous file at
+    /// `path`, or the whole temporary file. A named file is renamed like by [`fs::rename`],
+    /// while an unnamed file is first linked into the directory of `path` under a random name,
+    /// and then renamed. If the system doesn't allow linking it, e.g. because `/proc` isn't
+    /// mounted, it is copied to a named temporary file instead, and the returned file is the
+    /// copy.
+    ///
+    /// The file keeps the restrictive permissions it was created with, which can be changed
+    /// with [`File::set_permissions`] before or after persisting it. To make sure the data
+    /// survives a system crash, call [`File::sync_all`] before persisting the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, which contains the temporary file, if it could not be moved. This
+    /// includes the case where `path` is located on a different filesystem than the file,
+    /// so temporary files that are meant to be persisted should be created with
+    /// [`TempFile::new_in`] in the directory they are persisted to.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn persist<P: AsRef<Path>>(mut self, path: P) -> Result<File, PersistError> {
+        let result = match &mut self.path {
+            Some(temp) => fs::rename(&temp.path, path.as_ref()).map(|()| {
+                temp.take();
+            }),
+            None => persist_unnamed(&self.file, path.as_ref()).map(|copy| {
+                if let Some(copy) = copy {
+                    self.file = copy;
+                }
+            }),
+        };
+        match result {
+            Ok(()) => Ok(self.file),
+            Err(error) => Err(PersistError { error, file: self }),
+        }
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl fmt::Debug for TempFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TempFile").field("file", &self.file).field("path", &self.path()).finish()
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.file.read_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        self.file.is_read_vectored()
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.file.write_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.file.is_write_vectored()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl Seek for TempFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+impl PersistError {
+    /// Returns a reference to the error that caused the file not to be persisted.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the error that caused the file not to be persisted.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+
+    /// Returns the temporary file which could not be persisted.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn into_file(self) -> TempFile {
+        self.file
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl From<PersistError> for io::Error {
+    fn from(error: PersistError) -> io::Error {
+        error.error
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl fmt::Debug for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistError").field("error", &self.error).finish_non_exhaustive()
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to persist temporary file: {}", self.error)
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl error::Error for PersistError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl TempDir {
+    /// Creates a new temporary directory in the directory returned by [`env::temp_dir`].
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn new() -> io::Result<TempDir> {
+        TempDir::new_in(env::temp_dir())
+    }
+
+    /// Creates a new temporary directory in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `dir` does not exist, is not a directory, or a directory cannot be
+    /// created in it.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<TempDir> {
+        let dir = path::absolute(dir)?;
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        crate::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        let (path, ()) = with_random_path(&dir, |path| builder.create(path))?;
+        Ok(TempDir { path })
+    }
+
+    /// Returns the path of the temporary directory.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Keeps the directory and its contents instead of removing them, and returns its path.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    #[must_use = "the directory is kept, but its path is only returned here"]
+    pub fn keep(mut self) -> PathBuf {
+        mem::take(&mut self.path)
+    }
+
+    /// Removes the directory and its contents, like dropping the `TempDir`, but reports
+    /// errors instead of ignoring them.
+    #[unstable(feature = "fs_temp", issue = "none")]
+    pub fn close(mut self) -> io::Result<()> {
+        fs::remove_dir_all(mem::take(&mut self.path))
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+#[unstable(feature = "fs_temp", issue = "none")]
+impl fmt::Debug for TempDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TempDir").field("path", &self.path).finish()
+    }
+}
+
+/// Calls `create` with random paths in `dir` until it does not fail because the path
+/// already exists.
+fn with_random_path<T>(
+    dir: &Path,
+    mut create: impl FnMut(&Path) -> io::Result<T>,
+) -> io::Result<(PathBuf, T)> {
+    // With 36^12 possible names, running out of attempts means that something is wrong.
+    const ATTEMPTS: usize = 64;
+    const NAME_LEN: usize = 12;
+    // Lowercase only, so that names are also distinct on case-insensitive filesystems.
+    const CHARS: &[u8; 36] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+    for _ in 0..ATTEMPTS {
+        let (k1, k2) = hashmap_random_keys();
+        let mut bits = (u128::from(k1) << 64) | u128::from(k2);
+        let mut name = String::with_capacity(".tmp".len() + NAME_LEN);
+        name.push_str(".tmp");
+        for _ in 0..NAME_LEN {
+            name.push(char::from(CHARS[(bits % 36) as usize]));
+            bits /= 36;
+        }
+        let path = dir.join(name);
+        match create(&path) {
+            Ok(value) => return Ok((path, value)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::const_error!(
+        io::ErrorKind::AlreadyExists,
+        "too many temporary files exist, failed to find an unused name",
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn open_unnamed(dir: &Path) -> io::Result<Option<File>> {
+    use crate::sys_common::FromInner;
+
+    Ok(crate::sys::fs::open_tmpfile(dir)?.map(File::from_inner))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_unnamed(_dir: &Path) -> io::Result<Option<File>> {
+    Ok(None)
+}
+
+/// Moves an unnamed file to `path`.
+///
+/// Returns the file that was moved if it is not `file` itself, because `file` could not be
+/// linked and had to be copied to a named file instead.
+#[cfg(target_os = "linux")]
+fn persist_unnamed(file: &File, path: &Path) -> io::Result<Option<File>> {
+    use crate::sys_common::AsInner;
+
+    // Linking fails if `path` exists, so link the file next to it first and then rename it,
+    // which atomically replaces `path`.
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let temp = match with_random_path(dir, |temp| {
+        crate::sys::fs::link_tmpfile(file.as_inner(), temp)
+    }) {
+        Ok((temp, ())) => temp,
+        // Without `/proc`, older kernels can't link the file, so it is persisted as a copy.
+        Err(e) if matches!(e.kind(), io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied) => {
+            let copy = copy_to_named(file, dir)?;
+            return copy.persist(path).map(Some).map_err(|e| e.error);
+        }
+        Err(e) => return Err(e),
+    };
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })?;
+    Ok(None)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn persist_unnamed(_file: &File, _path: &Path) -> io::Result<Option<File>> {
+    unreachable!("unnamed temporary files are only created on Linux")
+}
+
+/// Copies the contents and permissions of `file` to a new named temporary file in `dir`.
+///
+/// The position of the copy is the one of `file`, which is left unchanged.
+#[cfg(target_os = "linux")]
+fn copy_to_named(mut file: &File, dir: &Path) -> io::Result<TempFile> {
+    let mut copy = TempFile::new_named_in(dir)?;
+    let pos = file.stream_position()?;
+    file.rewind()?;
+    let copied = io::copy(&mut file, &mut copy.file);
+    file.seek(SeekFrom::Start(pos))?;
+    copied?;
+    copy.file.seek(SeekFrom::Start(pos))?;
+    copy.file.set_permissions(file.metadata()?.permissions())?;
+    Ok(copy)
+}
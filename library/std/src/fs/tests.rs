@@ -1,6 +1,6 @@
 use rand::RngCore;
 
-use crate::fs::{self, File, FileTimes, OpenOptions};
+use crate::fs::{self, Dir, File, FileTimes, OpenOptions};
 use crate::io::prelude::*;
 use crate::io::{BorrowedBuf, ErrorKind, SeekFrom};
 use crate::mem::MaybeUninit;
@@ -1912,3 +1912,497 @@ fn test_hidden_file_truncation() {
     let metadata = file.metadata().unwrap();
     assert_eq!(metadata.len(), 0);
 }
+
+#[test]
+fn dir_relative_operations() {
+    let tmpdir = tmpdir();
+    let dir = check!(Dir::open(tmpdir.path()));
+
+    check!(dir.create_dir("sub"));
+    let opts = OpenOptions::new().write(true).create(true).clone();
+    let mut file = check!(dir.open_file_with("sub/a.txt", &opts));
+    check!(file.write_all(b"hello"));
+    drop(file);
+    assert!(check!(dir.symlink_metadata("sub")).is_dir());
+    assert_eq!(check!(dir.symlink_metadata("sub/a.txt")).len(), 5);
+
+    let sub = check!(dir.open_dir("sub"));
+    let mut contents = String::new();
+    check!(check!(sub.open_file("a.txt")).read_to_string(&mut contents));
+    assert_eq!(contents, "hello");
+    assert_eq!(dir.open_file("missing").unwrap_err().kind(), ErrorKind::NotFound);
+
+    // Operations keep working on a directory after it has been moved.
+    let sub_name = if cfg!(unix) {
+        check!(fs::rename(tmpdir.join("sub"), tmpdir.join("moved")));
+        "moved"
+    } else {
+        "sub"
+    };
+    check!(sub.rename("a.txt", &dir, "b.txt"));
+    assert!(tmpdir.join("b.txt").is_file());
+    check!(dir.rename("b.txt", &sub, "c.txt"));
+    assert!(tmpdir.join(sub_name).join("c.txt").is_file());
+
+    let entries: Vec<_> = check!(sub.read_dir()).map(|entry| check!(entry).file_name()).collect();
+    assert_eq!(entries, ["c.txt"]);
+
+    check!(sub.remove_file("c.txt"));
+    check!(dir.remove_dir(sub_name));
+    assert_eq!(check!(dir.read_dir()).count(), 0);
+}
+
+#[test]
+fn dir_open_not_a_directory() {
+    let tmpdir = tmpdir();
+    check!(File::create(tmpdir.join("file")));
+    assert!(Dir::open(tmpdir.join("file")).is_err());
+    assert!(Dir::open(tmpdir.join("missing")).is_err());
+}
+
+#[test]
+// The fallback `Dir` used on these targets doesn't support `Dir::open_beneath`.
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "redox",
+        target_os = "espidf",
+        target_os = "horizon",
+        target_os = "vita",
+        target_os = "nto",
+        target_os = "vxworks",
+        miri
+    ))
+))]
+fn dir_beneath_rejects_escapes() {
+    let tmpdir = tmpdir();
+    check!(fs::create_dir_all(tmpdir.join("root/sub")));
+    check!(File::create(tmpdir.join("root/sub/file")));
+    check!(File::create(tmpdir.join("outside")));
+    check!(symlink_file(tmpdir.join("outside"), tmpdir.join("root/link")));
+    check!(symlink_dir(tmpdir.path(), tmpdir.join("root/dirlink")));
+
+    let dir = check!(Dir::open_beneath(tmpdir.join("root")));
+    check!(dir.open_file("sub/file"));
+    check!(dir.open_file("sub/../sub/./file"));
+    check!(dir.open_dir("sub/.."));
+
+    for path in ["../outside", "sub/../../outside", "link", "dirlink/outside", "/etc/passwd"] {
+        assert!(dir.open_file(path).is_err(), "{path} could be opened");
+    }
+    assert!(dir.open_dir("dirlink").is_err());
+    assert!(dir.remove_file("../outside").is_err());
+    assert!(tmpdir.join("outside").exists());
+
+    // The link itself is still inside of the directory.
+    assert!(check!(dir.symlink_metadata("link")).file_type().is_symlink());
+    check!(dir.remove_file("link"));
+
+    // Subdirectories are confined as well.
+    let sub = check!(dir.open_dir("sub"));
+    assert!(sub.open_file("../sub/file").is_err());
+
+    // Without confinement, the same paths are resolved normally.
+    let dir = check!(Dir::open(tmpdir.join("root")));
+    check!(dir.open_file("../outside"));
+    check!(dir.open_file("dirlink/outside"));
+}
+
+fn walk_relative(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root).unwrap().to_str().unwrap().replace('\\', "/")
+}
+
+fn walk_names(walk: fs::WalkDir, root: &Path) -> Vec<String> {
+    walk.into_iter().map(|entry| walk_relative(check!(entry).path(), root)).collect()
+}
+
+#[test]
+fn walk_dir_order_and_depth() {
+    let tmpdir = tmpdir();
+    let root = tmpdir.join("walk");
+    check!(fs::create_dir_all(root.join("a/b")));
+    check!(fs::create_dir(root.join("c")));
+    check!(File::create(root.join("a/b/file")));
+    check!(File::create(root.join("a/file")));
+    check!(File::create(root.join("file")));
+
+    let all = walk_names(fs::walk_dir(&root).sort_by_file_name(), &root);
+    assert_eq!(all, ["", "a", "a/b", "a/b/file", "a/file", "c", "file"]);
+    let depths: Vec<_> =
+        fs::walk_dir(&root).sort_by_file_name().into_iter().map(|e| check!(e).depth()).collect();
+    assert_eq!(depths, [0, 1, 2, 3, 2, 1, 1]);
+
+    // Without sorting, every entry is still yielded exactly once.
+    let mut unsorted = walk_names(fs::walk_dir(&root), &root);
+    unsorted.sort();
+    assert_eq!(unsorted, all);
+
+    let post_order =
+        walk_names(fs::walk_dir(&root).sort_by_file_name().contents_first(true), &root);
+    assert_eq!(post_order, ["a/b/file", "a/b", "a/file", "a", "c", "file", ""]);
+
+    let limited =
+        walk_names(fs::walk_dir(&root).sort_by_file_name().min_depth(1).max_depth(1), &root);
+    assert_eq!(limited, ["a", "c", "file"]);
+    let root_only = walk_names(fs::walk_dir(&root).max_depth(0), &root);
+    assert_eq!(root_only, [""]);
+
+    let mut walk = fs::walk_dir(&root).sort_by_file_name().into_iter();
+    let mut skipped = Vec::new();
+    while let Some(entry) = walk.next() {
+        let entry = check!(entry);
+        if entry.file_name() == "a" {
+            walk.skip_current_dir();
+        }
+        skipped.push(walk_relative(entry.path(), &root));
+    }
+    assert_eq!(skipped, ["", "a", "c", "file"]);
+}
+
+#[test]
+fn walk_dir_errors() {
+    let tmpdir = tmpdir();
+
+    let missing = tmpdir.join("missing");
+    let mut walk = fs::walk_dir(&missing).into_iter();
+    let err = walk.next().unwrap().unwrap_err();
+    assert!(walk.next().is_none());
+    assert_eq!(err.path(), missing);
+    assert_eq!(err.depth(), 0);
+    assert_eq!(err.io_error().kind(), ErrorKind::NotFound);
+    assert_eq!(crate::io::Error::from(err).kind(), ErrorKind::NotFound);
+
+    // A file is yielded on its own.
+    let file = tmpdir.join("file");
+    check!(File::create(&file));
+    let entries: Vec<_> = fs::walk_dir(&file).into_iter().map(|e| check!(e)).collect();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].file_type().is_file());
+    assert!(entries[0].dir_entry().is_none());
+    assert_eq!(entries[0].file_name(), "file");
+}
+
+#[test]
+fn walk_dir_symlinks() {
+    let tmpdir = tmpdir();
+    if !got_symlink_permission(&tmpdir) {
+        return;
+    };
+    let root = tmpdir.join("walk");
+    check!(fs::create_dir_all(root.join("dir/sub")));
+    check!(File::create(root.join("dir/sub/file")));
+    check!(symlink_dir(root.join("dir/sub"), root.join("link")));
+    check!(symlink_dir(&root, root.join("dir/sub/up")));
+    check!(symlink_file(root.join("missing"), root.join("broken")));
+
+    // Links are yielded but not followed by default.
+    let names = walk_names(fs::walk_dir(&root).sort_by_file_name(), &root);
+    assert_eq!(names, ["", "broken", "dir", "dir/sub", "dir/sub/file", "dir/sub/up", "link"]);
+    let mut walk = fs::walk_dir(&root).max_depth(1).into_iter().map(|e| check!(e));
+    let link = walk.find(|e| e.file_name() == "link").unwrap();
+    assert!(link.file_type().is_symlink());
+    assert!(!link.path_is_symlink());
+
+    let mut names = Vec::new();
+    let mut loops = Vec::new();
+    let mut broken = Vec::new();
+    for entry in fs::walk_dir(&root).follow_links(true).sort_by_file_name() {
+        match entry {
+            Ok(entry) => {
+                if entry.file_name() == "link" {
+                    assert!(entry.file_type().is_dir());
+                    assert!(entry.path_is_symlink());
+                    assert!(check!(entry.metadata()).is_dir());
+                }
+                names.push(walk_relative(entry.path(), &root));
+            }
+            Err(err) => match err.loop_ancestor() {
+                Some(ancestor) => {
+                    assert_eq!(err.io_error().kind(), ErrorKind::FilesystemLoop);
+                    loops.push((walk_relative(err.path(), &root), walk_relative(ancestor, &root)));
+                }
+                None => broken.push(walk_relative(err.path(), &root)),
+            },
+        }
+    }
+    assert_eq!(names, ["", "dir", "dir/sub", "dir/sub/file", "link", "link/file"]);
+    assert_eq!(
+        loops,
+        [("dir/sub/up".to_owned(), "".to_owned()), ("link/up".to_owned(), "".to_owned())]
+    );
+    assert_eq!(broken, ["broken"]);
+}
+
+#[test]
+fn copy_dir_all_copies_tree() {
+    let tmpdir = tmpdir();
+    let src = tmpdir.join("src");
+    let dst = tmpdir.join("dst");
+    check!(fs::create_dir_all(src.join("a/b")));
+    check!(fs::create_dir(src.join("empty")));
+    check!(fs::write(src.join("a/b/file"), b"hello"));
+    check!(fs::write(src.join("file"), b"world!"));
+
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(54321);
+    let times = FileTimes::new().set_modified(modified);
+    check!(check!(File::options().write(true).open(src.join("file"))).set_times(times));
+    let mut readonly = check!(fs::metadata(src.join("a/b/file"))).permissions();
+    readonly.set_readonly(true);
+    check!(fs::set_permissions(src.join("a/b/file"), readonly));
+    // Set last, as creating the contents updates the times of the directory.
+    #[cfg(unix)]
+    check!(check!(File::open(src.join("a"))).set_times(times));
+
+    let progress = Arc::new(crate::sync::Mutex::new(Vec::new()));
+    let recorded = progress.clone();
+    let bytes = check!(
+        fs::CopyOptions::new()
+            .progress(move |p| {
+                recorded.lock().unwrap().push((p.bytes_copied(), p.entries_copied()))
+            })
+            .copy_dir_all(&src, dst.join("nested"))
+    );
+    assert_eq!(bytes, 11);
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 5);
+    assert_eq!(progress.last(), Some(&(11, 5)));
+
+    let dst = dst.join("nested");
+    assert_eq!(check!(fs::read(dst.join("a/b/file"))), b"hello");
+    assert_eq!(check!(fs::read(dst.join("file"))), b"world!");
+    assert!(dst.join("empty").is_dir());
+    assert!(check!(fs::metadata(dst.join("a/b/file"))).permissions().readonly());
+    assert_eq!(check!(check!(fs::metadata(dst.join("file"))).modified()), modified);
+    #[cfg(unix)]
+    assert_eq!(check!(check!(fs::metadata(dst.join("a"))).modified()), modified);
+
+    // Copying again overwrites files and keeps existing entries.
+    check!(fs::remove_dir_all(dst.join("a")));
+    check!(fs::write(src.join("file"), b"again"));
+    check!(fs::write(dst.join("extra"), b""));
+    let bytes = check!(
+        fs::CopyOptions::new().preserve_times(false).reflink(false).copy_dir_all(&src, &dst)
+    );
+    assert_eq!(bytes, 10);
+    assert_eq!(check!(fs::read(dst.join("file"))), b"again");
+    assert!(dst.join("extra").exists());
+}
+
+#[test]
+fn copy_dir_all_errors() {
+    let tmpdir = tmpdir();
+    let src = tmpdir.join("src");
+    check!(fs::create_dir_all(src.join("sub")));
+
+    let err = fs::copy_dir_all(&src, src.join("sub/dst")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = fs::copy_dir_all(&src, &src).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(!src.join("sub/dst").exists());
+
+    let file = tmpdir.join("file");
+    check!(File::create(&file));
+    let err = fs::copy_dir_all(&file, tmpdir.join("dst")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotADirectory);
+
+    let err = fs::copy_dir_all(tmpdir.join("missing"), tmpdir.join("dst")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert!(!tmpdir.join("dst").exists());
+}
+
+#[test]
+fn copy_dir_all_symlinks() {
+    let tmpdir = tmpdir();
+    if !got_symlink_permission(&tmpdir) {
+        return;
+    };
+    let src = tmpdir.join("src");
+    check!(fs::create_dir_all(src.join("dir")));
+    check!(fs::write(src.join("dir/file"), b"data"));
+    check!(symlink_file(Path::new("dir").join("file"), src.join("file_link")));
+    check!(symlink_dir("dir", src.join("dir_link")));
+
+    // Links are recreated with the same target by default.
+    let dst = tmpdir.join("dst");
+    assert_eq!(check!(fs::copy_dir_all(&src, &dst)), 4);
+    for link in ["file_link", "dir_link"] {
+        assert!(check!(fs::symlink_metadata(dst.join(link))).file_type().is_symlink());
+        assert_eq!(check!(fs::read_link(dst.join(link))), check!(fs::read_link(src.join(link))));
+    }
+    assert_eq!(check!(fs::read(dst.join("file_link"))), b"data");
+    // Existing links are replaced.
+    assert_eq!(check!(fs::copy_dir_all(&src, &dst)), 4);
+
+    // Following them copies their targets instead.
+    let followed = tmpdir.join("followed");
+    assert_eq!(check!(fs::CopyOptions::new().follow_links(true).copy_dir_all(&src, &followed)), 12);
+    assert!(check!(fs::symlink_metadata(followed.join("file_link"))).is_file());
+    assert!(check!(fs::symlink_metadata(followed.join("dir_link"))).is_dir());
+    assert_eq!(check!(fs::read(followed.join("dir_link/file"))), b"data");
+}
+
+#[test]
+fn temp_file_removed_on_drop() {
+    let tmpdir = tmpdir();
+    for named in [false, true] {
+        let mut file = if named {
+            check!(fs::TempFile::new_named_in(tmpdir.path()))
+        } else {
+            check!(fs::TempFile::new_in(tmpdir.path()))
+        };
+        check!(file.write_all(b"hello"));
+        check!(file.rewind());
+        let mut data = String::new();
+        check!(file.read_to_string(&mut data));
+        assert_eq!(data, "hello");
+
+        let path = file.path().map(Path::to_path_buf);
+        if named {
+            let path = path.as_ref().unwrap();
+            assert!(path.starts_with(tmpdir.path()));
+            assert_eq!(check!(fs::read(path)), b"hello");
+        }
+        drop(file);
+        if let Some(path) = path {
+            assert!(!path.exists());
+        }
+        assert_eq!(check!(fs::read_dir(tmpdir.path())).count(), 0);
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn temp_file_permissions() {
+    use crate::os::unix::fs::PermissionsExt;
+
+    let tmpdir = tmpdir();
+    let file = check!(fs::TempFile::new_named_in(tmpdir.path()));
+    assert_eq!(check!(file.as_file().metadata()).permissions().mode() & 0o777, 0o600);
+    let dir = check!(fs::TempDir::new_in(tmpdir.path()));
+    assert_eq!(check!(fs::metadata(dir.path())).permissions().mode() & 0o777, 0o700);
+}
+
+#[test]
+fn temp_file_persist() {
+    let tmpdir = tmpdir();
+    let target = tmpdir.join("target");
+    for (named, contents) in [(false, "first"), (true, "second"), (false, "third")] {
+        let mut file = if named {
+            check!(fs::TempFile::new_named_in(tmpdir.path()))
+        } else {
+            check!(fs::TempFile::new_in(tmpdir.path()))
+        };
+        check!(file.write_all(contents.as_bytes()));
+        // An existing file at the target is replaced.
+        let mut file = check!(file.persist(&target));
+        check!(file.write_all(b"!"));
+        drop(file);
+        assert_eq!(check!(fs::read_to_string(&target)), format!("{contents}!"));
+        let names: Vec<_> =
+            check!(fs::read_dir(tmpdir.path())).map(|entry| check!(entry).file_name()).collect();
+        assert_eq!(names, ["target"]);
+    }
+
+    // The file is returned on failure, and still removed when it is dropped.
+    let missing = tmpdir.join("missing/target");
+    let err = fs::TempFile::new_in(tmpdir.path()).unwrap().persist(&missing).unwrap_err();
+    assert_eq!(err.error().kind(), ErrorKind::NotFound);
+    let file = err.into_file();
+    check!(fs::create_dir(tmpdir.join("missing")));
+    check!(file.persist(&missing));
+    assert!(missing.is_file());
+}
+
+#[test]
+fn temp_dir() {
+    let tmpdir = tmpdir();
+    let dir = check!(fs::TempDir::new_in(tmpdir.path()));
+    let path = dir.path().to_path_buf();
+    assert!(path.starts_with(tmpdir.path()));
+    check!(fs::create_dir(path.join("sub")));
+    check!(fs::write(path.join("sub/file"), b"data"));
+    drop(dir);
+    assert!(!path.exists());
+
+    let dir = check!(fs::TempDir::new_in(tmpdir.path()));
+    let path = dir.keep();
+    assert!(path.is_dir());
+    check!(fs::remove_dir(&path));
+
+    let dir = check!(fs::TempDir::new_in(tmpdir.path()));
+    let path = dir.path().to_path_buf();
+    check!(dir.close());
+    assert!(!path.exists());
+
+    assert!(fs::TempDir::new_in(tmpdir.join("missing")).is_err());
+    assert!(fs::TempFile::new_in(tmpdir.join("missing")).is_err());
+}
+
+#[test]
+fn atomic_writer_replaces_on_finish() {
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("config");
+    check!(fs::write(&path, b"old"));
+
+    let mut writer = check!(fs::AtomicWriter::new(&path));
+    check!(writer.write_all(b"new"));
+    // Nothing is visible before the writer is finished.
+    assert_eq!(check!(fs::read(&path)), b"old");
+    check!(writer.finish());
+    assert_eq!(check!(fs::read(&path)), b"new");
+
+    // Dropping the writer rolls back.
+    let mut writer = check!(fs::AtomicWriter::new(&path));
+    check!(writer.write_all(b"discarded"));
+    drop(writer);
+    assert_eq!(check!(fs::read(&path)), b"new");
+
+    check!(fs::write_atomic(tmpdir.join("created"), b"created"));
+    assert_eq!(check!(fs::read(tmpdir.join("created"))), b"created");
+
+    let mut names: Vec<_> =
+        check!(fs::read_dir(tmpdir.path())).map(|entry| check!(entry).file_name()).collect();
+    names.sort();
+    assert_eq!(names, ["config", "created"]);
+
+    let err = fs::AtomicWriter::new(tmpdir.join("missing/config")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+#[cfg(unix)]
+fn atomic_writer_permissions() {
+    use crate::os::unix::fs::PermissionsExt;
+
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("script");
+    check!(fs::write(&path, b"old"));
+    check!(fs::set_permissions(&path, fs::Permissions::from_mode(0o751)));
+    check!(fs::write_atomic(&path, b"new"));
+    assert_eq!(check!(fs::metadata(&path)).permissions().mode() & 0o777, 0o751);
+
+    let mut writer = check!(fs::AtomicWriter::new(&path));
+    writer.set_permissions(fs::Permissions::from_mode(0o640));
+    check!(writer.finish());
+    assert_eq!(check!(fs::metadata(&path)).permissions().mode() & 0o777, 0o640);
+}
+
+#[test]
+#[cfg(unix)]
+fn atomic_writer_replaces_symlink() {
+    use crate::os::unix::fs::PermissionsExt;
+
+    let tmpdir = tmpdir();
+    let target = tmpdir.join("target");
+    let link = tmpdir.join("link");
+    check!(fs::write(&target, b"target"));
+    check!(fs::set_permissions(&target, fs::Permissions::from_mode(0o644)));
+    check!(crate::os::unix::fs::symlink(&target, &link));
+
+    check!(fs::write_atomic(&link, b"new"));
+    assert!(!check!(fs::symlink_metadata(&link)).is_symlink());
+    assert_eq!(check!(fs::read(&link)), b"new");
+    // The new file doesn't get the permissions of the previous target.
+    assert_eq!(check!(fs::metadata(&link)).permissions().mode() & 0o777, 0o600);
+    assert_eq!(check!(fs::read(&target)), b"target");
+}
@@ -0,0 +1,633 @@
+//! Recursive directory traversal, see [`walk_dir`].
+
+use super::{DirEntry, FileType, Metadata, ReadDir};
+use crate::cmp::Ordering;
+use crate::error::Error;
+use crate::ffi::OsStr;
+use crate::path::{Path, PathBuf};
+use crate::{fmt, fs, io, vec};
+
+/// Returns an iterator over the entries within a directory and all of its subdirectories.
+///
+/// The iterator yields a [`WalkDirEntry`] for `path` itself first, then one for every file,
+/// directory and symbolic link below it. The returned [`WalkDir`] can be used to configure the
+/// traversal before iterating over it:
+///
+/// * [`min_depth`] and [`max_depth`] limit which levels of the tree are yielded and visited.
+/// * [`sort_by`] and [`sort_by_file_name`] fix the order of the entries in each directory,
+///   which is otherwise platform and filesystem dependent, like for [`read_dir`].
+/// * [`contents_first`] yields every directory after its contents instead of before.
+/// * [`follow_links`] descends into symbolic links to directories, and detects loops.
+///
+/// Errors are yielded for the entries they concern, and the traversal continues afterwards. A
+/// directory that cannot be read is still yielded itself, followed by the error.
+///
+/// The walk is built on [`read_dir`] and [`DirEntry::file_type`], so no `stat` calls are made
+/// on platforms where reading a directory also returns the types of its entries, unless
+/// symbolic links are followed.
+///
+/// If `path` is a symbolic link it is always followed, as it would be by [`read_dir`].
+///
+/// [`min_depth`]: WalkDir::min_depth
+/// [`max_depth`]: WalkDir::max_depth
+/// [`sort_by`]: WalkDir::sort_by
+/// [`sort_by_file_name`]: WalkDir::sort_by_file_name
+/// [`contents_first`]: WalkDir::contents_first
+/// [`follow_links`]: WalkDir::follow_links
+/// [`read_dir`]: fs::read_dir
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_walk_dir)]
+/// use std::fs;
+///
+/// fn main() -> std::io::Result<()> {
+///     for entry in fs::walk_dir("src").sort_by_file_name() {
+///         let entry = entry?;
+///         if entry.file_type().is_file() {
+///             println!("{}", entry.path().display());
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub fn walk_dir<P: AsRef<Path>>(path: P) -> WalkDir {
+    WalkDir {
+        root: path.as_ref().to_path_buf(),
+        min_depth: 0,
+        max_depth: usize::MAX,
+        follow_links: false,
+        contents_first: false,
+        sort: None,
+    }
+}
+
+type SortFn = dyn FnMut(&WalkDirEntry, &WalkDirEntry) -> Ordering + Send + Sync;
+
+/// A builder for a recursive directory traversal.
+///
+/// This struct is created by [`walk_dir`], see its documentation for more.
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub struct WalkDir {
+    root: PathBuf,
+    min_depth: usize,
+    max_depth: usize,
+    follow_links: bool,
+    contents_first: bool,
+    sort: Option<Box<SortFn>>,
+}
+
+impl WalkDir {
+    /// Only yields entries at least `depth` levels below the root.
+    ///
+    /// The root is at depth 0 and its direct contents at depth 1, so a `min_depth` of 1 skips
+    /// the root. Shallower directories are still visited. The default is 0.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn min_depth(mut self, depth: usize) -> WalkDir {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Does not yield or visit entries more than `depth` levels below the root.
+    ///
+    /// Directories at `depth` are yielded, but not read. A `max_depth` of 0 only yields the
+    /// root and a `max_depth` of 1 behaves like [`read_dir`](fs::read_dir). There is no limit
+    /// by default.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn max_depth(mut self, depth: usize) -> WalkDir {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets whether symbolic links are followed.
+    ///
+    /// When enabled, entries for symbolic links have the [file type](WalkDirEntry::file_type)
+    /// of their target and links to directories are descended into. A link that points back
+    /// to one of its own ancestors is yielded as an error, for which
+    /// [`WalkDirError::loop_ancestor`] returns the ancestor, instead of being visited again.
+    /// Broken links are yielded as errors as well.
+    ///
+    /// This makes the walk look up the metadata of every link and directory it visits. It is
+    /// disabled by default.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn follow_links(mut self, follow: bool) -> WalkDir {
+        self.follow_links = follow;
+        self
+    }
+
+    /// Sets whether directories are yielded after their contents instead of before.
+    ///
+    /// This is useful to remove a tree, for example, as a directory is empty by the time it is
+    /// yielded. It is disabled by default.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn contents_first(mut self, contents_first: bool) -> WalkDir {
+        self.contents_first = contents_first;
+        self
+    }
+
+    /// Sorts the entries of every directory with `compare`.
+    ///
+    /// Each directory is read completely before its first entry is yielded. Errors that occur
+    /// while reading it are yielded before its entries.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(fs_walk_dir)]
+    /// use std::fs;
+    ///
+    /// // Visit the files of each directory before its subdirectories.
+    /// let walk = fs::walk_dir("src").sort_by(|a, b| {
+    ///     a.file_type().is_dir().cmp(&b.file_type().is_dir())
+    ///         .then_with(|| a.file_name().cmp(b.file_name()))
+    /// });
+    /// for entry in walk {
+    ///     println!("{}", entry.unwrap().path().display());
+    /// }
+    /// ```
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn sort_by<F>(mut self, compare: F) -> WalkDir
+    where
+        F: FnMut(&WalkDirEntry, &WalkDirEntry) -> Ordering + Send + Sync + 'static,
+    {
+        self.sort = Some(Box::new(compare));
+        self
+    }
+
+    /// Sorts the entries of every directory by their file names.
+    ///
+    /// This is a shorthand for [`sort_by`](Self::sort_by) that compares
+    /// [`WalkDirEntry::file_name`].
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn sort_by_file_name(self) -> WalkDir {
+        self.sort_by(|a, b| a.file_name().cmp(b.file_name()))
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl IntoIterator for WalkDir {
+    type Item = Result<WalkDirEntry, WalkDirError>;
+    type IntoIter = WalkDirIter;
+
+    fn into_iter(mut self) -> WalkDirIter {
+        let start = Some(crate::mem::take(&mut self.root));
+        WalkDirIter { opts: self, start, stack: Vec::new() }
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl fmt::Debug for WalkDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkDir")
+            .field("root", &self.root)
+            .field("min_depth", &self.min_depth)
+            .field("max_depth", &self.max_depth)
+            .field("follow_links", &self.follow_links)
+            .field("contents_first", &self.contents_first)
+            .field("sorted", &self.sort.is_some())
+            .finish()
+    }
+}
+
+/// An iterator over the entries of a directory tree.
+///
+/// This struct is created by calling `into_iter` on a [`WalkDir`].
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub struct WalkDirIter {
+    opts: WalkDir,
+    /// The root, until it has been visited.
+    start: Option<PathBuf>,
+    /// The directories being read, from the root down to the current one.
+    stack: Vec<Frame>,
+}
+
+struct Frame {
+    path: PathBuf,
+    list: DirList,
+    /// The directory's own entry, when it is yielded after its contents.
+    deferred: Option<WalkDirEntry>,
+    /// Identifies the directory for loop detection, if links are followed.
+    id: Option<DirId>,
+}
+
+enum DirList {
+    Read(ReadDir),
+    Sorted(vec::IntoIter<Result<WalkDirEntry, WalkDirError>>),
+    /// Reading the directory failed, and the error is yielded once.
+    Error(Option<WalkDirError>),
+}
+
+#[derive(PartialEq, Eq)]
+enum DirId {
+    Id(u64, u64),
+    /// For platforms without file IDs in `Metadata`.
+    #[allow(dead_code)] // not constructed on all platforms
+    Path(PathBuf),
+}
+
+impl WalkDirIter {
+    /// Skips the remaining contents of the current directory.
+    ///
+    /// The current directory is the one most recently yielded, or the parent of the file most
+    /// recently yielded. If [`contents_first`](WalkDir::contents_first) is set, the directory
+    /// itself is still yielded afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(fs_walk_dir)]
+    /// use std::fs;
+    ///
+    /// let mut walk = fs::walk_dir(".").into_iter();
+    /// while let Some(entry) = walk.next() {
+    ///     let entry = entry.unwrap();
+    ///     if entry.file_name() == ".git" {
+    ///         walk.skip_current_dir();
+    ///         continue;
+    ///     }
+    ///     println!("{}", entry.path().display());
+    /// }
+    /// ```
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn skip_current_dir(&mut self) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.list = DirList::Error(None);
+        }
+    }
+
+    /// Starts reading `entry` if it is a directory, and returns it if it is to be yielded now.
+    fn visit(&mut self, entry: WalkDirEntry) -> Option<Result<WalkDirEntry, WalkDirError>> {
+        let WalkDir { min_depth, max_depth, follow_links, contents_first, .. } = self.opts;
+        if !entry.file_type.is_dir() || entry.depth >= max_depth {
+            return (entry.depth >= min_depth).then_some(Ok(entry));
+        }
+
+        let id = if follow_links {
+            let id = match dir_id(&entry.path) {
+                Ok(id) => id,
+                Err(inner) => return Some(Err(WalkDirError::new(entry.path, entry.depth, inner))),
+            };
+            if let Some(ancestor) = self.stack.iter().find(|frame| frame.id.as_ref() == Some(&id)) {
+                return Some(Err(WalkDirError {
+                    loop_ancestor: Some(ancestor.path.clone()),
+                    ..WalkDirError::new(
+                        entry.path,
+                        entry.depth,
+                        io::const_error!(io::ErrorKind::FilesystemLoop, "filesystem loop found"),
+                    )
+                }));
+            }
+            Some(id)
+        } else {
+            None
+        };
+
+        let list = match fs::read_dir(&entry.path) {
+            Ok(read_dir) => match &mut self.opts.sort {
+                None => DirList::Read(read_dir),
+                Some(sort) => {
+                    let depth = entry.depth + 1;
+                    let mut entries: Vec<_> = read_dir
+                        .map(|child| child_entry(child, &entry.path, depth, follow_links))
+                        .collect();
+                    entries.sort_by(|a, b| match (a, b) {
+                        (Ok(a), Ok(b)) => sort(a, b),
+                        (Err(_), Err(_)) => Ordering::Equal,
+                        (Err(_), Ok(_)) => Ordering::Less,
+                        (Ok(_), Err(_)) => Ordering::Greater,
+                    });
+                    DirList::Sorted(entries.into_iter())
+                }
+            },
+            Err(inner) => {
+                DirList::Error(Some(WalkDirError::new(entry.path.clone(), entry.depth, inner)))
+            }
+        };
+
+        let path = entry.path.clone();
+        if contents_first {
+            self.stack.push(Frame { path, list, deferred: Some(entry), id });
+            None
+        } else {
+            self.stack.push(Frame { path, list, deferred: None, id });
+            (entry.depth >= min_depth).then_some(Ok(entry))
+        }
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl Iterator for WalkDirIter {
+    type Item = Result<WalkDirEntry, WalkDirError>;
+
+    fn next(&mut self) -> Option<Result<WalkDirEntry, WalkDirError>> {
+        if let Some(root) = self.start.take() {
+            match root_entry(root) {
+                Ok(entry) => {
+                    if let Some(next) = self.visit(entry) {
+                        return Some(next);
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let frame = self.stack.last_mut()?;
+            let next = match &mut frame.list {
+                DirList::Read(read_dir) => read_dir
+                    .next()
+                    .map(|child| child_entry(child, &frame.path, depth, self.opts.follow_links)),
+                DirList::Sorted(entries) => entries.next(),
+                DirList::Error(err) => err.take().map(Err),
+            };
+            match next {
+                Some(Ok(entry)) => {
+                    if let Some(next) = self.visit(entry) {
+                        return Some(next);
+                    }
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    let frame = self.stack.pop().unwrap();
+                    if let Some(entry) = frame.deferred {
+                        if entry.depth >= self.opts.min_depth {
+                            return Some(Ok(entry));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl fmt::Debug for WalkDirIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dirs: Vec<&Path> = self.stack.iter().map(|frame| frame.path.as_path()).collect();
+        f.debug_struct("WalkDirIter").field("opts", &self.opts).field("dirs", &dirs).finish()
+    }
+}
+
+fn root_entry(path: PathBuf) -> Result<WalkDirEntry, WalkDirError> {
+    let (file_type, is_symlink) = match fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => match fs::metadata(&path) {
+            Ok(metadata) => (metadata.file_type(), true),
+            Err(inner) => return Err(WalkDirError::new(path, 0, inner)),
+        },
+        Ok(metadata) => (metadata.file_type(), false),
+        Err(inner) => return Err(WalkDirError::new(path, 0, inner)),
+    };
+    Ok(WalkDirEntry { path, file_type, depth: 0, is_symlink, entry: None })
+}
+
+fn child_entry(
+    entry: io::Result<DirEntry>,
+    dir: &Path,
+    depth: usize,
+    follow_links: bool,
+) -> Result<WalkDirEntry, WalkDirError> {
+    // Errors from reading the directory itself are reported for the directory.
+    let entry = entry.map_err(|inner| WalkDirError::new(dir.to_path_buf(), depth - 1, inner))?;
+    let path = entry.path();
+    // Uses the file type cached by `read_dir` where the platform provides one.
+    let mut file_type = match entry.file_type() {
+        Ok(file_type) => file_type,
+        Err(inner) => return Err(WalkDirError::new(path, depth, inner)),
+    };
+    let is_symlink = file_type.is_symlink();
+    if follow_links && is_symlink {
+        match fs::metadata(&path) {
+            Ok(metadata) => file_type = metadata.file_type(),
+            Err(inner) => return Err(WalkDirError::new(path, depth, inner)),
+        }
+    }
+    Ok(WalkDirEntry {
+        path,
+        file_type,
+        depth,
+        is_symlink: follow_links && is_symlink,
+        entry: Some(entry),
+    })
+}
+
+#[cfg(any(unix, target_os = "wasi"))]
+fn dir_id(path: &Path) -> io::Result<DirId> {
+    #[cfg(unix)]
+    use crate::os::unix::fs::MetadataExt;
+    #[cfg(target_os = "wasi")]
+    use crate::os::wasi::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    Ok(DirId::Id(metadata.dev(), metadata.ino()))
+}
+
+#[cfg(windows)]
+fn dir_id(path: &Path) -> io::Result<DirId> {
+    use crate::os::windows::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    match (metadata.volume_serial_number(), metadata.file_index()) {
+        (Some(volume), Some(index)) => Ok(DirId::Id(volume.into(), index)),
+        _ => fs::canonicalize(path).map(DirId::Path),
+    }
+}
+
+#[cfg(not(any(unix, target_os = "wasi", windows)))]
+fn dir_id(path: &Path) -> io::Result<DirId> {
+    fs::canonicalize(path).map(DirId::Path)
+}
+
+/// An entry yielded by a [`WalkDirIter`].
+///
+/// Unlike a [`DirEntry`], this can also represent the root of the walk.
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub struct WalkDirEntry {
+    path: PathBuf,
+    file_type: FileType,
+    depth: usize,
+    /// Whether this is a symbolic link that was followed.
+    is_symlink: bool,
+    /// `None` for the root.
+    entry: Option<DirEntry>,
+}
+
+impl WalkDirEntry {
+    /// Returns the full path to this entry, which is the path the walk started from joined
+    /// with the names of the entries leading to it.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the full path to this entry, consuming it.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+
+    /// Returns the file name of this entry.
+    ///
+    /// For the root, this is the last component of the path the walk started from, or the
+    /// whole path if it has no file name, such as `..` or `/`.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn file_name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+
+    /// Returns the file type of this entry.
+    ///
+    /// If this entry is a symbolic link that was followed, this is the type of its target.
+    /// Otherwise it is the type of the entry itself, so it can be a symbolic link.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns how many levels below the root this entry is, where the root is at depth 0.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns `true` if this entry is a symbolic link that was followed.
+    ///
+    /// This can only happen for the root, or when [`WalkDir::follow_links`] is set.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn path_is_symlink(&self) -> bool {
+        self.is_symlink
+    }
+
+    /// Returns the metadata for this entry.
+    ///
+    /// If this entry is a symbolic link that was followed, this is the metadata of its target
+    /// and otherwise the metadata of the entry itself, consistent with
+    /// [`file_type`](Self::file_type). This may make a `stat` call, see [`DirEntry::metadata`].
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        match &self.entry {
+            _ if self.is_symlink => fs::metadata(&self.path),
+            Some(entry) => entry.metadata(),
+            None => fs::symlink_metadata(&self.path),
+        }
+    }
+
+    /// Returns the underlying [`DirEntry`], or `None` for the root of the walk.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn dir_entry(&self) -> Option<&DirEntry> {
+        self.entry.as_ref()
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl fmt::Debug for WalkDirEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkDirEntry")
+            .field("path", &self.path)
+            .field("file_type", &self.file_type)
+            .field("depth", &self.depth)
+            .field("path_is_symlink", &self.is_symlink)
+            .finish()
+    }
+}
+
+/// An error that occurred while walking a directory tree.
+///
+/// Besides the underlying [`io::Error`], this records the path the error occurred for and its
+/// depth in the tree. Converting it into an [`io::Error`] keeps the [`ErrorKind`] and includes
+/// the path in the message.
+///
+/// [`ErrorKind`]: io::ErrorKind
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+#[derive(Debug)]
+pub struct WalkDirError {
+    path: PathBuf,
+    depth: usize,
+    inner: io::Error,
+    loop_ancestor: Option<PathBuf>,
+}
+
+impl WalkDirError {
+    fn new(path: PathBuf, depth: usize, inner: io::Error) -> WalkDirError {
+        WalkDirError { path, depth, inner, loop_ancestor: None }
+    }
+
+    /// Returns the path of the entry or directory this error occurred for.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the depth of [`path`](Self::path) in the tree.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// If this error is for a symbolic link that leads back to one of its ancestors, returns
+    /// that ancestor.
+    ///
+    /// The [kind](io::Error::kind) of such errors is [`FilesystemLoop`].
+    ///
+    /// [`FilesystemLoop`]: io::ErrorKind::FilesystemLoop
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn loop_ancestor(&self) -> Option<&Path> {
+        self.loop_ancestor.as_deref()
+    }
+
+    /// Returns the underlying I/O error.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn io_error(&self) -> &io::Error {
+        &self.inner
+    }
+
+    /// Returns the underlying I/O error, consuming this error.
+    #[must_use]
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn into_io_error(self) -> io::Error {
+        self.inner
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl fmt::Display for WalkDirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.loop_ancestor {
+            Some(ancestor) => write!(
+                f,
+                "{}: {} (it leads back to {})",
+                self.path.display(),
+                self.inner,
+                ancestor.display(),
+            ),
+            None => write!(f, "{}: {}", self.path.display(), self.inner),
+        }
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl Error for WalkDirError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl From<WalkDirError> for io::Error {
+    fn from(err: WalkDirError) -> io::Error {
+        io::Error::new(err.inner.kind(), err)
+    }
+}
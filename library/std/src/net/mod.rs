@@ -26,6 +26,8 @@
 
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::ip_addr::{IpAddr, Ipv4Addr, Ipv6Addr, Ipv6MulticastScope};
+#[unstable(feature = "net_poll", issue = "none")]
+pub use self::poll::{Event, Events, Interest, Pollable, Poller};
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::socket_addr::{SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
 #[unstable(feature = "tcplistener_into_incoming", issue = "88373")]
@@ -37,6 +39,7 @@
 use crate::io::{self, ErrorKind};
 
 mod ip_addr;
+mod poll;
 mod socket_addr;
 mod tcp;
 #[cfg(test)]
@@ -0,0 +1,471 @@
+//! Waiting for readiness on several sockets at once, see [`Poller`].
+
+#[cfg(all(test, any(all(unix, not(target_os = "l4re")), windows)))]
+mod tests;
+
+use crate::marker::PhantomData;
+use crate::ops::{BitOr, BitOrAssign};
+#[cfg(any(all(unix, not(target_os = "l4re")), windows))]
+use crate::sys::net::{PollEvent, PollHandle, PollSet};
+use crate::time::Duration;
+use crate::{fmt, io, thread};
+
+/// The kinds of readiness a [`Poller`] waits for on a registered source.
+///
+/// Interests can be combined with the `|` operator.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[unstable(feature = "net_poll", issue = "none")]
+pub struct Interest {
+    readable: bool,
+    writable: bool,
+}
+
+impl Interest {
+    /// Interest in the source becoming readable.
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub const READABLE: Interest = Interest { readable: true, writable: false };
+
+    /// Interest in the source becoming writable.
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub const WRITABLE: Interest = Interest { readable: false, writable: true };
+
+    /// Interest in neither, which still reports errors and hangups.
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub const NONE: Interest = Interest { readable: false, writable: false };
+
+    /// Returns `true` if this includes [`Interest::READABLE`].
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub const fn is_readable(self) -> bool {
+        self.readable
+    }
+
+    /// Returns `true` if this includes [`Interest::WRITABLE`].
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub const fn is_writable(self) -> bool {
+        self.writable
+    }
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl BitOr for Interest {
+    type Output = Interest;
+
+    fn bitor(self, rhs: Interest) -> Interest {
+        Interest {
+            readable: self.readable || rhs.readable,
+            writable: self.writable || rhs.writable,
+        }
+    }
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl BitOrAssign for Interest {
+    fn bitor_assign(&mut self, rhs: Interest) {
+        *self = *self | rhs;
+    }
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl fmt::Debug for Interest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.readable, self.writable) {
+            (true, true) => f.write_str("READABLE | WRITABLE"),
+            (true, false) => f.write_str("READABLE"),
+            (false, true) => f.write_str("WRITABLE"),
+            (false, false) => f.write_str("NONE"),
+        }
+    }
+}
+
+mod private {
+    pub trait Sealed {
+        fn poll_handle(&self) -> super::PollHandle<'_>;
+    }
+}
+
+/// A socket or other handle that can be registered with a [`Poller`].
+///
+/// This is implemented for every type that implements [`AsFd`] on Unix and [`AsSocket`] on
+/// Windows, which includes [`TcpStream`], [`TcpListener`] and [`UdpSocket`]. It is not
+/// implemented on other platforms, where polling is not supported.
+///
+/// This trait is sealed and cannot be implemented outside of the standard library.
+///
+/// [`AsFd`]: crate::os::fd::AsFd
+/// [`AsSocket`]: crate::os::windows::io::AsSocket
+/// [`TcpStream`]: crate::net::TcpStream
+/// [`TcpListener`]: crate::net::TcpListener
+/// [`UdpSocket`]: crate::net::UdpSocket
+#[unstable(feature = "net_poll", issue = "none")]
+pub trait Pollable: private::Sealed {}
+
+#[cfg(all(unix, not(target_os = "l4re")))]
+impl<T: crate::os::fd::AsFd + ?Sized> private::Sealed for T {
+    fn poll_handle(&self) -> PollHandle<'_> {
+        self.as_fd()
+    }
+}
+
+#[cfg(all(unix, not(target_os = "l4re")))]
+#[unstable(feature = "net_poll", issue = "none")]
+impl<T: crate::os::fd::AsFd + ?Sized> Pollable for T {}
+
+#[cfg(windows)]
+impl<T: crate::os::windows::io::AsSocket + ?Sized> private::Sealed for T {
+    fn poll_handle(&self) -> PollHandle<'_> {
+        self.as_socket()
+    }
+}
+
+#[cfg(windows)]
+#[unstable(feature = "net_poll", issue = "none")]
+impl<T: crate::os::windows::io::AsSocket + ?Sized> Pollable for T {}
+
+/// Waits for readiness on a set of sockets.
+///
+/// Sources are registered with [`add`], which returns a key that identifies the source in the
+/// [`Event`]s reported after calling [`poll`]. A `Poller` borrows its sources, so it is
+/// typically rebuilt for every iteration of an event loop, which keeps it in sync with the set
+/// of open connections. Readiness is level-triggered: an event is reported for as long as the
+/// condition holds, not only when it changes.
+///
+/// Readiness is only a hint. A read or write can still block after an event, for example if
+/// another thread got to the data first, so sources should be put in non-blocking mode with
+/// `set_nonblocking` and [`io::ErrorKind::WouldBlock`] errors should be handled.
+///
+/// [`add`]: Poller::add
+/// [`poll`]: Poller::poll
+///
+/// # Platform-specific behavior
+///
+/// This currently uses `poll` on Unix and `WSAPoll` on Windows. Polling is not supported on
+/// other platforms, where no type implements [`Pollable`]. Note that this
+/// [may change in the future][changes].
+///
+/// [changes]: io#platform-specific-behavior
+///
+/// # Examples
+///
+/// A single-threaded echo server:
+///
+/// ```no_run
+/// #![feature(net_poll)]
+/// use std::io::{self, Read, Write};
+/// use std::net::{Interest, Poller, TcpListener, TcpStream};
+///
+/// fn main() -> io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:8080")?;
+///     listener.set_nonblocking(true)?;
+///     let mut clients: Vec<TcpStream> = Vec::new();
+///     loop {
+///         let mut poller = Poller::new();
+///         poller.add(&listener, Interest::READABLE);
+///         for client in &clients {
+///             poller.add(client, Interest::READABLE);
+///         }
+///         poller.poll(None)?;
+///
+///         let mut closed = Vec::new();
+///         let mut accept = false;
+///         for event in poller.events() {
+///             if event.key() == 0 {
+///                 accept = true;
+///                 continue;
+///             }
+///             let index = event.key() - 1;
+///             let mut buf = [0; 1024];
+///             match (&clients[index]).read(&mut buf) {
+///                 Ok(0) => closed.push(index),
+///                 Ok(n) => (&clients[index]).write_all(&buf[..n])?,
+///                 Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+///                 Err(_) => closed.push(index),
+///             }
+///         }
+///         drop(poller);
+///
+///         for index in closed.into_iter().rev() {
+///             clients.swap_remove(index);
+///         }
+///         if accept {
+///             let (client, _) = listener.accept()?;
+///             client.set_nonblocking(true)?;
+///             clients.push(client);
+///         }
+///     }
+/// }
+/// ```
+#[unstable(feature = "net_poll", issue = "none")]
+pub struct Poller<'a> {
+    set: PollSet,
+    _sources: PhantomData<&'a ()>,
+}
+
+impl<'a> Poller<'a> {
+    /// Creates an empty poller.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub const fn new() -> Poller<'a> {
+        Poller { set: PollSet::new(), _sources: PhantomData }
+    }
+
+    /// Registers `source` with the given interest, and returns the key of the new entry.
+    ///
+    /// Keys are assigned in order, starting at zero, and stay valid until [`clear`] is called.
+    /// A source can be registered more than once.
+    ///
+    /// [`clear`]: Poller::clear
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn add<S: Pollable + ?Sized>(&mut self, source: &'a S, interest: Interest) -> usize {
+        self.set.push(source.poll_handle(), interest.readable, interest.writable);
+        self.set.len() - 1
+    }
+
+    /// Changes the interest of the entry with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` was not returned by [`add`](Poller::add) since the last [`clear`].
+    ///
+    /// [`clear`]: Poller::clear
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn set_interest(&mut self, key: usize, interest: Interest) {
+        self.set.set_interest(key, interest.readable, interest.writable);
+    }
+
+    /// Removes all registered sources.
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn clear(&mut self) {
+        self.set.clear();
+    }
+
+    /// Returns the number of registered sources.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Returns `true` if no sources are registered.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Blocks the current thread until at least one registered source is ready, or until
+    /// `timeout` has elapsed.
+    ///
+    /// Returns the number of sources for which readiness was reported, which is zero if the
+    /// timeout elapsed first. The readiness itself can then be inspected with
+    /// [`events`](Poller::events). A `timeout` of `None` waits indefinitely.
+    ///
+    /// Errors and hangups are always reported, even for sources registered with
+    /// [`Interest::NONE`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if no sources are registered
+    /// and `timeout` is `None`, as that would block forever.
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<usize> {
+        if self.is_empty() {
+            // Not every platform allows polling an empty set, so just sleep instead.
+            return match timeout {
+                Some(timeout) => {
+                    thread::sleep(timeout);
+                    Ok(0)
+                }
+                None => Err(io::const_error!(
+                    io::ErrorKind::InvalidInput,
+                    "cannot wait indefinitely without any sources to poll",
+                )),
+            };
+        }
+        self.set.poll(timeout)
+    }
+
+    /// Returns an iterator over the readiness reported by the last call to
+    /// [`poll`](Poller::poll), in the order of the keys.
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn events(&self) -> Events<'_> {
+        Events { set: &self.set, key: 0 }
+    }
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl Default for Poller<'_> {
+    fn default() -> Self {
+        Poller::new()
+    }
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl fmt::Debug for Poller<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Poller").field("len", &self.len()).finish_non_exhaustive()
+    }
+}
+
+/// The readiness of a source registered with a [`Poller`].
+#[derive(Clone, Copy)]
+#[unstable(feature = "net_poll", issue = "none")]
+pub struct Event {
+    key: usize,
+    inner: PollEvent,
+}
+
+impl Event {
+    /// Returns the key that [`Poller::add`] returned for the source.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Returns `true` if reading from the source will not block.
+    ///
+    /// This is also the case when the peer closed the connection or an error is pending, so
+    /// that the read reports the end of the stream or the error.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn is_readable(&self) -> bool {
+        self.inner.is_readable()
+    }
+
+    /// Returns `true` if writing to the source will not block.
+    ///
+    /// This is also the case when an error is pending, so that the write reports the error.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn is_writable(&self) -> bool {
+        self.inner.is_writable()
+    }
+
+    /// Returns `true` if the peer closed the connection, or the connection was reset.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn is_hangup(&self) -> bool {
+        self.inner.is_hangup()
+    }
+
+    /// Returns `true` if an error is pending on the source, or it is not a valid socket.
+    #[must_use]
+    #[unstable(feature = "net_poll", issue = "none")]
+    pub fn is_error(&self) -> bool {
+        self.inner.is_error()
+    }
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl fmt::Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("key", &self.key)
+            .field("readable", &self.is_readable())
+            .field("writable", &self.is_writable())
+            .field("hangup", &self.is_hangup())
+            .field("error", &self.is_error())
+            .finish()
+    }
+}
+
+/// An iterator over the [`Event`]s reported by a [`Poller`].
+///
+/// This struct is created by [`Poller::events`].
+#[unstable(feature = "net_poll", issue = "none")]
+pub struct Events<'p> {
+    set: &'p PollSet,
+    key: usize,
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl Iterator for Events<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        while self.key < self.set.len() {
+            let key = self.key;
+            self.key += 1;
+            if let Some(inner) = self.set.event(key) {
+                return Some(Event { key, inner });
+            }
+        }
+        None
+    }
+}
+
+#[unstable(feature = "net_poll", issue = "none")]
+impl fmt::Debug for Events<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Events").field("key", &self.key).finish_non_exhaustive()
+    }
+}
+
+/// Fallback for platforms without `poll`, on which no sources can be registered.
+#[cfg(not(any(all(unix, not(target_os = "l4re")), windows)))]
+mod unsupported {
+    use crate::io;
+    use crate::marker::PhantomData;
+    use crate::time::Duration;
+
+    pub struct PollHandle<'a>(!, PhantomData<&'a ()>);
+
+    pub struct PollSet(());
+
+    #[derive(Clone, Copy)]
+    pub struct PollEvent(!);
+
+    impl PollSet {
+        pub const fn new() -> PollSet {
+            PollSet(())
+        }
+
+        pub fn len(&self) -> usize {
+            0
+        }
+
+        pub fn push(&mut self, handle: PollHandle<'_>, _readable: bool, _writable: bool) {
+            handle.0
+        }
+
+        pub fn set_interest(&mut self, index: usize, _readable: bool, _writable: bool) {
+            panic!("invalid poller key {index}")
+        }
+
+        pub fn clear(&mut self) {}
+
+        pub fn poll(&mut self, _timeout: Option<Duration>) -> io::Result<usize> {
+            Err(io::const_error!(
+                io::ErrorKind::Unsupported,
+                "polling is not supported on this platform",
+            ))
+        }
+
+        pub fn event(&self, _index: usize) -> Option<PollEvent> {
+            None
+        }
+    }
+
+    impl PollEvent {
+        pub fn is_readable(self) -> bool {
+            self.0
+        }
+
+        pub fn is_writable(self) -> bool {
+            self.0
+        }
+
+        pub fn is_hangup(self) -> bool {
+            self.0
+        }
+
+        pub fn is_error(self) -> bool {
+            self.0
+        }
+    }
+}
+
+#[cfg(not(any(all(unix, not(target_os = "l4re")), windows)))]
+use unsupported::{PollEvent, PollHandle, PollSet};
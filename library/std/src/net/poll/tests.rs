@@ -0,0 +1,117 @@
+use crate::io::ErrorKind;
+use crate::io::prelude::*;
+use crate::net::test::next_test_ip4;
+use crate::net::{Interest, Poller, TcpListener, TcpStream, UdpSocket};
+use crate::time::{Duration, Instant};
+
+macro_rules! t {
+    ($e:expr) => {
+        match $e {
+            Ok(t) => t,
+            Err(e) => panic!("received error for `{}`: {}", stringify!($e), e),
+        }
+    };
+}
+
+#[test]
+fn interest() {
+    let both = Interest::READABLE | Interest::WRITABLE;
+    assert!(both.is_readable() && both.is_writable());
+    let mut interest = Interest::NONE;
+    assert!(!interest.is_readable() && !interest.is_writable());
+    interest |= Interest::WRITABLE;
+    assert_eq!(interest, Interest::WRITABLE);
+    assert_eq!(format!("{both:?}"), "READABLE | WRITABLE");
+}
+
+#[test]
+fn empty() {
+    let mut poller = Poller::new();
+    assert!(poller.is_empty());
+    assert_eq!(t!(poller.poll(Some(Duration::from_millis(1)))), 0);
+    assert_eq!(poller.poll(None).unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn tcp_readiness() {
+    let addr = next_test_ip4();
+    let listener = t!(TcpListener::bind(&addr));
+    let mut client = t!(TcpStream::connect(&addr));
+    let (mut server, _) = t!(listener.accept());
+
+    {
+        let mut poller = Poller::new();
+        let listener_key = poller.add(&listener, Interest::READABLE);
+        let server_key = poller.add(&server, Interest::READABLE);
+        let client_key = poller.add(&client, Interest::WRITABLE);
+        assert_eq!((listener_key, server_key, client_key), (0, 1, 2));
+        assert_eq!(poller.len(), 3);
+
+        // Only the client can make progress, by writing.
+        assert_eq!(t!(poller.poll(Some(Duration::from_secs(10)))), 1);
+        let events: Vec<_> = poller.events().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].key(), client_key);
+        assert!(events[0].is_writable() && !events[0].is_readable());
+
+        // Without interest in writing, nothing is ready until the timeout.
+        poller.set_interest(client_key, Interest::NONE);
+        let start = Instant::now();
+        assert_eq!(t!(poller.poll(Some(Duration::from_millis(20)))), 0);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(poller.events().count(), 0);
+    }
+
+    t!(client.write_all(b"ping"));
+    {
+        let mut poller = Poller::new();
+        poller.add(&listener, Interest::READABLE);
+        let server_key = poller.add(&server, Interest::READABLE);
+        assert_eq!(t!(poller.poll(None)), 1);
+        let event = poller.events().next().unwrap();
+        assert_eq!(event.key(), server_key);
+        assert!(event.is_readable() && !event.is_hangup());
+    }
+    let mut buf = [0; 4];
+    t!(server.read_exact(&mut buf));
+    assert_eq!(&buf, b"ping");
+
+    // A closed connection is reported as readable, so that the read returns the end of stream.
+    drop(client);
+    let mut poller = Poller::new();
+    poller.add(&server, Interest::READABLE);
+    assert_eq!(t!(poller.poll(Some(Duration::from_secs(10)))), 1);
+    assert!(poller.events().next().unwrap().is_readable());
+    drop(poller);
+    assert_eq!(t!(server.read(&mut buf)), 0);
+
+    // Pending connections make the listener readable.
+    let _client = t!(TcpStream::connect(&addr));
+    let mut poller = Poller::new();
+    poller.add(&listener, Interest::READABLE);
+    assert_eq!(t!(poller.poll(Some(Duration::from_secs(10)))), 1);
+}
+
+#[test]
+fn udp_readiness() {
+    let (addr1, addr2) = (next_test_ip4(), next_test_ip4());
+    let socket1 = t!(UdpSocket::bind(&addr1));
+    let socket2 = t!(UdpSocket::bind(&addr2));
+
+    let mut poller = Poller::new();
+    poller.add(&socket1, Interest::READABLE);
+    let key2 = poller.add(&socket2, Interest::READABLE);
+    assert_eq!(t!(poller.poll(Some(Duration::ZERO))), 0);
+
+    t!(socket1.send_to(b"x", &addr2));
+    assert_eq!(t!(poller.poll(Some(Duration::from_secs(10)))), 1);
+    let keys: Vec<_> = poller.events().map(|event| event.key()).collect();
+    assert_eq!(keys, [key2]);
+
+    // The same source can be registered with several interests.
+    poller.clear();
+    poller.add(&socket1, Interest::READABLE);
+    poller.add(&socket1, Interest::READABLE | Interest::WRITABLE);
+    assert_eq!(t!(poller.poll(Some(Duration::from_secs(10)))), 1);
+    assert_eq!(poller.events().next().unwrap().key(), 1);
+}
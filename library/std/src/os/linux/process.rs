@@ -11,6 +11,7 @@
 #[cfg(not(doc))]
 use crate::sys::{fd::FileDesc, linux::pidfd::PidFd as InnerPidFd};
 use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use crate::time::Duration;
 
 #[cfg(doc)]
 struct InnerPidFd;
@@ -84,6 +85,16 @@ pub fn wait(&self) -> Result<ExitStatus> {
     pub fn try_wait(&self) -> Result<Option<ExitStatus>> {
         Ok(self.inner.try_wait()?.map(FromInner::from_inner))
     }
+
+    /// Waits for the child to exit, giving up once `timeout` has elapsed.
+    ///
+    /// Unlike [`Child::wait_timeout`] this method will return an Error
+    /// if the child has already been reaped.
+    ///
+    /// [`Child::wait_timeout`]: process::Child::wait_timeout
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<Option<ExitStatus>> {
+        Ok(self.inner.wait_timeout(timeout)?.map(FromInner::from_inner))
+    }
 }
 
 impl AsInner<InnerPidFd> for PidFd {
@@ -360,6 +360,53 @@ fn into_raw(self) -> i32 {
     }
 }
 
+/// Unix-specific extensions to [`process::Child`].
+///
+/// This trait is sealed: it cannot be implemented outside the standard library.
+/// This is so that future additional methods are not breaking changes.
+#[unstable(feature = "process_new_group", issue = "none")]
+pub trait ChildExt: Sealed {
+    /// Sends `signal` to every process in the child's process group.
+    ///
+    /// Like [`Child::kill_process_group`], this only works for children spawned into their own
+    /// process group, and returns an [`InvalidInput`] error for any other child. It is
+    /// equivalent to calling `killpg` with the child's process group.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_new_group, child_wait_timeout)]
+    /// use std::os::unix::process::ChildExt;
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// const SIGTERM: i32 = 15;
+    ///
+    /// let mut child = Command::new("make")
+    ///     .new_process_group(true)
+    ///     .spawn()
+    ///     .expect("make failed to start");
+    ///
+    /// // Give the build a chance to shut down cleanly before killing it.
+    /// child.signal_process_group(SIGTERM).expect("failed to signal the build");
+    /// if child.wait_timeout(Duration::from_secs(5)).unwrap().is_none() {
+    ///     child.kill_process_group().expect("failed to kill the build");
+    ///     child.wait().unwrap();
+    /// }
+    /// ```
+    ///
+    /// [`Child::kill_process_group`]: process::Child::kill_process_group
+    /// [`InvalidInput`]: io::ErrorKind::InvalidInput
+    fn signal_process_group(&mut self, signal: i32) -> io::Result<()>;
+}
+
+#[unstable(feature = "process_new_group", issue = "none")]
+impl ChildExt for process::Child {
+    fn signal_process_group(&mut self, signal: i32) -> io::Result<()> {
+        self.as_inner_mut().signal_process_group(signal)
+    }
+}
+
 #[stable(feature = "process_extensions", since = "1.2.0")]
 impl FromRawFd for process::Stdio {
     #[inline]
@@ -2148,6 +2148,16 @@ pub struct Path {
 #[stable(since = "1.7.0", feature = "strip_prefix")]
 pub struct StripPrefixError(());
 
+/// An error returned from [`Path::relative_to`] if no relative path could be computed.
+///
+/// This `struct` is created by the [`relative_to`] method on [`Path`].
+/// See its documentation for more.
+///
+/// [`relative_to`]: Path::relative_to
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[unstable(feature = "path_relative_to", issue = "none")]
+pub struct RelativeToError(());
+
 impl Path {
     // The following (private!) function allows construction of a path from a u8
     // slice, which is only safe when it is known to follow the OsStr encoding.
@@ -2588,6 +2598,144 @@ fn _ends_with(&self, child: &Path) -> bool {
         iter_after(self.components().rev(), child.components().rev()).is_some()
     }
 
+    /// Normalizes the path lexically, without accessing the filesystem.
+    ///
+    /// This removes `.` components, repeated separators and trailing separators, and resolves
+    /// each `..` component by removing the normal component before it:
+    ///
+    /// * A [prefix] and a root directory are kept as they are.
+    /// * `..` components directly after a root directory are removed, as the parent of a root
+    ///   is the root itself.
+    /// * `..` components at the start of a relative path are kept, as they refer to directories
+    ///   outside of the path. This includes paths that only have a prefix, such as `C:..`.
+    /// * A relative path that normalizes to nothing becomes `.`.
+    ///
+    /// Unlike [`fs::canonicalize`], this does not resolve symbolic links, so the result can refer
+    /// to a different file than `self` if `..` follows a symbolic link. Paths with a
+    /// [verbatim prefix] are normalized the same way, even though Windows does not interpret
+    /// `.` and `..` in them.
+    ///
+    /// [prefix]: Prefix
+    /// [verbatim prefix]: Prefix::is_verbatim
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(normalize_lexically)]
+    /// use std::path::Path;
+    ///
+    /// assert_eq!(Path::new("/a/./b/../c/").normalize_lexically(), Path::new("/a/c"));
+    /// assert_eq!(Path::new("/../a").normalize_lexically(), Path::new("/a"));
+    /// assert_eq!(Path::new("a/../../b").normalize_lexically(), Path::new("../b"));
+    /// assert_eq!(Path::new("a/..").normalize_lexically(), Path::new("."));
+    /// ```
+    #[unstable(feature = "normalize_lexically", issue = "none")]
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn normalize_lexically(&self) -> PathBuf {
+        let mut normalized = PathBuf::with_capacity(self.as_os_str().len());
+        let mut has_root = false;
+        // Number of normal components at the end of `normalized` which `..` can remove.
+        let mut depth = 0usize;
+        for component in self.components() {
+            match component {
+                Component::Prefix(_) => normalized.push(component),
+                Component::RootDir => {
+                    normalized.push(component);
+                    has_root = true;
+                }
+                Component::CurDir => {}
+                Component::ParentDir if depth > 0 => {
+                    normalized.pop();
+                    depth -= 1;
+                }
+                Component::ParentDir if has_root => {}
+                Component::ParentDir => normalized.push(component),
+                Component::Normal(_) => {
+                    normalized.push(component);
+                    depth += 1;
+                }
+            }
+        }
+        if normalized.as_os_str().is_empty() {
+            normalized.push(Component::CurDir);
+        }
+        normalized
+    }
+
+    /// Returns a relative path that, when joined onto `base`, refers to the same location as
+    /// `self`.
+    ///
+    /// Both paths are first [normalized lexically](Path::normalize_lexically), and the result
+    /// is computed from their components alone, without accessing the filesystem. Components
+    /// are compared exactly, as by [`starts_with`], so this does not account for
+    /// case-insensitive filesystems. If both paths are the same, the result is `.`.
+    ///
+    /// [`starts_with`]: Path::starts_with
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if:
+    ///
+    /// * `self` and `base` do not have the same [prefix](Prefix), or one of them has a root
+    ///   directory and the other does not, or
+    /// * `base` would have to be left through a `..` component that is still present after
+    ///   normalization, as the name of the directory that `..` refers to is unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(path_relative_to)]
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("/usr/lib/libc.so");
+    ///
+    /// assert_eq!(path.relative_to("/usr/lib"), Ok("libc.so".into()));
+    /// assert_eq!(path.relative_to("/usr/bin"), Ok("../lib/libc.so".into()));
+    /// assert_eq!(path.relative_to("/usr/./lib/.."), Ok("lib/libc.so".into()));
+    /// assert_eq!(path.relative_to(path), Ok(".".into()));
+    ///
+    /// assert!(path.relative_to("usr/lib").is_err());
+    /// assert!(Path::new("a").relative_to("../b").is_err());
+    /// ```
+    #[unstable(feature = "path_relative_to", issue = "none")]
+    pub fn relative_to<P: AsRef<Path>>(&self, base: P) -> Result<PathBuf, RelativeToError> {
+        self._relative_to(base.as_ref())
+    }
+
+    fn _relative_to(&self, base: &Path) -> Result<PathBuf, RelativeToError> {
+        let path = self.normalize_lexically();
+        let base = base.normalize_lexically();
+        let is_head = |c: &Component<'_>| matches!(c, Component::Prefix(_) | Component::RootDir);
+        // A normalized path only contains `.` if that is all there is to it.
+        let mut path_iter = path.components().filter(|c| *c != Component::CurDir).peekable();
+        let mut base_iter = base.components().filter(|c| *c != Component::CurDir).peekable();
+
+        // The prefix and root directory have to match, then skip the components both share.
+        while let (Some(a), Some(b)) = (path_iter.peek(), base_iter.peek()) {
+            if a != b {
+                break;
+            }
+            path_iter.next();
+            base_iter.next();
+        }
+        if path_iter.peek().is_some_and(is_head) || base_iter.peek().is_some_and(is_head) {
+            return Err(RelativeToError(()));
+        }
+
+        let mut relative = PathBuf::new();
+        for component in base_iter {
+            if component == Component::ParentDir {
+                return Err(RelativeToError(()));
+            }
+            relative.push(Component::ParentDir);
+        }
+        relative.extend(path_iter);
+        if relative.as_os_str().is_empty() {
+            relative.push(Component::CurDir);
+        }
+        Ok(relative)
+    }
+
     /// Extracts the stem (non-extension) portion of [`self.file_name`].
     ///
     /// [`self.file_name`]: Path::file_name
@@ -3503,6 +3651,16 @@ fn description(&self) -> &str {
     }
 }
 
+#[unstable(feature = "path_relative_to", issue = "none")]
+impl fmt::Display for RelativeToError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("no relative path from the base path")
+    }
+}
+
+#[unstable(feature = "path_relative_to", issue = "none")]
+impl Error for RelativeToError {}
+
 /// Makes the path absolute without accessing the filesystem.
 ///
 /// If the path is relative, the current directory is used as the base directory.
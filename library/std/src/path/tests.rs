@@ -1953,6 +1953,98 @@ fn test_extension_path_sep_alternate() {
     assert_eq!(path, Path::new("path/to/file.d\\test"));
 }
 
+#[test]
+fn test_normalize_lexically() {
+    macro_rules! tn (
+        ($path:expr, $expected:expr) => ({
+            let actual = Path::new($path).normalize_lexically();
+            assert!(actual == Path::new($expected),
+                    "normalizing {:?}: Expected {:?}, got {:?}",
+                    $path, $expected, actual);
+        });
+    );
+
+    tn!("", ".");
+    tn!(".", ".");
+    tn!("./", ".");
+    tn!("a/..", ".");
+    tn!("a/b/../..", ".");
+    tn!("a", "a");
+    tn!("./a/./b/", "a/b");
+    tn!("a//b", "a/b");
+    tn!("a/b/../c", "a/c");
+    tn!("..", "..");
+    tn!("../a/..", "..");
+    tn!("a/../../b", "../b");
+    tn!("../../a/b/..", "../../a");
+    tn!("/", "/");
+    tn!("/..", "/");
+    tn!("/../a/./b/..", "/a");
+    tn!("/a/b/../../..", "/");
+
+    if cfg!(windows) {
+        tn!(r"C:\a\..\..\b", r"C:\b");
+        tn!(r"C:a\..\..\b", r"C:..\b");
+        tn!(r"C:..", r"C:..");
+        tn!(r"C:.", r"C:");
+        tn!(r"\\server\share\a\..\..", r"\\server\share\");
+        tn!(r"\\?\C:\a\.\b\..", r"\\?\C:\a");
+    }
+}
+
+#[test]
+fn test_relative_to() {
+    macro_rules! tr (
+        ($path:expr, $base:expr, $expected:expr) => ({
+            let actual = Path::new($path).relative_to($base);
+            assert!(actual.as_deref() == Ok(Path::new($expected)),
+                    "{:?} relative to {:?}: Expected {:?}, got {:?}",
+                    $path, $base, $expected, actual);
+        });
+        ($path:expr, $base:expr) => ({
+            let actual = Path::new($path).relative_to($base);
+            assert!(actual.is_err(),
+                    "{:?} relative to {:?}: Expected an error, got {:?}",
+                    $path, $base, actual);
+        });
+    );
+
+    tr!("/a/b/c", "/a/b", "c");
+    tr!("/a/b/c", "/a/b/c", ".");
+    tr!("/a/b", "/a/b/c/d", "../..");
+    tr!("/a/b/c", "/a/d/e", "../../b/c");
+    tr!("/a/b/c", "/", "a/b/c");
+    tr!("/", "/a/b", "../..");
+    tr!("/a/./b/../c", "/a/c/", ".");
+    tr!("/../a", "/a", ".");
+    tr!("a/b", "a", "b");
+    tr!("a/b", ".", "a/b");
+    tr!(".", "a/b", "../..");
+    tr!("a", "b", "../a");
+    tr!("../a", "..", "a");
+    tr!("../a", "../b", "../a");
+    tr!("../../a", "../b", "../../a");
+    tr!("..", "a", "../..");
+    tr!("a/../..", ".", "..");
+
+    tr!("/a", "a");
+    tr!("a", "/a");
+    tr!("a", "..");
+    tr!("a", "../b");
+    tr!("../a", "../../b");
+
+    if cfg!(windows) {
+        tr!(r"C:\a\b", r"C:\a", "b");
+        tr!(r"C:\a\b", r"c:\a\c", r"..\b");
+        tr!(r"C:a", r"C:b", r"..\a");
+        tr!(r"\\server\share\a", r"\\server\share\b\c", r"..\..\a");
+        tr!(r"C:\a", r"D:\a");
+        tr!(r"C:\a", r"C:a");
+        tr!(r"C:\a", r"\a");
+        tr!(r"\\server\share\a", r"\\server\other\a");
+    }
+}
+
 #[bench]
 #[cfg_attr(miri, ignore)] // Miri isn't fast...
 fn bench_path_cmp_fast_path_buf_sort(b: &mut test::Bencher) {
@@ -170,6 +170,7 @@
 #[stable(feature = "command_access", since = "1.57.0")]
 pub use crate::sys_common::process::CommandEnvs;
 use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use crate::time::Duration;
 use crate::{fmt, fs, str};
 
 /// Representation of a running or exited child process.
@@ -268,6 +269,13 @@ fn as_inner(&self) -> &imp::Process {
     }
 }
 
+impl AsInnerMut<imp::Process> for Child {
+    #[inline]
+    fn as_inner_mut(&mut self) -> &mut imp::Process {
+        &mut self.handle
+    }
+}
+
 impl FromInner<(imp::Process, imp::StdioPipes)> for Child {
     fn from_inner((handle, io): (imp::Process, imp::StdioPipes)) -> Child {
         Child {
@@ -1019,6 +1027,51 @@ pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
         self
     }
 
+    /// Sets whether the child process is spawned into a new process group.
+    ///
+    /// The child and every process it spawns in turn (unless they explicitly leave the group)
+    /// can then be killed together with [`Child::kill_process_group`], which makes it possible
+    /// to cancel a tool without leaking the processes it started.
+    ///
+    /// A child in its own process group is no longer sent the interrupt signal generated by
+    /// pressing Ctrl+C in the parent's terminal or console.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Unix, the child calls `setpgid(0, 0)` and becomes the leader of a new process group.
+    /// Passing `true` overrides any process group set with [`CommandExt::process_group`], and
+    /// vice versa. Passing `false` undoes an earlier `true`, but leaves a process group set with
+    /// `CommandExt::process_group` alone.
+    ///
+    /// On Windows, the child is created with `CREATE_NEW_PROCESS_GROUP` and assigned to a new
+    /// job object before it starts running, and the job object stands in for the process group.
+    ///
+    /// On other platforms, this setting may be ignored, and `Child::kill_process_group` returns
+    /// an error.
+    ///
+    /// [`CommandExt::process_group`]: crate::os::unix::process::CommandExt::process_group
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_new_group)]
+    /// use std::process::Command;
+    ///
+    /// let mut child = Command::new("make")
+    ///     .new_process_group(true)
+    ///     .spawn()
+    ///     .expect("make failed to start");
+    ///
+    /// // Cancel the build, including any compilers `make` has started.
+    /// child.kill_process_group().expect("failed to kill the build");
+    /// child.wait().expect("make wasn't running");
+    /// ```
+    #[unstable(feature = "process_new_group", issue = "none")]
+    pub fn new_process_group(&mut self, new_group: bool) -> &mut Command {
+        self.inner.new_process_group(new_group);
+        self
+    }
+
     /// Executes the command as a child process, returning a handle to it.
     ///
     /// By default, stdin, stdout and stderr are inherited from the parent.
@@ -2111,6 +2164,42 @@ pub fn kill(&mut self) -> io::Result<()> {
         self.handle.kill()
     }
 
+    /// Forces the child and all other processes in its process group to exit.
+    ///
+    /// This only works for children spawned into their own process group, with
+    /// [`Command::new_process_group`] or, on Unix, `CommandExt::process_group`. For any other
+    /// child an [`InvalidInput`] error is returned.
+    ///
+    /// Unlike [`kill`](Self::kill), this still has an effect after the child itself has been
+    /// waited for, since processes it started may remain in the group. If no process is left in
+    /// the group, `Ok(())` is returned.
+    ///
+    /// This is equivalent to sending a SIGKILL to the process group on Unix platforms, and to
+    /// terminating the job object the child was assigned to on Windows.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_new_group)]
+    /// use std::process::Command;
+    ///
+    /// let mut child = Command::new("sh")
+    ///     .args(["-c", "sleep 100 & sleep 100"])
+    ///     .new_process_group(true)
+    ///     .spawn()
+    ///     .expect("sh failed to start");
+    ///
+    /// // Kills both `sleep` processes, not only the shell.
+    /// child.kill_process_group().expect("process group couldn't be killed");
+    /// child.wait().expect("sh wasn't running");
+    /// ```
+    ///
+    /// [`InvalidInput`]: io::ErrorKind::InvalidInput
+    #[unstable(feature = "process_new_group", issue = "none")]
+    pub fn kill_process_group(&mut self) -> io::Result<()> {
+        self.handle.kill_process_group()
+    }
+
     /// Returns the OS-assigned process identifier associated with this child.
     ///
     /// # Examples
@@ -2196,6 +2285,46 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         Ok(self.handle.try_wait()?.map(ExitStatus))
     }
 
+    /// Waits for the child to exit, giving up once `timeout` has elapsed.
+    ///
+    /// If the child exits within `timeout`, then `Ok(Some(status))` is returned, and on Unix
+    /// the process ID is reaped. Otherwise `Ok(None)` is returned and the child keeps running,
+    /// so it can be waited for again or [killed](Self::kill). A zero `timeout` makes this
+    /// equivalent to [`try_wait`](Self::try_wait).
+    ///
+    /// Note that unlike `wait`, this function will not attempt to drop stdin.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Linux, this waits on a pidfd for the child, which is opened just for this call if
+    /// the child was not spawned with one. On Windows, this waits on the process handle. On
+    /// other Unix platforms, and on Linux if no pidfd can be obtained, the child is polled at
+    /// increasing intervals of at most a few milliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(child_wait_timeout)]
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+    ///
+    /// match child.wait_timeout(Duration::from_secs(1)) {
+    ///     Ok(Some(status)) => println!("exited with: {status}"),
+    ///     Ok(None) => {
+    ///         println!("still running after a second, killing it");
+    ///         child.kill().unwrap();
+    ///         child.wait().unwrap();
+    ///     }
+    ///     Err(e) => println!("error attempting to wait: {e}"),
+    /// }
+    /// ```
+    #[unstable(feature = "child_wait_timeout", issue = "none")]
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        Ok(self.handle.wait_timeout(timeout)?.map(ExitStatus))
+    }
+
     /// Simultaneously waits for the child to exit and collect all remaining
     /// output on the stdout/stderr handles, returning an `Output`
     /// instance.
@@ -736,3 +736,73 @@ fn terminate_exited_process() {
     assert!(p.kill().is_ok());
     assert!(p.kill().is_ok());
 }
+
+#[test]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn wait_timeout() {
+    use crate::time::Duration;
+
+    // The child waits for a line on stdin, so it exits once we close it.
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(&["/C", "set /p a="]);
+        cmd
+    } else {
+        let mut cmd = shell_cmd();
+        cmd.args(&["-c", "read a"]);
+        cmd
+    };
+    let mut p = cmd.stdin(Stdio::piped()).spawn().unwrap();
+    assert!(p.wait_timeout(Duration::ZERO).unwrap().is_none());
+    assert!(p.wait_timeout(Duration::from_millis(10)).unwrap().is_none());
+
+    drop(p.stdin.take());
+    let status = p.wait_timeout(Duration::from_secs(60)).unwrap().expect("child didn't exit");
+    // The status is remembered once the child has been waited for.
+    assert_eq!(p.wait_timeout(Duration::ZERO).unwrap(), Some(status));
+    assert_eq!(p.wait().unwrap(), status);
+}
+
+#[test]
+fn kill_process_group_requires_new_group() {
+    let mut p = known_command().stdout(Stdio::null()).spawn().unwrap();
+    assert_eq!(p.kill_process_group().unwrap_err().kind(), ErrorKind::InvalidInput);
+    p.wait().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn kill_process_group() {
+    use crate::os::unix::process::ExitStatusExt;
+
+    // The background `sleep` holds on to the write end of the pipe, so reading stdout to the end
+    // only finishes once it has been killed along with the shell.
+    let mut p = shell_cmd()
+        .args(&["-c", "sleep 1000 & sleep 1000"])
+        .stdout(Stdio::piped())
+        .new_process_group(true)
+        .spawn()
+        .unwrap();
+    assert_eq!(unsafe { libc::getpgid(p.id() as libc::pid_t) }, p.id() as libc::pid_t);
+
+    p.kill_process_group().unwrap();
+    assert_eq!(p.wait().unwrap().signal(), Some(libc::SIGKILL));
+    let mut out = Vec::new();
+    p.stdout.take().unwrap().read_to_end(&mut out).unwrap();
+
+    // Nothing is left in the group to kill.
+    p.kill_process_group().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn signal_process_group() {
+    use crate::os::unix::process::{ChildExt, CommandExt, ExitStatusExt};
+
+    // A group set with `CommandExt::process_group` can be signalled too.
+    let mut p = shell_cmd().args(&["-c", "sleep 1000"]).process_group(0).spawn().unwrap();
+    p.signal_process_group(libc::SIGTERM).unwrap();
+    assert_eq!(p.wait().unwrap().signal(), Some(libc::SIGTERM));
+}
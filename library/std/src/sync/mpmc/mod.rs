@@ -40,6 +40,11 @@
 //!
 //! [`unwrap`]: Result::unwrap
 //!
+//! ## Selection
+//!
+//! A [`Select`] waits on several channel operations at once, across both `mpmc` and
+//! [`mpsc`](crate::sync::mpsc) channels, and completes whichever one becomes ready first.
+//!
 //! # Examples
 //!
 //! Simple usage:
@@ -147,7 +152,11 @@
 mod zero;
 
 pub use error::*;
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub use select::{Select, SelectReceiver, SelectSender, SelectedOperation};
 
+use self::context::Context;
+use self::select::{Operation, SelectHandle, Token};
 use crate::fmt;
 use crate::panic::{RefUnwindSafe, UnwindSafe};
 use crate::time::{Duration, Instant};
@@ -649,6 +658,67 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+impl<T> Sender<T> {
+    /// Writes a message into the channel after a successful selection.
+    ///
+    /// # Safety
+    /// `token` must have been initialized by selecting a send operation on this sender.
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        unsafe {
+            match &self.flavor {
+                SenderFlavor::Array(chan) => chan.write(token, msg),
+                SenderFlavor::List(chan) => chan.write(token, msg),
+                SenderFlavor::Zero(chan) => chan.write(token, msg),
+            }
+        }
+    }
+}
+
+impl<T> SelectHandle for Sender<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().try_select(token),
+            SenderFlavor::List(chan) => chan.sender().try_select(token),
+            SenderFlavor::Zero(chan) => chan.sender().try_select(token),
+        }
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::List(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Zero(chan) => chan.sender().register(oper, cx),
+        }
+    }
+
+    fn unregister(&self, oper: Operation) {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().unregister(oper),
+            SenderFlavor::List(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Zero(chan) => chan.sender().unregister(oper),
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::List(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Zero(chan) => chan.sender().accept(token, cx),
+        }
+    }
+}
+
+/// Allows extension traits within `std`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for Sender<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> SelectSender<T> for Sender<T> {
+    fn as_mpmc_sender(&self) -> &Sender<T> {
+        self
+    }
+}
+
 /// The receiving half of Rust's [`channel`] (or [`sync_channel`]) type.
 /// Different threads can share this [`Sender`] by cloning it.
 ///
@@ -1374,3 +1444,64 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.pad("Receiver { .. }")
     }
 }
+
+impl<T> Receiver<T> {
+    /// Reads a message from the channel after a successful selection.
+    ///
+    /// # Safety
+    /// `token` must have been initialized by selecting a receive operation on this receiver.
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        unsafe {
+            match &self.flavor {
+                ReceiverFlavor::Array(chan) => chan.read(token),
+                ReceiverFlavor::List(chan) => chan.read(token),
+                ReceiverFlavor::Zero(chan) => chan.read(token),
+            }
+        }
+    }
+}
+
+impl<T> SelectHandle for Receiver<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::List(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Zero(chan) => chan.receiver().try_select(token),
+        }
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::List(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Zero(chan) => chan.receiver().register(oper, cx),
+        }
+    }
+
+    fn unregister(&self, oper: Operation) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::List(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Zero(chan) => chan.receiver().unregister(oper),
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::List(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Zero(chan) => chan.receiver().accept(token, cx),
+        }
+    }
+}
+
+/// Allows extension traits within `std`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for Receiver<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> SelectReceiver<T> for Receiver<T> {
+    fn as_mpmc_receiver(&self) -> &Receiver<T> {
+        self
+    }
+}
@@ -1,3 +1,12 @@
+//! Interface to select over a set of channel operations.
+
+use super::context::Context;
+use super::error::*;
+use super::{Receiver, Sender, utils};
+use crate::marker::PhantomData;
+use crate::time::{Duration, Instant};
+use crate::{fmt, mem, thread};
+
 /// Temporary data that gets initialized during a blocking operation, and is consumed by
 /// `read` or `write`.
 ///
@@ -6,7 +15,6 @@
 pub struct Token {
     pub(crate) array: super::array::ArrayToken,
     pub(crate) list: super::list::ListToken,
-    #[allow(dead_code)]
     pub(crate) zero: super::zero::ZeroToken,
 }
 
@@ -69,3 +77,724 @@ fn into(self) -> usize {
         }
     }
 }
+
+/// A receiver or a sender that can participate in selection.
+pub(crate) trait SelectHandle {
+    /// Attempts to select an operation and returns `true` on success.
+    fn try_select(&self, token: &mut Token) -> bool;
+
+    /// Registers an operation for execution and returns `true` if it is now ready.
+    fn register(&self, oper: Operation, cx: &Context) -> bool;
+
+    /// Unregisters an operation for execution.
+    fn unregister(&self, oper: Operation);
+
+    /// Attempts to select an operation the thread got woken up for and returns `true` on success.
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool;
+}
+
+/// How long a select operation may block.
+#[derive(Debug, Clone, Copy)]
+enum Timeout {
+    /// No blocking.
+    Now,
+
+    /// Block forever.
+    Never,
+
+    /// Block until the specified instant.
+    At(Instant),
+}
+
+/// Runs until one of the operations is selected, potentially blocking the current thread.
+///
+/// Successful receive operations will have to be followed up by `read` and successful send
+/// operations by `write`.
+fn run_select(
+    handles: &mut [(&dyn SelectHandle, usize, *const u8)],
+    timeout: Timeout,
+) -> Option<(Token, usize, *const u8)> {
+    if handles.is_empty() {
+        // Wait until the timeout and return.
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => loop {
+                thread::park();
+            },
+            Timeout::At(when) => {
+                let now = Instant::now();
+                if now < when {
+                    thread::sleep(when - now);
+                }
+                return None;
+            }
+        }
+    }
+
+    // Shuffle the operations for fairness.
+    utils::shuffle(handles);
+
+    // Create a token, which serves as a temporary variable that gets initialized in this function
+    // and is later used by a call to `read` or `write` that completes the selected operation.
+    let mut token = Token::default();
+
+    // Try selecting one of the operations without blocking.
+    for &(handle, i, ptr) in handles.iter() {
+        if handle.try_select(&mut token) {
+            return Some((token, i, ptr));
+        }
+    }
+
+    loop {
+        // Prepare for blocking.
+        let res = Context::with(|cx| {
+            let mut sel = Selected::Waiting;
+            let mut registered_count = 0;
+            let mut index_ready = None;
+
+            if let Timeout::Now = timeout {
+                cx.try_select(Selected::Aborted).unwrap();
+            }
+
+            // Register all operations.
+            for (handle, i, _) in handles.iter_mut() {
+                registered_count += 1;
+
+                // If registration returns `true`, that means the operation has just become ready.
+                if handle.register(Operation::hook::<&dyn SelectHandle>(handle), cx) {
+                    // Try aborting select.
+                    sel = match cx.try_select(Selected::Aborted) {
+                        Ok(()) => {
+                            index_ready = Some(*i);
+                            Selected::Aborted
+                        }
+                        Err(s) => s,
+                    };
+                    break;
+                }
+
+                // If another thread has already selected one of the operations, stop registration.
+                sel = cx.selected();
+                if sel != Selected::Waiting {
+                    break;
+                }
+            }
+
+            if sel == Selected::Waiting {
+                let deadline = match timeout {
+                    Timeout::Now => unreachable!(),
+                    Timeout::Never => None,
+                    Timeout::At(when) => Some(when),
+                };
+
+                // Block the current thread.
+                // SAFETY: the context belongs to the current thread.
+                sel = unsafe { cx.wait_until(deadline) };
+            }
+
+            // Unregister all registered operations.
+            for (handle, _, _) in handles.iter_mut().take(registered_count) {
+                handle.unregister(Operation::hook::<&dyn SelectHandle>(handle));
+            }
+
+            match sel {
+                Selected::Waiting => unreachable!(),
+                Selected::Aborted => {
+                    // If an operation became ready during registration, try selecting it.
+                    if let Some(index_ready) = index_ready {
+                        for &(handle, i, ptr) in handles.iter() {
+                            if i == index_ready && handle.try_select(&mut token) {
+                                return Some((i, ptr));
+                            }
+                        }
+                    }
+                }
+                Selected::Disconnected => {}
+                Selected::Operation(_) => {
+                    // Find the selected operation.
+                    for (handle, i, ptr) in handles.iter_mut() {
+                        // Is this the selected operation?
+                        if sel == Selected::Operation(Operation::hook::<&dyn SelectHandle>(handle))
+                        {
+                            // Try selecting this operation.
+                            if handle.accept(&mut token, cx) {
+                                return Some((*i, *ptr));
+                            }
+                        }
+                    }
+                }
+            }
+
+            None
+        });
+
+        // Return if an operation was selected.
+        if let Some((i, ptr)) = res {
+            return Some((token, i, ptr));
+        }
+
+        // Try selecting one of the operations without blocking.
+        for &(handle, i, ptr) in handles.iter() {
+            if handle.try_select(&mut token) {
+                return Some((token, i, ptr));
+            }
+        }
+
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => {}
+            Timeout::At(when) => {
+                if Instant::now() >= when {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// The receiving half of a channel that can be added to a [`Select`].
+///
+/// This trait is sealed: it is implemented by the receivers of both [`mpmc`] and
+/// [`mpsc`] channels and cannot be implemented outside of the standard library.
+///
+/// [`mpmc`]: crate::sync::mpmc
+/// [`mpsc`]: crate::sync::mpsc
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub trait SelectReceiver<T>: crate::sealed::Sealed {
+    #[doc(hidden)]
+    fn as_mpmc_receiver(&self) -> &Receiver<T>;
+}
+
+/// The sending half of a channel that can be added to a [`Select`].
+///
+/// This trait is sealed: it is implemented by the senders of both [`mpmc`] and [`mpsc`]
+/// channels and cannot be implemented outside of the standard library.
+///
+/// [`mpmc`]: crate::sync::mpmc
+/// [`mpsc`]: crate::sync::mpsc
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub trait SelectSender<T>: crate::sealed::Sealed {
+    #[doc(hidden)]
+    fn as_mpmc_sender(&self) -> &Sender<T>;
+}
+
+/// Selects from a set of channel operations.
+///
+/// `Select` allows you to define a set of channel operations, wait until any one of them becomes
+/// ready, and finally execute it. If multiple operations are ready at the same time, a random one
+/// among them is selected.
+///
+/// An operation is considered to be ready if it doesn't have to block. Note that it is ready even
+/// when it will simply return an error because the channel is disconnected.
+///
+/// Both [`mpmc`] and [`mpsc`] channels can take part in the same selection.
+///
+/// Selection is done in two steps. First, [`select`] (or one of its non-blocking or timed
+/// variants) picks an operation that is ready and returns a [`SelectedOperation`]. Then the
+/// operation has to be completed by calling [`SelectedOperation::recv`] or
+/// [`SelectedOperation::send`] with the same channel endpoint that was used to register it.
+/// Dropping the [`SelectedOperation`] instead panics.
+///
+/// [`mpmc`]: crate::sync::mpmc
+/// [`mpsc`]: crate::sync::mpsc
+/// [`select`]: Select::select
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_channel, mpmc_select)]
+///
+/// use std::sync::mpmc::{self, Select};
+/// use std::thread;
+///
+/// let (tx1, rx1) = mpmc::channel();
+/// let (_tx2, rx2) = mpmc::channel::<i32>();
+///
+/// thread::spawn(move || tx1.send(10).unwrap());
+///
+/// let mut sel = Select::new();
+/// let oper1 = sel.recv(&rx1);
+/// let oper2 = sel.recv(&rx2);
+///
+/// // Only the first operation can ever become ready.
+/// let oper = sel.select();
+/// match oper.index() {
+///     i if i == oper1 => assert_eq!(oper.recv(&rx1), Ok(10)),
+///     i if i == oper2 => panic!("nothing is ever sent on the second channel"),
+///     _ => unreachable!(),
+/// }
+/// ```
+///
+/// Selecting over [`mpsc`] channels works the same way:
+///
+/// ```
+/// #![feature(mpmc_select)]
+///
+/// use std::sync::mpmc::Select;
+/// use std::sync::mpsc;
+///
+/// let (tx1, rx1) = mpsc::sync_channel::<i32>(0);
+/// let (tx2, rx2) = mpsc::channel();
+/// tx2.send("hello").unwrap();
+///
+/// let mut sel = Select::new();
+/// let send = sel.send(&tx1);
+/// let recv = sel.recv(&rx2);
+///
+/// // Nobody is receiving from `rx1`, so only the receive operation is ready.
+/// let oper = sel.try_select().unwrap();
+/// assert_eq!(oper.index(), recv);
+/// assert_eq!(oper.recv(&rx2), Ok("hello"));
+/// # let _ = (send, rx1);
+/// ```
+#[unstable(feature = "mpmc_select", issue = "none")]
+#[derive(Clone)]
+pub struct Select<'a> {
+    /// A list of senders and receivers participating in selection.
+    handles: Vec<(&'a dyn SelectHandle, usize, *const u8)>,
+
+    /// The next index to assign to an operation.
+    next_index: usize,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of channel operations for selection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_select)]
+    ///
+    /// use std::sync::mpmc::Select;
+    ///
+    /// let mut sel = Select::new();
+    ///
+    /// // There are no operations, so none of them can be ready.
+    /// assert!(sel.try_select().is_err());
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    #[must_use]
+    pub fn new() -> Select<'a> {
+        Select { handles: Vec::with_capacity(4), next_index: 0 }
+    }
+
+    /// Adds a send operation.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (tx, rx) = mpmc::channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.send(&tx);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), index);
+    /// oper.send(&tx, 1).unwrap();
+    /// assert_eq!(rx.recv(), Ok(1));
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn send<T: 'a>(&mut self, s: &'a impl SelectSender<T>) -> usize {
+        let s = s.as_mpmc_sender();
+        let i = self.next_index;
+        let ptr = s as *const Sender<T> as *const u8;
+        self.handles.push((s, i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Adds a receive operation.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (tx, rx) = mpmc::channel();
+    /// tx.send(1).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.recv(&rx);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), index);
+    /// assert_eq!(oper.recv(&rx), Ok(1));
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn recv<T: 'a>(&mut self, r: &'a impl SelectReceiver<T>) -> usize {
+        let r = r.as_mpmc_receiver();
+        let i = self.next_index;
+        let ptr = r as *const Receiver<T> as *const u8;
+        self.handles.push((r, i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Attempts to select one of the operations without blocking.
+    ///
+    /// If an operation is ready, it is selected and returned. If multiple operations are ready at
+    /// the same time, a random one among them is selected. If none of the operations are ready, an
+    /// error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (tx1, rx1) = mpmc::channel::<i32>();
+    /// let (tx2, rx2) = mpmc::channel();
+    /// tx2.send(2).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&rx1);
+    /// let oper2 = sel.recv(&rx2);
+    ///
+    /// // Only the second operation is ready.
+    /// let oper = sel.try_select().unwrap();
+    /// assert_eq!(oper.index(), oper2);
+    /// assert_eq!(oper.recv(&rx2), Ok(2));
+    ///
+    /// // Now neither operation is ready.
+    /// assert!(sel.try_select().is_err());
+    /// # let _ = (tx1, oper1);
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
+        match run_select(&mut self.handles, Timeout::Now) {
+            None => Err(TrySelectError),
+            Some((token, index, ptr)) => Ok(SelectedOperation::new(token, index, ptr)),
+        }
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it.
+    ///
+    /// Once an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no operations have been added to `Select`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    /// use std::thread;
+    ///
+    /// let (tx, rx1) = mpmc::channel::<i32>();
+    /// let (_, rx2) = mpmc::channel::<i32>();
+    ///
+    /// thread::spawn(move || tx.send(1).unwrap());
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&rx1);
+    /// let oper2 = sel.recv(&rx2);
+    ///
+    /// // The second channel is disconnected, so its operation is ready straight away unless
+    /// // the message on the first channel arrives first.
+    /// let oper = sel.select();
+    /// match oper.index() {
+    ///     i if i == oper1 => assert_eq!(oper.recv(&rx1), Ok(1)),
+    ///     i if i == oper2 => assert!(oper.recv(&rx2).is_err()),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        if self.handles.is_empty() {
+            panic!("no operations have been added to `Select`");
+        }
+
+        let (token, index, ptr) = run_select(&mut self.handles, Timeout::Never).unwrap();
+        SelectedOperation::new(token, index, ptr)
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready and selects it.
+    ///
+    /// If an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected. If none of the operations
+    /// become ready for the specified duration, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select, SelectTimeoutError};
+    /// use std::time::Duration;
+    ///
+    /// let (_tx, rx) = mpmc::channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&rx);
+    ///
+    /// // Nothing is ever sent, so the operation times out.
+    /// assert_eq!(
+    ///     sel.select_timeout(Duration::from_millis(100)).map(|oper| oper.index()),
+    ///     Err(SelectTimeoutError)
+    /// );
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.select_deadline(deadline),
+            // So far in the future that it's practically the same as waiting indefinitely.
+            None => Ok(self.select()),
+        }
+    }
+
+    /// Blocks until a given deadline, or until one of the operations becomes ready and selects it.
+    ///
+    /// If an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected. If none of the operations
+    /// become ready before the given deadline, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (tx, rx) = mpmc::channel();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     tx.send(1).unwrap();
+    /// });
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.recv(&rx);
+    ///
+    /// let oper = sel.select_deadline(Instant::now() + Duration::from_secs(10)).unwrap();
+    /// assert_eq!(oper.index(), index);
+    /// assert_eq!(oper.recv(&rx), Ok(1));
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn select_deadline(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        match run_select(&mut self.handles, Timeout::At(deadline)) {
+            None => Err(SelectTimeoutError),
+            Some((token, index, ptr)) => Ok(SelectedOperation::new(token, index, ptr)),
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<'a> Default for Select<'a> {
+    fn default() -> Select<'a> {
+        Select::new()
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Select { .. }")
+    }
+}
+
+/// A selected operation that needs to be completed.
+///
+/// To complete the operation, call [`send`] or [`recv`].
+///
+/// # Panics
+///
+/// Dropping a `SelectedOperation` without completing it panics, unless the thread is already
+/// panicking. This includes returning early, e.g. with `?`, between selecting the operation and
+/// completing it.
+///
+/// Once an operation is selected, the other side of the channel may be waiting for it to be
+/// completed, like a sender blocked on a zero-capacity channel until its message is received.
+/// Leaving the operation incomplete could deadlock that thread, so it is treated as a bug.
+///
+/// [`send`]: SelectedOperation::send
+/// [`recv`]: SelectedOperation::recv
+#[unstable(feature = "mpmc_select", issue = "none")]
+#[must_use = "the selected operation must be completed with `send` or `recv`"]
+pub struct SelectedOperation<'a> {
+    /// Token needed to complete the operation.
+    token: Token,
+
+    /// The index of the selected operation.
+    index: usize,
+
+    /// The address of the selected `Sender` or `Receiver`.
+    ptr: *const u8,
+
+    /// Indicates that `Sender`s and `Receiver`s are borrowed.
+    _marker: PhantomData<&'a ()>,
+}
+
+impl SelectedOperation<'_> {
+    fn new(token: Token, index: usize, ptr: *const u8) -> Self {
+        SelectedOperation { token, index, ptr, _marker: PhantomData }
+    }
+
+    /// Returns the index of the selected operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select};
+    ///
+    /// let (tx1, rx1) = mpmc::channel::<i32>();
+    /// let (tx2, rx2) = mpmc::channel::<i32>();
+    /// tx1.send(1).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&rx1);
+    /// let oper2 = sel.recv(&rx2);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_ne!(oper.index(), oper2);
+    /// oper.recv(&rx1).unwrap();
+    /// # drop(tx2);
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the send operation.
+    ///
+    /// The passed [`Sender`] reference must be the same one that was used in
+    /// [`Select::send`] when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Sender`] reference is passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, Select, SendError};
+    ///
+    /// let (tx, rx) = mpmc::channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.send(&tx);
+    ///
+    /// drop(rx);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.send(&tx, 10), Err(SendError(10)));
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn send<T>(mut self, s: &impl SelectSender<T>, msg: T) -> Result<(), SendError<T>> {
+        let s = s.as_mpmc_sender();
+        assert!(
+            s as *const Sender<T> as *const u8 == self.ptr,
+            "passed a sender that wasn't selected",
+        );
+        let res = unsafe { s.write(&mut self.token, msg) };
+        mem::forget(self);
+        res.map_err(SendError)
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// The passed [`Receiver`] reference must be the same one that was used in
+    /// [`Select::recv`] when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Receiver`] reference is passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel, mpmc_select)]
+    ///
+    /// use std::sync::mpmc::{self, RecvError, Select};
+    ///
+    /// let (tx, rx) = mpmc::channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&rx);
+    ///
+    /// drop(tx);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.recv(&rx), Err(RecvError));
+    /// ```
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn recv<T>(mut self, r: &impl SelectReceiver<T>) -> Result<T, RecvError> {
+        let r = r.as_mpmc_receiver();
+        assert!(
+            r as *const Receiver<T> as *const u8 == self.ptr,
+            "passed a receiver that wasn't selected",
+        );
+        let res = unsafe { r.read(&mut self.token) };
+        mem::forget(self);
+        res.map_err(|_| RecvError)
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl fmt::Debug for SelectedOperation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SelectedOperation { .. }")
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl Drop for SelectedOperation<'_> {
+    fn drop(&mut self) {
+        // Don't turn a panic while completing the operation into an abort.
+        if !thread::panicking() {
+            panic!("dropped `SelectedOperation` without completing the operation");
+        }
+    }
+}
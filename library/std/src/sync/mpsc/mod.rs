@@ -634,6 +634,17 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Allows extension traits within `std`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for Sender<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> mpmc::SelectSender<T> for Sender<T> {
+    fn as_mpmc_sender(&self) -> &mpmc::Sender<T> {
+        &self.inner
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // SyncSender
 ////////////////////////////////////////////////////////////////////////////////
@@ -758,6 +769,17 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Allows extension traits within `std`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for SyncSender<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> mpmc::SelectSender<T> for SyncSender<T> {
+    fn as_mpmc_sender(&self) -> &mpmc::Sender<T> {
+        &self.inner
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Receiver
 ////////////////////////////////////////////////////////////////////////////////
@@ -1094,6 +1116,17 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Allows extension traits within `std`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for Receiver<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> mpmc::SelectReceiver<T> for Receiver<T> {
+    fn as_mpmc_receiver(&self) -> &mpmc::Receiver<T> {
+        &self.inner
+    }
+}
+
 #[stable(feature = "rust1", since = "1.0.0")]
 impl<T> fmt::Debug for SendError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
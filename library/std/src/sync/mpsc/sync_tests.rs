@@ -667,3 +667,84 @@ fn drop_unreceived() {
     assert!(weak.upgrade().is_none());
     drop(tx);
 }
+
+#[test]
+fn select_send_and_recv() {
+    let (tx1, rx1) = sync_channel::<i32>(1);
+    let (tx2, rx2) = sync_channel::<i32>(1);
+    tx1.send(1).unwrap();
+
+    let mut sel = mpmc::Select::new();
+    let oper1 = sel.send(&tx1);
+    let oper2 = sel.send(&tx2);
+    // The first channel is full.
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    oper.send(&tx2, 2).unwrap();
+    assert!(sel.try_select().is_err());
+
+    assert_eq!(rx1.recv(), Ok(1));
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    oper.send(&tx1, 3).unwrap();
+    assert_eq!(rx1.recv(), Ok(3));
+    assert_eq!(rx2.recv(), Ok(2));
+}
+
+#[test]
+fn select_rendezvous_recv() {
+    let (tx, rx) = sync_channel::<i32>(0);
+    let (_tx2, rx2) = sync_channel::<i32>(0);
+    let t = thread::spawn(move || {
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+    });
+
+    let mut sel = mpmc::Select::new();
+    let oper1 = sel.recv(&rx);
+    sel.recv(&rx2);
+    for i in 0..10 {
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(&rx), Ok(i));
+    }
+    t.join().unwrap();
+}
+
+#[test]
+fn select_rendezvous_send() {
+    let (tx, rx) = sync_channel::<i32>(0);
+    let (tx2, _rx2) = sync_channel::<i32>(0);
+    let t = thread::spawn(move || (0..10).map(|_| rx.recv().unwrap()).collect::<Vec<_>>());
+
+    let mut sel = mpmc::Select::new();
+    let oper1 = sel.send(&tx);
+    sel.send(&tx2);
+    for i in 0..10 {
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        oper.send(&tx, i).unwrap();
+    }
+    assert_eq!(t.join().unwrap(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn select_rendezvous_both_sides() {
+    let (tx, rx) = sync_channel::<i32>(0);
+    let stress = stress_factor() + 100;
+    let t = thread::spawn(move || {
+        let mut sel = mpmc::Select::new();
+        sel.send(&tx);
+        for i in 0..stress {
+            sel.select().send(&tx, i as i32).unwrap();
+        }
+    });
+
+    let mut sel = mpmc::Select::new();
+    sel.recv(&rx);
+    for i in 0..stress {
+        assert_eq!(sel.select().recv(&rx), Ok(i as i32));
+    }
+    t.join().unwrap();
+}
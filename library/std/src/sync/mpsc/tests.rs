@@ -719,3 +719,130 @@ fn issue_39364() {
     let _tx = t.join().unwrap(); // delay dropping until end of test
     let _ = rx.recv_timeout(Duration::from_millis(500));
 }
+
+#[test]
+fn select_smoke() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<i32>();
+    tx2.send(2).unwrap();
+
+    let mut sel = mpmc::Select::new();
+    let oper1 = sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&rx2), Ok(2));
+    assert!(sel.try_select().is_err());
+
+    tx1.send(1).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx1), Ok(1));
+}
+
+#[test]
+fn select_disconnected() {
+    let (tx, rx) = channel::<i32>();
+    let (_tx2, rx2) = channel::<i32>();
+    drop(tx);
+
+    let mut sel = mpmc::Select::new();
+    let oper1 = sel.recv(&rx);
+    sel.recv(&rx2);
+    let oper = sel.select_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx), Err(RecvError));
+}
+
+#[test]
+fn select_timeout() {
+    let (_tx, rx) = channel::<i32>();
+
+    let mut sel = mpmc::Select::new();
+    sel.recv(&rx);
+    let start = Instant::now();
+    assert_eq!(
+        sel.select_timeout(Duration::from_millis(100)).map(|oper| oper.index()),
+        Err(mpmc::SelectTimeoutError)
+    );
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[test]
+fn select_wakes_up() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<i32>();
+    let t = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        tx2.send(2).unwrap();
+        tx1
+    });
+
+    let mut sel = mpmc::Select::new();
+    sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&rx2), Ok(2));
+    let _tx1 = t.join().unwrap();
+}
+
+#[test]
+fn select_mixed_channels() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = mpmc::channel::<i32>();
+    let stress = stress_factor() + 100;
+
+    let t = thread::spawn(move || {
+        for i in 0..stress {
+            if i % 2 == 0 {
+                tx1.send(i as i32).unwrap();
+            } else {
+                tx2.send(i as i32).unwrap();
+            }
+        }
+        // Keep both channels connected until everything has been received.
+        (tx1, tx2)
+    });
+
+    let mut received = Vec::new();
+    let mut sel = mpmc::Select::new();
+    let oper1 = sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    while received.len() < stress {
+        let oper = sel.select();
+        let msg = match oper.index() {
+            i if i == oper1 => oper.recv(&rx1),
+            i if i == oper2 => oper.recv(&rx2),
+            _ => unreachable!(),
+        };
+        received.push(msg.unwrap());
+    }
+    let _senders = t.join().unwrap();
+
+    received.sort();
+    assert_eq!(received, (0..stress as i32).collect::<Vec<_>>());
+}
+
+#[test]
+#[should_panic(expected = "passed a receiver that wasn't selected")]
+fn select_wrong_receiver() {
+    let (tx, rx1) = channel::<i32>();
+    let (_tx2, rx2) = channel::<i32>();
+    tx.send(1).unwrap();
+
+    let mut sel = mpmc::Select::new();
+    sel.recv(&rx1);
+    let _ = sel.select().recv(&rx2);
+}
+
+#[test]
+#[should_panic(expected = "dropped `SelectedOperation` without completing the operation")]
+fn select_dropped_without_completing() {
+    let (tx, rx) = channel::<i32>();
+    tx.send(1).unwrap();
+
+    let mut sel = mpmc::Select::new();
+    sel.recv(&rx);
+    drop(sel.select());
+}
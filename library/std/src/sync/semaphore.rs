@@ -0,0 +1,288 @@
+#[cfg(test)]
+mod tests;
+
+use crate::fmt;
+use crate::sys::sync as sys;
+use crate::time::Duration;
+
+/// A counting semaphore, which limits how many threads can use a resource at the same time.
+///
+/// A semaphore holds a number of permits. Threads take permits with [`acquire`] and the
+/// related methods, blocking until enough permits are available, and give them back when the
+/// returned [`SemaphorePermit`] is dropped.
+///
+/// Unlike a [`Mutex`], a semaphore does not protect any data by itself, and the thread which
+/// releases permits does not need to be the one that acquired them, see
+/// [`SemaphorePermit::forget`] and [`Semaphore::add_permits`].
+///
+/// Waiting threads are not served in any particular order. In particular, a thread waiting
+/// for many permits at once can be overtaken by threads that ask for fewer.
+///
+/// [`acquire`]: Semaphore::acquire
+/// [`Mutex`]: crate::sync::Mutex
+///
+/// # Examples
+///
+/// Limiting the number of parallel downloads to three:
+///
+/// ```
+/// #![feature(semaphore)]
+/// use std::sync::Semaphore;
+/// use std::thread;
+///
+/// static DOWNLOADS: Semaphore = Semaphore::new(3);
+///
+/// thread::scope(|s| {
+///     for i in 0..10 {
+///         s.spawn(move || {
+///             let _permit = DOWNLOADS.acquire();
+///             // At most three threads get here at the same time.
+///             println!("downloading file {i}");
+///         });
+///     }
+/// });
+/// ```
+#[unstable(feature = "semaphore", issue = "none")]
+pub struct Semaphore {
+    inner: sys::Semaphore,
+}
+
+/// An RAII guard for permits acquired from a [`Semaphore`].
+///
+/// The permits are returned to the semaphore when this structure is dropped, unless it is
+/// [forgotten](SemaphorePermit::forget).
+///
+/// This structure is created by [`Semaphore::acquire`] and the related methods.
+#[must_use = "if unused the permits will immediately be released"]
+#[clippy::has_significant_drop]
+#[unstable(feature = "semaphore", issue = "none")]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    count: usize,
+}
+
+impl Semaphore {
+    /// The largest number of permits a semaphore can hold.
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub const MAX_PERMITS: usize = sys::Semaphore::MAX_PERMITS;
+
+    /// Creates a new semaphore with the given number of permits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permits` exceeds [`Semaphore::MAX_PERMITS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(4);
+    /// assert_eq!(semaphore.available_permits(), 4);
+    /// ```
+    #[unstable(feature = "semaphore", issue = "none")]
+    #[must_use]
+    #[inline]
+    pub const fn new(permits: usize) -> Semaphore {
+        Semaphore { inner: sys::Semaphore::new(permits) }
+    }
+
+    /// Acquires a permit, blocking the current thread until one is available.
+    ///
+    /// The permit is released when the returned guard is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(1);
+    /// let permit = semaphore.acquire();
+    /// assert_eq!(semaphore.available_permits(), 0);
+    /// drop(permit);
+    /// assert_eq!(semaphore.available_permits(), 1);
+    /// ```
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn acquire(&self) -> SemaphorePermit<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Acquires `n` permits at once, blocking the current thread until they are all available.
+    ///
+    /// No permits are taken until all of them can be taken together, so two threads that
+    /// both acquire several permits cannot deadlock by each holding some of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds [`Semaphore::MAX_PERMITS`], as the call could never return.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(5);
+    /// let permit = semaphore.acquire_many(3);
+    /// assert_eq!(permit.count(), 3);
+    /// assert_eq!(semaphore.available_permits(), 2);
+    /// ```
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn acquire_many(&self, n: usize) -> SemaphorePermit<'_> {
+        assert!(n <= Self::MAX_PERMITS, "cannot acquire more than `Semaphore::MAX_PERMITS`");
+        self.inner.acquire(n);
+        SemaphorePermit { semaphore: self, count: n }
+    }
+
+    /// Attempts to acquire a permit without blocking.
+    ///
+    /// Returns `None` if no permit is available right now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(1);
+    /// let permit = semaphore.try_acquire().unwrap();
+    /// assert!(semaphore.try_acquire().is_none());
+    /// drop(permit);
+    /// assert!(semaphore.try_acquire().is_some());
+    /// ```
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    /// Attempts to acquire `n` permits at once without blocking.
+    ///
+    /// Returns `None`, without taking any permits, if fewer than `n` are available right now.
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn try_acquire_many(&self, n: usize) -> Option<SemaphorePermit<'_>> {
+        self.inner.try_acquire(n).then(|| SemaphorePermit { semaphore: self, count: n })
+    }
+
+    /// Acquires a permit, blocking the current thread for at most `timeout`.
+    ///
+    /// Returns `None` if no permit became available within the timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(semaphore)]
+    /// use std::sync::Semaphore;
+    /// use std::time::Duration;
+    ///
+    /// let semaphore = Semaphore::new(0);
+    /// assert!(semaphore.acquire_timeout(Duration::from_millis(10)).is_none());
+    /// ```
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn acquire_timeout(&self, timeout: Duration) -> Option<SemaphorePermit<'_>> {
+        self.acquire_many_timeout(1, timeout)
+    }
+
+    /// Acquires `n` permits at once, blocking the current thread for at most `timeout`.
+    ///
+    /// Returns `None`, without taking any permits, if they did not all become available
+    /// within the timeout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds [`Semaphore::MAX_PERMITS`], as the permits could never be acquired.
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn acquire_many_timeout(&self, n: usize, timeout: Duration) -> Option<SemaphorePermit<'_>> {
+        assert!(n <= Self::MAX_PERMITS, "cannot acquire more than `Semaphore::MAX_PERMITS`");
+        self.inner
+            .acquire_timeout(n, timeout)
+            .then(|| SemaphorePermit { semaphore: self, count: n })
+    }
+
+    /// Adds `n` permits to the semaphore, waking up threads that are waiting for them.
+    ///
+    /// This can be used to give back permits that were [forgotten](SemaphorePermit::forget),
+    /// or to raise the limit of the semaphore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of available permits would exceed [`Semaphore::MAX_PERMITS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(0);
+    /// semaphore.add_permits(2);
+    /// assert_eq!(semaphore.available_permits(), 2);
+    /// ```
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn add_permits(&self, n: usize) {
+        self.inner.release(n);
+    }
+
+    /// Returns the number of permits that are currently available.
+    ///
+    /// Other threads can acquire or release permits at any time, so the returned value may be
+    /// out of date by the time it is used.
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn available_permits(&self) -> usize {
+        self.inner.available()
+    }
+}
+
+#[unstable(feature = "semaphore", issue = "none")]
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore").field("available_permits", &self.available_permits()).finish()
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Returns the number of permits held by this guard.
+    #[unstable(feature = "semaphore", issue = "none")]
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Consumes the guard without releasing its permits.
+    ///
+    /// The permits are permanently removed from the semaphore, unless they are given back
+    /// later with [`Semaphore::add_permits`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(2);
+    /// semaphore.acquire().forget();
+    /// assert_eq!(semaphore.available_permits(), 1);
+    /// ```
+    #[unstable(feature = "semaphore", issue = "none")]
+    pub fn forget(mut self) {
+        self.count = 0;
+    }
+}
+
+#[unstable(feature = "semaphore", issue = "none")]
+impl Drop for SemaphorePermit<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.count > 0 {
+            self.semaphore.inner.release(self.count);
+        }
+    }
+}
+
+#[unstable(feature = "semaphore", issue = "none")]
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit").field("count", &self.count).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,137 @@
+use crate::sync::atomic::AtomicUsize;
+use crate::sync::atomic::Ordering::SeqCst;
+use crate::sync::mpsc::channel;
+use crate::sync::{Arc, Semaphore};
+use crate::thread;
+use crate::time::{Duration, Instant};
+
+#[test]
+fn smoke() {
+    let s = Semaphore::new(2);
+    let a = s.acquire();
+    let b = s.try_acquire().unwrap();
+    assert!(s.try_acquire().is_none());
+    assert_eq!(s.available_permits(), 0);
+    drop(a);
+    assert_eq!(s.available_permits(), 1);
+    drop(b);
+    assert_eq!(s.available_permits(), 2);
+}
+
+#[test]
+fn acquire_many() {
+    let s = Semaphore::new(5);
+    let p = s.acquire_many(3);
+    assert_eq!(p.count(), 3);
+    assert!(s.try_acquire_many(3).is_none());
+    assert_eq!(s.available_permits(), 2);
+    let q = s.try_acquire_many(2).unwrap();
+    drop(p);
+    assert_eq!(s.available_permits(), 3);
+    drop(q);
+    assert_eq!(s.available_permits(), 5);
+
+    let empty = s.acquire_many(0);
+    assert_eq!(empty.count(), 0);
+    drop(empty);
+    assert_eq!(s.available_permits(), 5);
+}
+
+#[test]
+fn forget_and_add_permits() {
+    let s = Semaphore::new(1);
+    s.acquire().forget();
+    assert!(s.try_acquire().is_none());
+    s.add_permits(2);
+    assert_eq!(s.available_permits(), 2);
+}
+
+#[test]
+#[should_panic = "semaphore permit count overflowed"]
+fn add_permits_overflow() {
+    let s = Semaphore::new(Semaphore::MAX_PERMITS);
+    s.add_permits(1);
+}
+
+#[test]
+#[should_panic]
+fn new_too_many() {
+    let _ = Semaphore::new(Semaphore::MAX_PERMITS + 1);
+}
+
+#[test]
+#[should_panic = "cannot acquire more than `Semaphore::MAX_PERMITS`"]
+fn acquire_many_timeout_too_many() {
+    let s = Semaphore::new(0);
+    let _ = s.acquire_many_timeout(Semaphore::MAX_PERMITS + 1, Duration::from_millis(1));
+}
+
+#[test]
+#[cfg_attr(any(target_os = "emscripten", target_os = "wasi"), ignore)] // no threads
+fn acquire_timeout() {
+    let s = Semaphore::new(1);
+    let _p = s.acquire();
+    let start = Instant::now();
+    assert!(s.acquire_timeout(Duration::from_millis(20)).is_none());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert!(s.acquire_many_timeout(0, Duration::ZERO).is_some());
+
+    let s = Arc::new(Semaphore::new(0));
+    let s2 = s.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        s2.add_permits(1);
+    });
+    assert!(s.acquire_timeout(Duration::from_secs(60)).is_some());
+    handle.join().unwrap();
+}
+
+#[test]
+#[cfg_attr(any(target_os = "emscripten", target_os = "wasi"), ignore)] // no threads
+fn limits_concurrency() {
+    const N: usize = 3;
+    const THREADS: usize = 10;
+
+    let s = Arc::new(Semaphore::new(N));
+    let active = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = channel();
+    for _ in 0..THREADS {
+        let (s, active, tx) = (s.clone(), active.clone(), tx.clone());
+        thread::spawn(move || {
+            for _ in 0..100 {
+                let _p = s.acquire();
+                let now = active.fetch_add(1, SeqCst) + 1;
+                assert!(now <= N);
+                thread::yield_now();
+                active.fetch_sub(1, SeqCst);
+            }
+            tx.send(()).unwrap();
+        });
+    }
+    drop(tx);
+    for _ in 0..THREADS {
+        rx.recv().unwrap();
+    }
+    assert_eq!(s.available_permits(), N);
+}
+
+#[test]
+#[cfg_attr(any(target_os = "emscripten", target_os = "wasi"), ignore)] // no threads
+fn waiters_for_different_counts() {
+    // A release that is too small for one waiter must still wake up the others.
+    let s = Arc::new(Semaphore::new(0));
+    let (tx, rx) = channel();
+    for n in [1, 3] {
+        let (s, tx) = (s.clone(), tx.clone());
+        thread::spawn(move || {
+            s.acquire_many(n).forget();
+            tx.send(n).unwrap();
+        });
+    }
+    thread::sleep(Duration::from_millis(10));
+    s.add_permits(1);
+    assert_eq!(rx.recv().unwrap(), 1);
+    s.add_permits(3);
+    assert_eq!(rx.recv().unwrap(), 3);
+    assert_eq!(s.available_permits(), 0);
+}
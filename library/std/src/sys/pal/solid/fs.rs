@@ -9,7 +9,7 @@
 use crate::sync::Arc;
 use crate::sys::time::SystemTime;
 use crate::sys::unsupported;
-pub use crate::sys_common::fs::exists;
+pub use crate::sys_common::fs::{Dir, exists};
 use crate::sys_common::ignore_notfound;
 
 /// A file descriptor.
@@ -609,3 +609,5 @@ pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
 
     io::copy(&mut reader, &mut writer)
 }
+
+pub use self::copy as copy_reflink;
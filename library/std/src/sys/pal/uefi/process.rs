@@ -9,6 +9,7 @@
 use crate::sys::pipe::AnonPipe;
 use crate::sys::unsupported;
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
+use crate::time::Duration;
 use crate::{fmt, io};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -67,6 +68,8 @@ pub fn stderr(&mut self, stderr: Stdio) {
         self.stderr = Some(stderr);
     }
 
+    pub fn new_process_group(&mut self, _new_group: bool) {}
+
     pub fn get_program(&self) -> &OsStr {
         self.prog.as_ref()
     }
@@ -267,6 +270,14 @@ pub fn wait(&mut self) -> io::Result<ExitStatus> {
     pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         self.0
     }
+
+    pub fn wait_timeout(&mut self, _timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        self.0
+    }
+
+    pub fn kill_process_group(&mut self) -> io::Result<()> {
+        self.0
+    }
 }
 
 pub struct CommandArgs<'a> {
@@ -246,7 +246,7 @@ pub struct FileAttr {
 
 // all DirEntry's will have a reference to this struct
 struct InnerReadDir {
-    dirp: Dir,
+    dirp: DirStream,
     root: PathBuf,
 }
 
@@ -261,10 +261,10 @@ fn new(inner: InnerReadDir) -> Self {
     }
 }
 
-struct Dir(*mut libc::DIR);
+struct DirStream(*mut libc::DIR);
 
-unsafe impl Send for Dir {}
-unsafe impl Sync for Dir {}
+unsafe impl Send for DirStream {}
+unsafe impl Sync for DirStream {}
 
 #[cfg(any(
     target_os = "android",
@@ -854,7 +854,7 @@ pub(crate) fn debug_assert_fd_is_open(fd: RawFd) {
     }
 }
 
-impl Drop for Dir {
+impl Drop for DirStream {
     fn drop(&mut self) {
         // dirfd isn't supported everywhere
         #[cfg(not(any(
@@ -1793,7 +1793,7 @@ pub fn readdir(path: &Path) -> io::Result<ReadDir> {
         Err(Error::last_os_error())
     } else {
         let root = path.to_path_buf();
-        let inner = InnerReadDir { dirp: Dir(ptr), root };
+        let inner = InnerReadDir { dirp: DirStream(ptr), root };
         Ok(ReadDir::new(inner))
     }
 }
@@ -1877,6 +1877,62 @@ pub fn link(original: &Path, link: &Path) -> io::Result<()> {
     })
 }
 
+/// Opens an unnamed regular file in `dir` with `O_TMPFILE`.
+///
+/// Returns `Ok(None)` if the kernel or the filesystem of `dir` does not support `O_TMPFILE`.
+#[cfg(target_os = "linux")]
+pub fn open_tmpfile(dir: &Path) -> io::Result<Option<File>> {
+    let mut opts = OpenOptions::new();
+    opts.read(true);
+    opts.write(true);
+    opts.custom_flags(libc::O_TMPFILE);
+    opts.mode(0o600);
+    match File::open(dir, &opts) {
+        Ok(file) => Ok(Some(file)),
+        Err(e) => match e.raw_os_error() {
+            // Kernels before 3.11 ignore `__O_TMPFILE` and fail because `O_TMPFILE` includes
+            // `O_DIRECTORY`, and filesystems without support report `EOPNOTSUPP`.
+            Some(libc::EISDIR | libc::EOPNOTSUPP | libc::EINVAL) => Ok(None),
+            _ => Err(e),
+        },
+    }
+}
+
+/// Gives a file opened with `open_tmpfile` a name, failing if `link` already exists.
+#[cfg(target_os = "linux")]
+pub fn link_tmpfile(file: &File, link: &Path) -> io::Result<()> {
+    run_path_with_cstr(link, &|link| {
+        let linked = cvt(unsafe {
+            libc::linkat(
+                file.as_raw_fd(),
+                c"".as_ptr(),
+                libc::AT_FDCWD,
+                link.as_ptr(),
+                libc::AT_EMPTY_PATH,
+            )
+        });
+        match linked {
+            // Kernels before 6.10 only allow `AT_EMPTY_PATH` with `CAP_DAC_READ_SEARCH`, and
+            // report `ENOENT` otherwise. Linking through `/proc` works without it.
+            Err(e) if matches!(e.raw_os_error(), Some(libc::ENOENT | libc::EPERM)) => {}
+            linked => return linked.map(drop),
+        }
+        let original = format!("/proc/self/fd/{}", file.as_raw_fd());
+        run_path_with_cstr(Path::new(&original), &|original| {
+            cvt(unsafe {
+                libc::linkat(
+                    libc::AT_FDCWD,
+                    original.as_ptr(),
+                    libc::AT_FDCWD,
+                    link.as_ptr(),
+                    libc::AT_SYMLINK_FOLLOW,
+                )
+            })?;
+            Ok(())
+        })
+    })
+}
+
 pub fn stat(p: &Path) -> io::Result<FileAttr> {
     run_path_with_cstr(p, &|p| {
         cfg_has_statx! {
@@ -1988,11 +2044,64 @@ pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
     io::copy(&mut reader, &mut writer)
 }
 
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub use self::copy as copy_reflink;
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
+    copy_impl(from, to, false)
+}
+
+/// Like `copy`, but first tries to share the data blocks of `from` with `to` using `FICLONE`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn copy_reflink(from: &Path, to: &Path) -> io::Result<u64> {
+    copy_impl(from, to, true)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn copy_impl(from: &Path, to: &Path, reflink: bool) -> io::Result<u64> {
+    // `_IOW(0x94, 9, int)`, which is not exposed by every libc version we support.
+    #[cfg(any(
+        target_arch = "mips",
+        target_arch = "mips32r6",
+        target_arch = "mips64",
+        target_arch = "mips64r6",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc",
+        target_arch = "sparc64",
+    ))]
+    const FICLONE: u32 = 0x80049409;
+    #[cfg(not(any(
+        target_arch = "mips",
+        target_arch = "mips32r6",
+        target_arch = "mips64",
+        target_arch = "mips64r6",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc",
+        target_arch = "sparc64",
+    )))]
+    const FICLONE: u32 = 0x40049409;
+
     let (mut reader, reader_metadata) = open_from(from)?;
     let max_len = u64::MAX;
-    let (mut writer, _) = open_to_and_set_permissions(to, reader_metadata)?;
+    let (mut writer, writer_metadata) = open_to_and_set_permissions(to, reader_metadata)?;
+
+    if reflink && writer_metadata.is_file() {
+        match cvt(unsafe { libc::ioctl(writer.as_raw_fd(), FICLONE as _, reader.as_raw_fd()) }) {
+            // The clone replaces the whole contents of `to`, so its new length is the number of
+            // bytes copied, even if `from` was modified in the meantime.
+            Ok(_) => return writer.metadata().map(|m| m.len()),
+            Err(e) => match e.raw_os_error() {
+                // The filesystem does not support cloning, the files are on different
+                // filesystems, or `from` cannot be cloned, e.g. because it is a swap file.
+                // The regular copy below handles all of these.
+                Some(libc::EOPNOTSUPP | libc::ENOTTY | libc::EXDEV | libc::EINVAL) => (),
+                _ => return Err(e),
+            },
+        }
+    }
 
     use super::kernel_copy::{CopyResult, copy_regular_files};
 
@@ -2104,6 +2213,7 @@ pub fn chroot(dir: &Path) -> io::Result<()> {
     Err(io::const_error!(io::ErrorKind::Unsupported, "chroot not supported by vxworks"))
 }
 
+pub use dir_impl::Dir;
 pub use remove_dir_impl::remove_dir_all;
 
 // Fallback for REDOX, ESP-ID, Horizon, Vita, Vxworks and Miri
@@ -2136,7 +2246,7 @@ mod remove_dir_impl {
     #[cfg(all(target_os = "linux", target_env = "gnu"))]
     use libc::{fdopendir, openat64 as openat, unlinkat};
 
-    use super::{Dir, DirEntry, InnerReadDir, ReadDir, lstat};
+    use super::{DirEntry, DirStream, InnerReadDir, ReadDir, lstat};
     use crate::ffi::CStr;
     use crate::io;
     use crate::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
@@ -2162,7 +2272,7 @@ fn fdreaddir(dir_fd: OwnedFd) -> io::Result<(ReadDir, RawFd)> {
         if ptr.is_null() {
             return Err(io::Error::last_os_error());
         }
-        let dirp = Dir(ptr);
+        let dirp = DirStream(ptr);
         // file descriptor is automatically closed by libc::closedir() now, so give up ownership
         let new_parent_fd = dir_fd.into_raw_fd();
         // a valid root is not needed because we do not call any functions involving the full path
@@ -2279,3 +2389,258 @@ pub fn remove_dir_all(p: &Path) -> io::Result<()> {
         remove_dir_all_modern(p)
     }
 }
+
+// Fallback for the same targets as `remove_dir_impl`, which lack some of the `*at` functions
+#[cfg(any(
+    target_os = "redox",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita",
+    target_os = "nto",
+    target_os = "vxworks",
+    miri
+))]
+mod dir_impl {
+    pub use crate::sys_common::fs::Dir;
+}
+
+// Directory handles using openat(), mkdirat(), unlinkat(), renameat() and fstatat()
+#[cfg(not(any(
+    target_os = "redox",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita",
+    target_os = "nto",
+    target_os = "vxworks",
+    miri
+)))]
+mod dir_impl {
+    #[cfg(not(any(
+        all(target_os = "linux", not(target_env = "musl")),
+        target_os = "emscripten",
+        target_os = "hurd"
+    )))]
+    use libc::fstatat as fstatat64;
+    #[cfg(any(
+        all(target_os = "linux", not(target_env = "musl")),
+        target_os = "emscripten",
+        target_os = "hurd"
+    ))]
+    use libc::fstatat64;
+    #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+    use libc::openat;
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    use libc::openat64 as openat;
+    use libc::{c_int, fdopendir, mkdirat, renameat, unlinkat};
+
+    use super::remove_dir_impl::openat_nofollow_dironly;
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    use super::try_statx;
+    use super::{DirStream, File, FileAttr, InnerReadDir, OpenOptions, ReadDir, stat64};
+    use crate::ffi::{CStr, OsStr};
+    use crate::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+    use crate::os::unix::prelude::{OwnedFd, RawFd};
+    use crate::path::{Component, Path, PathBuf};
+    use crate::sys::common::small_c_string::run_path_with_cstr;
+    use crate::sys::fd::FileDesc;
+    use crate::sys::{cvt, cvt_r};
+    use crate::{fmt, io, mem};
+
+    /// An open directory that operations are performed relative to.
+    pub struct Dir {
+        fd: OwnedFd,
+        /// The path the directory was opened with, used for `DirEntry::path` and `Debug`.
+        root: PathBuf,
+        /// Whether paths must be resolved without leaving the directory.
+        beneath: bool,
+    }
+
+    impl Dir {
+        pub fn open(path: &Path) -> io::Result<Dir> {
+            Dir::open_with(path, false)
+        }
+
+        pub fn open_beneath(path: &Path) -> io::Result<Dir> {
+            Dir::open_with(path, true)
+        }
+
+        fn open_with(path: &Path, beneath: bool) -> io::Result<Dir> {
+            let fd = run_path_with_cstr(path, &|path| {
+                cvt_r(|| unsafe {
+                    openat(
+                        libc::AT_FDCWD,
+                        path.as_ptr(),
+                        libc::O_CLOEXEC | libc::O_RDONLY | libc::O_DIRECTORY,
+                    )
+                })
+            })?;
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            Ok(Dir { fd, root: path.to_owned(), beneath })
+        }
+
+        pub fn open_dir(&self, path: &Path) -> io::Result<Dir> {
+            let flags = libc::O_CLOEXEC | libc::O_RDONLY | libc::O_DIRECTORY | self.nofollow();
+            let fd = self.resolve(path, &|dirfd, name| {
+                cvt_r(|| unsafe { openat(dirfd, name.as_ptr(), flags) })
+            })?;
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            Ok(Dir { fd, root: self.root.join(path), beneath: self.beneath })
+        }
+
+        pub fn open_file(&self, path: &Path, opts: &OpenOptions) -> io::Result<File> {
+            let flags = libc::O_CLOEXEC
+                | opts.get_access_mode()?
+                | opts.get_creation_mode()?
+                | (opts.custom_flags as c_int & !libc::O_ACCMODE)
+                | self.nofollow();
+            let fd = self.resolve(path, &|dirfd, name| {
+                // See `File::open_c` for why the mode is passed as a `c_int`.
+                cvt_r(|| unsafe { openat(dirfd, name.as_ptr(), flags, opts.mode as c_int) })
+            })?;
+            Ok(File(unsafe { FileDesc::from_raw_fd(fd) }))
+        }
+
+        pub fn create_dir(&self, path: &Path) -> io::Result<()> {
+            self.resolve(path, &|dirfd, name| {
+                cvt(unsafe { mkdirat(dirfd, name.as_ptr(), 0o777) }).map(drop)
+            })
+        }
+
+        pub fn remove_file(&self, path: &Path) -> io::Result<()> {
+            self.resolve(path, &|dirfd, name| {
+                cvt(unsafe { unlinkat(dirfd, name.as_ptr(), 0) }).map(drop)
+            })
+        }
+
+        pub fn remove_dir(&self, path: &Path) -> io::Result<()> {
+            self.resolve(path, &|dirfd, name| {
+                cvt(unsafe { unlinkat(dirfd, name.as_ptr(), libc::AT_REMOVEDIR) }).map(drop)
+            })
+        }
+
+        pub fn rename(&self, from: &Path, to_dir: &Dir, to: &Path) -> io::Result<()> {
+            self.resolve(from, &|from_fd, from| {
+                to_dir.resolve(to, &|to_fd, to| {
+                    cvt(unsafe { renameat(from_fd, from.as_ptr(), to_fd, to.as_ptr()) }).map(drop)
+                })
+            })
+        }
+
+        pub fn symlink_metadata(&self, path: &Path) -> io::Result<FileAttr> {
+            self.resolve(path, &|dirfd, name| {
+                cfg_has_statx! {
+                    if let Some(ret) = unsafe { try_statx(
+                        dirfd,
+                        name.as_ptr(),
+                        libc::AT_SYMLINK_NOFOLLOW | libc::AT_STATX_SYNC_AS_STAT,
+                        libc::STATX_BASIC_STATS | libc::STATX_BTIME,
+                    ) } {
+                        return ret;
+                    }
+                }
+
+                let mut stat: stat64 = unsafe { mem::zeroed() };
+                cvt(unsafe {
+                    fstatat64(dirfd, name.as_ptr(), &mut stat, libc::AT_SYMLINK_NOFOLLOW)
+                })?;
+                Ok(FileAttr::from_stat64(stat))
+            })
+        }
+
+        pub fn read_dir(&self) -> io::Result<ReadDir> {
+            // Open the directory again rather than duplicating the descriptor, so that the
+            // stream gets its own offset and `read_dir` can be called more than once.
+            let fd = cvt_r(|| unsafe {
+                openat(
+                    self.fd.as_raw_fd(),
+                    c".".as_ptr(),
+                    libc::O_CLOEXEC | libc::O_RDONLY | libc::O_DIRECTORY,
+                )
+            })?;
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            let ptr = unsafe { fdopendir(fd.as_raw_fd()) };
+            if ptr.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            // The descriptor is closed by `closedir` from now on.
+            let _ = fd.into_raw_fd();
+            let inner = InnerReadDir { dirp: DirStream(ptr), root: self.root.clone() };
+            Ok(ReadDir::new(inner))
+        }
+
+        /// Extra `open` flags for the last path component.
+        fn nofollow(&self) -> c_int {
+            if self.beneath { libc::O_NOFOLLOW } else { 0 }
+        }
+
+        /// Calls `f` with the directory containing the last component of `path` and the name
+        /// of that component.
+        ///
+        /// Without `beneath`, the whole path is handed to the `*at` function, which resolves
+        /// it like the operating system always does. With `beneath`, the path is walked one
+        /// component at a time, refusing symbolic links, absolute paths and `..` components
+        /// that would leave the directory.
+        fn resolve<T>(
+            &self,
+            path: &Path,
+            f: &dyn Fn(RawFd, &CStr) -> io::Result<T>,
+        ) -> io::Result<T> {
+            if !self.beneath {
+                return run_path_with_cstr(path, &|path| f(self.fd.as_raw_fd(), path));
+            }
+
+            let mut components: Vec<Component<'_>> = path.components().collect();
+            let Some(last) = components.pop() else {
+                return Err(io::const_error!(io::ErrorKind::InvalidInput, "empty path"));
+            };
+
+            // The directories opened on the way to the last component.
+            let mut parents: Vec<OwnedFd> = Vec::new();
+            let parent_fd = |parents: &[OwnedFd]| {
+                parents.last().map_or(self.fd.as_raw_fd(), |fd| fd.as_raw_fd())
+            };
+            for component in components {
+                match component {
+                    Component::Prefix(_) | Component::RootDir => return Err(escape_error()),
+                    Component::CurDir => {}
+                    Component::ParentDir => {
+                        parents.pop().ok_or_else(escape_error)?;
+                    }
+                    Component::Normal(name) => {
+                        let parent = parent_fd(&parents);
+                        let fd = run_path_with_cstr(Path::new(name), &|name| {
+                            openat_nofollow_dironly(Some(parent), name)
+                        })?;
+                        parents.push(fd);
+                    }
+                }
+            }
+
+            let name = match last {
+                Component::Prefix(_) | Component::RootDir => return Err(escape_error()),
+                Component::CurDir => OsStr::new("."),
+                Component::ParentDir => {
+                    parents.pop().ok_or_else(escape_error)?;
+                    OsStr::new(".")
+                }
+                Component::Normal(name) => name,
+            };
+            let parent = parent_fd(&parents);
+            run_path_with_cstr(Path::new(name), &|name| f(parent, name))
+        }
+    }
+
+    fn escape_error() -> io::Error {
+        io::const_error!(io::ErrorKind::InvalidInput, "path escapes the directory")
+    }
+
+    impl fmt::Debug for Dir {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Dir")
+                .field("fd", &self.fd.as_raw_fd())
+                .field("path", &self.root)
+                .field("beneath", &self.beneath)
+                .finish()
+        }
+    }
+}
@@ -4,6 +4,7 @@
 use crate::sys::pal::unix::fd::FileDesc;
 use crate::sys::process::ExitStatus;
 use crate::sys_common::{AsInner, FromInner, IntoInner};
+use crate::time::{Duration, Instant};
 
 #[cfg(test)]
 mod tests;
@@ -12,6 +13,13 @@
 pub(crate) struct PidFd(FileDesc);
 
 impl PidFd {
+    /// Opens a pidfd for the process `pid`, which must be an unreaped child of ours so that the
+    /// pid cannot have been recycled.
+    pub fn open(pid: libc::pid_t) -> io::Result<PidFd> {
+        let fd = cvt(unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) })?;
+        Ok(unsafe { PidFd::from_raw_fd(fd as RawFd) })
+    }
+
     pub fn kill(&self) -> io::Result<()> {
         cvt(unsafe {
             libc::syscall(
@@ -50,6 +58,28 @@ pub fn try_wait(&self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::from_waitid_siginfo(siginfo)))
         }
     }
+
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        // The pidfd becomes readable once the process has exited.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => timeout,
+            };
+            // Round up, so that we don't spin when less than a millisecond is left.
+            let millis = remaining.as_nanos().div_ceil(1_000_000).min(libc::c_int::MAX as u128);
+            let mut pollfd =
+                libc::pollfd { fd: self.0.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+            match cvt(unsafe { libc::poll(&mut pollfd, 1, millis as libc::c_int) }) {
+                Ok(0) if remaining.is_zero() => return self.try_wait(),
+                Ok(0) => {}
+                Ok(_) => return self.try_wait(),
+                Err(e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
 
 impl AsInner<FileDesc> for PidFd {
@@ -619,6 +619,102 @@ unsafe fn from_raw_fd(raw_fd: RawFd) -> Self {
     }
 }
 
+/// The handle type that can be registered with a [`PollSet`].
+pub type PollHandle<'a> = BorrowedFd<'a>;
+
+/// A set of sockets, or other file descriptors, to wait for readiness on with `poll`.
+pub struct PollSet {
+    fds: Vec<libc::pollfd>,
+}
+
+/// The readiness reported by `poll` for a single entry of a [`PollSet`].
+#[derive(Clone, Copy)]
+pub struct PollEvent(libc::c_short);
+
+impl PollSet {
+    pub const fn new() -> PollSet {
+        PollSet { fds: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fds.len()
+    }
+
+    pub fn push(&mut self, handle: BorrowedFd<'_>, readable: bool, writable: bool) {
+        let events = poll_events(readable, writable);
+        self.fds.push(libc::pollfd { fd: handle.as_raw_fd(), events, revents: 0 });
+    }
+
+    pub fn set_interest(&mut self, index: usize, readable: bool, writable: bool) {
+        self.fds[index].events = poll_events(readable, writable);
+    }
+
+    pub fn clear(&mut self) {
+        self.fds.clear();
+    }
+
+    pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<usize> {
+        // Overflows are rounded up to an infinite timeout.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            let timeout = match deadline {
+                // Round up, so that we never return before the deadline, and
+                // wait in several steps if the timeout does not fit in a `c_int`.
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_nanos()
+                    .div_ceil(1_000_000)
+                    .min(c_int::MAX as u128) as c_int,
+                None => -1,
+            };
+            let n = unsafe { libc::poll(self.fds.as_mut_ptr(), self.fds.len() as _, timeout) };
+            match cvt(n) {
+                Ok(0) if deadline.is_some_and(|deadline| Instant::now() < deadline) => {}
+                Ok(n) => return Ok(n as usize),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns the readiness of the entry at `index`, or `None` if the last
+    /// call to `poll` did not report any for it.
+    pub fn event(&self, index: usize) -> Option<PollEvent> {
+        let revents = self.fds[index].revents;
+        (revents != 0).then_some(PollEvent(revents))
+    }
+}
+
+fn poll_events(readable: bool, writable: bool) -> libc::c_short {
+    let mut events = 0;
+    if readable {
+        events |= libc::POLLIN;
+    }
+    if writable {
+        events |= libc::POLLOUT;
+    }
+    events
+}
+
+impl PollEvent {
+    pub fn is_readable(self) -> bool {
+        // Reading returns the end of the stream or the pending error in these cases.
+        self.0 & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0
+    }
+
+    pub fn is_writable(self) -> bool {
+        self.0 & (libc::POLLOUT | libc::POLLERR) != 0
+    }
+
+    pub fn is_hangup(self) -> bool {
+        self.0 & libc::POLLHUP != 0
+    }
+
+    pub fn is_error(self) -> bool {
+        self.0 & (libc::POLLERR | libc::POLLNVAL) != 0
+    }
+}
+
 // In versions of glibc prior to 2.26, there's a bug where the DNS resolver
 // will cache the contents of /etc/resolv.conf, so changes to that file on disk
 // can be ignored by a long-running program. That can break DNS lookups on e.g.
@@ -104,6 +104,8 @@ pub struct Command {
     #[cfg(target_os = "linux")]
     create_pidfd: bool,
     pgroup: Option<pid_t>,
+    /// Whether `pgroup` was set by `new_process_group` rather than by `pgroup`.
+    new_process_group: bool,
 }
 
 // Create a new type for argv, so that we can make it `Send` and `Sync`
@@ -196,6 +198,7 @@ pub fn new(program: &OsStr) -> Command {
             stdout: None,
             stderr: None,
             pgroup: None,
+            new_process_group: false,
         }
     }
 
@@ -221,6 +224,7 @@ pub fn new(program: &OsStr) -> Command {
             stderr: None,
             create_pidfd: false,
             pgroup: None,
+            new_process_group: false,
         }
     }
 
@@ -258,6 +262,15 @@ pub fn groups(&mut self, groups: &[gid_t]) {
     }
     pub fn pgroup(&mut self, pgroup: pid_t) {
         self.pgroup = Some(pgroup);
+        self.new_process_group = false;
+    }
+    pub fn new_process_group(&mut self, new_group: bool) {
+        if new_group {
+            self.pgroup = Some(0);
+        } else if self.new_process_group {
+            self.pgroup = None;
+        }
+        self.new_process_group = new_group;
     }
 
     #[cfg(target_os = "linux")]
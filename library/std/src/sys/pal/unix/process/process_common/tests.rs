@@ -134,6 +134,24 @@ fn test_process_group_no_posix_spawn() {
     }
 }
 
+#[test]
+fn test_new_process_group_keeps_pgroup() {
+    let mut cmd = Command::new(OsStr::new("cat"));
+    cmd.pgroup(42);
+    cmd.new_process_group(false);
+    assert_eq!(cmd.get_pgroup(), Some(42));
+    cmd.new_process_group(true);
+    assert_eq!(cmd.get_pgroup(), Some(0));
+    cmd.new_process_group(false);
+    assert_eq!(cmd.get_pgroup(), None);
+
+    // `false` only undoes what `true` did.
+    cmd.new_process_group(true);
+    cmd.pgroup(42);
+    cmd.new_process_group(false);
+    assert_eq!(cmd.get_pgroup(), Some(42));
+}
+
 #[test]
 fn test_program_kind() {
     let vectors = &[
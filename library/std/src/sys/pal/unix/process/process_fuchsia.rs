@@ -3,6 +3,8 @@
 use crate::num::NonZero;
 use crate::sys::process::process_common::*;
 use crate::sys::process::zircon::{Handle, zx_handle_t};
+use crate::sys_common::process::wait_timeout_by_polling;
+use crate::time::Duration;
 use crate::{fmt, io, mem, ptr};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -229,6 +231,21 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
         Ok(Some(ExitStatus(proc_info.return_code)))
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        wait_timeout_by_polling(self, timeout)
+    }
+
+    pub fn kill_process_group(&mut self) -> io::Result<()> {
+        Err(io::const_error!(
+            io::ErrorKind::Unsupported,
+            "process groups are not supported by fuchsia",
+        ))
+    }
+
+    pub fn signal_process_group(&mut self, _signal: c_int) -> io::Result<()> {
+        self.kill_process_group()
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
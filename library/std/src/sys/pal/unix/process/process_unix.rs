@@ -16,6 +16,8 @@
 #[cfg(target_os = "linux")]
 use crate::sys::pal::unix::linux::pidfd::PidFd;
 use crate::sys::process::process_common::*;
+use crate::sys_common::process::wait_timeout_by_polling;
+use crate::time::Duration;
 use crate::{fmt, mem, sys};
 
 cfg_if::cfg_if! {
@@ -23,7 +25,6 @@
     if #[cfg(any(target_env = "nto70", target_env = "nto71"))] {
         use crate::thread;
         use libc::{c_char, posix_spawn_file_actions_t, posix_spawnattr_t};
-        use crate::time::Duration;
         use crate::sync::LazyLock;
         // Get smallest amount of time we can sleep.
         // Return a common value if it cannot be determined.
@@ -69,7 +70,8 @@ pub fn spawn(
 
         let (ours, theirs) = self.setup_io(default, needs_stdin)?;
 
-        if let Some(ret) = self.posix_spawn(&theirs, envp.as_ref())? {
+        if let Some(mut ret) = self.posix_spawn(&theirs, envp.as_ref())? {
+            ret.pgroup = self.get_pgroup();
             return Ok((ret, ours));
         }
 
@@ -131,6 +133,7 @@ pub fn spawn(
 
         // Safety: We obtained the pidfd (on Linux) using SOCK_SEQPACKET, so it's valid.
         let mut p = unsafe { Process::new(pid, pidfd) };
+        p.pgroup = self.get_pgroup();
         let mut bytes = [0; 8];
 
         // loop to handle EINTR
@@ -911,6 +914,8 @@ union Cmsg {
 pub struct Process {
     pid: pid_t,
     status: Option<ExitStatus>,
+    // The process group the child was put into, where 0 means a new group led by the child.
+    pgroup: Option<pid_t>,
     // On Linux, stores the pidfd created for this child.
     // This is None if the user did not request pidfd creation,
     // or if the pidfd could not be created for some reason
@@ -932,12 +937,12 @@ unsafe fn new(pid: pid_t, pidfd: pid_t) -> Self {
         use crate::sys_common::FromInner;
         // Safety: If `pidfd` is nonnegative, we assume it's valid and otherwise unowned.
         let pidfd = (pidfd >= 0).then(|| PidFd::from_inner(sys::fd::FileDesc::from_raw_fd(pidfd)));
-        Process { pid, status: None, pidfd }
+        Process { pid, status: None, pgroup: None, pidfd }
     }
 
     #[cfg(not(target_os = "linux"))]
     unsafe fn new(pid: pid_t, _pidfd: pid_t) -> Self {
-        Process { pid, status: None }
+        Process { pid, status: None, pgroup: None }
     }
 
     pub fn id(&self) -> u32 {
@@ -997,6 +1002,57 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        if let Some(status) = self.try_wait()? {
+            return Ok(Some(status));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            // Waiting on a pidfd doesn't require one to have been requested at spawn time, since
+            // the pid of a child we haven't reaped yet can't be recycled.
+            let opened;
+            let pid_fd = match self.pidfd.as_ref() {
+                Some(pid_fd) => Some(pid_fd),
+                None => {
+                    opened = PidFd::open(self.pid).ok();
+                    opened.as_ref()
+                }
+            };
+            if let Some(pid_fd) = pid_fd {
+                let status = pid_fd.wait_timeout(timeout)?;
+                if let Some(status) = status {
+                    self.status = Some(status)
+                }
+                return Ok(status);
+            }
+        }
+        wait_timeout_by_polling(self, timeout)
+    }
+
+    pub fn kill_process_group(&mut self) -> io::Result<()> {
+        self.signal_process_group(libc::SIGKILL)
+    }
+
+    pub fn signal_process_group(&mut self, signal: c_int) -> io::Result<()> {
+        let pgroup = match self.pgroup {
+            Some(0) => self.pid,
+            Some(pgroup) => pgroup,
+            None => {
+                return Err(io::const_error!(
+                    ErrorKind::InvalidInput,
+                    "the child was not spawned into its own process group",
+                ));
+            }
+        };
+        // Unlike with `kill`, the group may outlive the child, so keep sending the signal after
+        // the child has been waited for. The group ID can't be reused while any process is left
+        // in the group, and if none is, there is nothing left to signal.
+        match cvt(unsafe { libc::kill(-pgroup, signal) }) {
+            Err(e) if e.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+            result => result.map(drop),
+        }
+    }
 }
 
 /// Unix exit statuses
@@ -6,6 +6,8 @@
 use crate::sys::cvt;
 use crate::sys::pal::unix::thread;
 use crate::sys::process::process_common::*;
+use crate::sys_common::process::wait_timeout_by_polling;
+use crate::time::Duration;
 use crate::{fmt, sys};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -180,6 +182,20 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        wait_timeout_by_polling(self, timeout)
+    }
+
+    pub fn kill_process_group(&mut self) -> io::Result<()> {
+        Err(
+            io::const_error!(ErrorKind::Unsupported, "process groups are not supported by vxworks",),
+        )
+    }
+
+    pub fn signal_process_group(&mut self, _signal: c_int) -> io::Result<()> {
+        self.kill_process_group()
+    }
 }
 
 /// Unix exit statuses
@@ -5,6 +5,7 @@
 use crate::path::{Path, PathBuf};
 use crate::sys::time::SystemTime;
 use crate::sys::unsupported;
+pub use crate::sys_common::fs::Dir;
 
 pub struct File(!);
 
@@ -342,3 +343,5 @@ pub fn canonicalize(_p: &Path) -> io::Result<PathBuf> {
 pub fn copy(_from: &Path, _to: &Path) -> io::Result<u64> {
     unsupported()
 }
+
+pub use self::copy as copy_reflink;
@@ -12,7 +12,7 @@
 use crate::sys::common::small_c_string::run_path_with_cstr;
 use crate::sys::time::SystemTime;
 use crate::sys::unsupported;
-pub use crate::sys_common::fs::exists;
+pub use crate::sys_common::fs::{Dir, exists};
 use crate::sys_common::{AsInner, FromInner, IntoInner, ignore_notfound};
 use crate::{fmt, iter, ptr};
 
@@ -811,6 +811,8 @@ pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
     io::copy(&mut reader, &mut writer)
 }
 
+pub use self::copy as copy_reflink;
+
 pub fn remove_dir_all(path: &Path) -> io::Result<()> {
     let (parent, path) = open_parent(path)?;
     remove_dir_all_recursive(&parent, &path)
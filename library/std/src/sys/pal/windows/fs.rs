@@ -12,6 +12,7 @@
 use crate::sys::path::maybe_verbatim;
 use crate::sys::time::SystemTime;
 use crate::sys::{Align8, c, cvt};
+pub use crate::sys_common::fs::Dir;
 use crate::sys_common::{AsInner, FromInner, IntoInner};
 use crate::{fmt, ptr, slice};
 
@@ -1464,6 +1465,8 @@ pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
     Ok(size as u64)
 }
 
+pub use self::copy as copy_reflink;
+
 pub fn junction_point(original: &Path, link: &Path) -> io::Result<()> {
     // Create and open a new directory in one go.
     let mut opts = OpenOptions::new();
@@ -10,7 +10,7 @@
 use crate::sync::OnceLock;
 use crate::sys::c;
 use crate::sys_common::{AsInner, FromInner, IntoInner, net};
-use crate::time::Duration;
+use crate::time::{Duration, Instant};
 use crate::{cmp, mem, ptr, sys};
 
 #[allow(non_camel_case_types)]
@@ -572,3 +572,98 @@ unsafe fn from_raw_socket(raw_socket: RawSocket) -> Self {
         unsafe { Self(FromRawSocket::from_raw_socket(raw_socket)) }
     }
 }
+
+/// The handle type that can be registered with a [`PollSet`].
+pub type PollHandle<'a> = BorrowedSocket<'a>;
+
+/// A set of sockets to wait for readiness on with `WSAPoll`.
+pub struct PollSet {
+    fds: Vec<c::WSAPOLLFD>,
+}
+
+/// The readiness reported by `WSAPoll` for a single entry of a [`PollSet`].
+#[derive(Clone, Copy)]
+pub struct PollEvent(c::WSAPOLL_EVENT_FLAGS);
+
+impl PollSet {
+    pub const fn new() -> PollSet {
+        PollSet { fds: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fds.len()
+    }
+
+    pub fn push(&mut self, handle: BorrowedSocket<'_>, readable: bool, writable: bool) {
+        let fd = handle.as_raw_socket() as c::SOCKET;
+        self.fds.push(c::WSAPOLLFD { fd, events: poll_events(readable, writable), revents: 0 });
+    }
+
+    pub fn set_interest(&mut self, index: usize, readable: bool, writable: bool) {
+        self.fds[index].events = poll_events(readable, writable);
+    }
+
+    pub fn clear(&mut self) {
+        self.fds.clear();
+    }
+
+    pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<usize> {
+        init();
+        // Overflows are rounded up to an infinite timeout.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            let timeout = match deadline {
+                // Round up, so that we never return before the deadline, and
+                // wait in several steps if the timeout does not fit in a `c_int`.
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_nanos()
+                    .div_ceil(1_000_000)
+                    .min(c_int::MAX as u128) as c_int,
+                None => -1,
+            };
+            let n = unsafe { c::WSAPoll(self.fds.as_mut_ptr(), self.fds.len() as u32, timeout) };
+            match cvt(n)? {
+                0 if deadline.is_some_and(|deadline| Instant::now() < deadline) => {}
+                n => return Ok(n as usize),
+            }
+        }
+    }
+
+    /// Returns the readiness of the entry at `index`, or `None` if the last
+    /// call to `poll` did not report any for it.
+    pub fn event(&self, index: usize) -> Option<PollEvent> {
+        let revents = self.fds[index].revents;
+        (revents != 0).then_some(PollEvent(revents))
+    }
+}
+
+fn poll_events(readable: bool, writable: bool) -> c::WSAPOLL_EVENT_FLAGS {
+    let mut events = 0;
+    if readable {
+        events |= c::POLLRDNORM;
+    }
+    if writable {
+        events |= c::POLLWRNORM;
+    }
+    events
+}
+
+impl PollEvent {
+    pub fn is_readable(self) -> bool {
+        // Reading returns the end of the stream or the pending error in these cases.
+        self.0 & (c::POLLRDNORM | c::POLLHUP | c::POLLERR) != 0
+    }
+
+    pub fn is_writable(self) -> bool {
+        self.0 & (c::POLLWRNORM | c::POLLERR) != 0
+    }
+
+    pub fn is_hangup(self) -> bool {
+        self.0 & c::POLLHUP != 0
+    }
+
+    pub fn is_error(self) -> bool {
+        self.0 & (c::POLLERR | c::POLLNVAL) != 0
+    }
+}
@@ -21,9 +21,10 @@
 use crate::sys::fs::{File, OpenOptions};
 use crate::sys::handle::Handle;
 use crate::sys::pipe::{self, AnonPipe};
-use crate::sys::{cvt, path, stdio};
+use crate::sys::{cvt, dur2timeout, path, stdio};
 use crate::sys_common::IntoInner;
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
+use crate::time::Duration;
 use crate::{cmp, env, fmt, mem, ptr};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -158,6 +159,7 @@ pub struct Command {
     flags: u32,
     show_window: Option<u16>,
     detach: bool, // not currently exposed in std::process
+    new_process_group: bool,
     stdin: Option<Stdio>,
     stdout: Option<Stdio>,
     stderr: Option<Stdio>,
@@ -190,6 +192,7 @@ pub fn new(program: &OsStr) -> Command {
             flags: 0,
             show_window: None,
             detach: false,
+            new_process_group: false,
             stdin: None,
             stdout: None,
             stderr: None,
@@ -222,6 +225,9 @@ pub fn creation_flags(&mut self, flags: u32) {
     pub fn show_window(&mut self, cmd_show: Option<u16>) {
         self.show_window = cmd_show;
     }
+    pub fn new_process_group(&mut self, new_group: bool) {
+        self.new_process_group = new_group;
+    }
 
     pub fn force_quotes(&mut self, enabled: bool) {
         self.force_quotes_enabled = enabled;
@@ -306,6 +312,19 @@ pub fn spawn(
         if self.detach {
             flags |= c::DETACHED_PROCESS | c::CREATE_NEW_PROCESS_GROUP;
         }
+        // Windows has no process groups that can be killed as a whole, so the child is put in
+        // a job object instead. It is created suspended so that it can't start any processes
+        // before it is in the job, as those would escape it.
+        let job = if self.new_process_group {
+            flags |= c::CREATE_NEW_PROCESS_GROUP | c::CREATE_SUSPENDED;
+            let job = unsafe { c::CreateJobObjectW(ptr::null(), ptr::null()) };
+            if job.is_null() {
+                return Err(Error::last_os_error());
+            }
+            Some(unsafe { Handle::from_raw_handle(job) })
+        } else {
+            None
+        };
 
         let (envp, _data) = make_envp(maybe_env)?;
         let (dirp, _data) = make_dirp(self.cwd.as_ref())?;
@@ -389,15 +408,35 @@ pub fn spawn(
             ))
         }?;
 
-        unsafe {
-            Ok((
-                Process {
-                    handle: Handle::from_raw_handle(pi.hProcess),
-                    main_thread_handle: Handle::from_raw_handle(pi.hThread),
-                },
-                pipes,
-            ))
+        let process = unsafe {
+            Process {
+                handle: Handle::from_raw_handle(pi.hProcess),
+                main_thread_handle: Handle::from_raw_handle(pi.hThread),
+                job,
+            }
+        };
+
+        if let Some(job) = &process.job {
+            let result = cvt(unsafe {
+                c::AssignProcessToJobObject(job.as_raw_handle(), process.handle.as_raw_handle())
+            })
+            .and_then(|_| {
+                // Leave the child suspended if that's what the caller asked for.
+                if self.flags & c::CREATE_SUSPENDED != 0 {
+                    return Ok(());
+                }
+                match unsafe { c::ResumeThread(process.main_thread_handle.as_raw_handle()) } {
+                    u32::MAX => Err(Error::last_os_error()),
+                    _ => Ok(()),
+                }
+            });
+            if let Err(err) = result {
+                unsafe { c::TerminateProcess(process.handle.as_raw_handle(), 1) };
+                return Err(err);
+            }
         }
+
+        Ok((process, pipes))
     }
 
     pub fn output(&mut self) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>)> {
@@ -656,6 +695,8 @@ fn from(_: io::Stderr) -> Stdio {
 pub struct Process {
     handle: Handle,
     main_thread_handle: Handle,
+    // The job object standing in for the process group, if one was requested.
+    job: Option<Handle>,
 }
 
 impl Process {
@@ -708,6 +749,31 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
     }
 
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        unsafe {
+            match c::WaitForSingleObject(self.handle.as_raw_handle(), dur2timeout(timeout)) {
+                c::WAIT_OBJECT_0 => {}
+                c::WAIT_TIMEOUT => {
+                    return Ok(None);
+                }
+                _ => return Err(io::Error::last_os_error()),
+            }
+            let mut status = 0;
+            cvt(c::GetExitCodeProcess(self.handle.as_raw_handle(), &mut status))?;
+            Ok(Some(ExitStatus(status)))
+        }
+    }
+
+    pub fn kill_process_group(&mut self) -> io::Result<()> {
+        let Some(job) = &self.job else {
+            return Err(io::const_error!(
+                ErrorKind::InvalidInput,
+                "the child was not spawned into its own process group",
+            ));
+        };
+        cvt(unsafe { c::TerminateJobObject(job.as_raw_handle(), 1) }).map(drop)
+    }
+
     pub fn handle(&self) -> &Handle {
         &self.handle
     }
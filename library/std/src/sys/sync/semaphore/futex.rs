@@ -0,0 +1,129 @@
+use crate::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::sys::futex::{Futex, Primitive, futex_wait, futex_wake_all};
+use crate::time::{Duration, Instant};
+
+pub struct Semaphore {
+    // The lower 31 bits hold the number of available permits. The highest bit
+    // is set when there may be threads waiting for permits.
+    futex: Futex,
+}
+
+const WAITING: Primitive = 1 << 31;
+const PERMITS_MASK: Primitive = WAITING - 1;
+
+impl Semaphore {
+    pub const MAX_PERMITS: usize = PERMITS_MASK as usize;
+
+    #[inline]
+    pub const fn new(permits: usize) -> Self {
+        assert!(permits <= Self::MAX_PERMITS);
+        Self { futex: Futex::new(permits as Primitive) }
+    }
+
+    #[inline]
+    pub fn available(&self) -> usize {
+        (self.futex.load(Relaxed) & PERMITS_MASK) as usize
+    }
+
+    #[inline]
+    pub fn try_acquire(&self, n: usize) -> bool {
+        let mut state = self.futex.load(Relaxed);
+        loop {
+            if ((state & PERMITS_MASK) as usize) < n {
+                return false;
+            }
+            // This never touches the waiting bit, as `n` is at most the number of permits.
+            match self.futex.compare_exchange_weak(state, state - n as Primitive, Acquire, Relaxed)
+            {
+                Ok(_) => return true,
+                Err(s) => state = s,
+            }
+        }
+    }
+
+    #[inline]
+    pub fn acquire(&self, n: usize) {
+        if !self.try_acquire(n) {
+            self.acquire_contended(n, None);
+        }
+    }
+
+    #[inline]
+    pub fn acquire_timeout(&self, n: usize, timeout: Duration) -> bool {
+        self.try_acquire(n) || self.acquire_contended(n, Some(timeout))
+    }
+
+    #[cold]
+    fn acquire_contended(&self, n: usize, timeout: Option<Duration>) -> bool {
+        // Overflows are rounded up to an infinite timeout.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        let mut state = self.futex.load(Relaxed);
+        loop {
+            if ((state & PERMITS_MASK) as usize) >= n {
+                match self.futex.compare_exchange_weak(
+                    state,
+                    state - n as Primitive,
+                    Acquire,
+                    Relaxed,
+                ) {
+                    Ok(_) => return true,
+                    Err(s) => state = s,
+                }
+                continue;
+            }
+
+            // Make sure that the next release wakes us up.
+            // We avoid an unnecessary write if the bit is already set.
+            if state & WAITING == 0 {
+                if let Err(s) =
+                    self.futex.compare_exchange(state, state | WAITING, Relaxed, Relaxed)
+                {
+                    state = s;
+                    continue;
+                }
+                state |= WAITING;
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => Some(remaining),
+                    _ => return false,
+                },
+                None => None,
+            };
+            // Wait for the futex to change state, assuming there are still not
+            // enough permits. A timeout is noticed when computing the remaining
+            // time above, after trying to take the permits one last time.
+            futex_wait(&self.futex, state, remaining);
+            state = self.futex.load(Relaxed);
+        }
+    }
+
+    #[inline]
+    pub fn release(&self, n: usize) {
+        let mut state = self.futex.load(Relaxed);
+        loop {
+            let permits = (state & PERMITS_MASK) as usize;
+            if n > Self::MAX_PERMITS - permits {
+                panic!("semaphore permit count overflowed");
+            }
+            // Clear the waiting bit, as all waiters are woken up below. The
+            // ones that still don't get enough permits will set it again.
+            let new = (permits + n) as Primitive;
+            match self.futex.compare_exchange_weak(state, new, Release, Relaxed) {
+                Ok(_) => break,
+                Err(s) => state = s,
+            }
+        }
+        if state & WAITING != 0 {
+            self.wake();
+        }
+    }
+
+    #[cold]
+    fn wake(&self) {
+        // All waiters are woken up, as they might be waiting for different
+        // numbers of permits, so waking just one might not make progress.
+        futex_wake_all(&self.futex);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::cell::UnsafeCell;
+use crate::sys::sync::{Condvar, Mutex};
+use crate::time::{Duration, Instant};
+
+/// A semaphore for platforms without futexes, built on the platform's mutex
+/// and condition variable.
+pub struct Semaphore {
+    mutex: Mutex,
+    cvar: Condvar,
+    /// Only accessed while `mutex` is locked.
+    permits: UnsafeCell<usize>,
+}
+
+unsafe impl Send for Semaphore {}
+unsafe impl Sync for Semaphore {}
+
+impl Semaphore {
+    // The same limit as for the futex-based semaphore, to behave the same on all platforms.
+    pub const MAX_PERMITS: usize = i32::MAX as usize;
+
+    #[inline]
+    pub const fn new(permits: usize) -> Self {
+        assert!(permits <= Self::MAX_PERMITS);
+        Self { mutex: Mutex::new(), cvar: Condvar::new(), permits: UnsafeCell::new(permits) }
+    }
+
+    /// Runs `f` with the mutex locked.
+    fn with_permits<R>(&self, f: impl FnOnce(&mut usize) -> R) -> R {
+        self.mutex.lock();
+        // SAFETY: the mutex is locked, so there are no other references to the count.
+        let r = f(unsafe { &mut *self.permits.get() });
+        unsafe { self.mutex.unlock() };
+        r
+    }
+
+    pub fn available(&self) -> usize {
+        self.with_permits(|permits| *permits)
+    }
+
+    pub fn try_acquire(&self, n: usize) -> bool {
+        self.with_permits(|permits| {
+            let acquired = *permits >= n;
+            if acquired {
+                *permits -= n;
+            }
+            acquired
+        })
+    }
+
+    pub fn acquire(&self, n: usize) {
+        self.acquire_optional_timeout(n, None);
+    }
+
+    pub fn acquire_timeout(&self, n: usize, timeout: Duration) -> bool {
+        self.acquire_optional_timeout(n, Some(timeout))
+    }
+
+    fn acquire_optional_timeout(&self, n: usize, timeout: Option<Duration>) -> bool {
+        // Overflows are rounded up to an infinite timeout.
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        self.mutex.lock();
+        let acquired = loop {
+            // SAFETY: the mutex is locked, and the reference does not outlive the iteration.
+            let permits = unsafe { &mut *self.permits.get() };
+            if *permits >= n {
+                *permits -= n;
+                break true;
+            }
+            match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => unsafe {
+                        self.cvar.wait_timeout(&self.mutex, remaining);
+                    },
+                    _ => break false,
+                },
+                None => unsafe { self.cvar.wait(&self.mutex) },
+            }
+        };
+        unsafe { self.mutex.unlock() };
+        acquired
+    }
+
+    pub fn release(&self, n: usize) {
+        let overflowed = self.with_permits(|permits| {
+            let overflowed = n > Self::MAX_PERMITS - *permits;
+            if !overflowed {
+                *permits += n;
+            }
+            overflowed
+        });
+        if overflowed {
+            panic!("semaphore permit count overflowed");
+        }
+        // All waiters are woken up, as they might be waiting for different
+        // numbers of permits, so waking just one might not make progress.
+        self.cvar.notify_all();
+    }
+}
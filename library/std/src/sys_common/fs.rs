@@ -1,9 +1,9 @@
 #![allow(dead_code)] // not used on all platforms
 
-use crate::fs;
 use crate::io::{self, Error, ErrorKind};
-use crate::path::Path;
+use crate::path::{Path, PathBuf};
 use crate::sys_common::ignore_notfound;
+use crate::{fmt, fs, sys};
 
 pub(crate) const NOT_FILE_ERROR: Error = io::const_error!(
     ErrorKind::InvalidInput,
@@ -58,3 +58,65 @@ pub fn exists(path: &Path) -> io::Result<bool> {
         Err(error) => Err(error),
     }
 }
+
+/// A directory handle for platforms without `openat`-style system calls.
+///
+/// Operations are performed by joining paths to the path the directory was opened with, so
+/// they are not protected against the directory being moved or replaced in the meantime.
+pub struct Dir {
+    root: PathBuf,
+}
+
+impl Dir {
+    pub fn open(path: &Path) -> io::Result<Dir> {
+        if !sys::fs::stat(path)?.file_type().is_dir() {
+            return Err(io::const_error!(ErrorKind::NotADirectory, "not a directory"));
+        }
+        Ok(Dir { root: path.to_owned() })
+    }
+
+    pub fn open_beneath(_path: &Path) -> io::Result<Dir> {
+        Err(io::const_error!(
+            ErrorKind::Unsupported,
+            "resolving paths beneath a directory is not supported on this platform",
+        ))
+    }
+
+    pub fn open_dir(&self, path: &Path) -> io::Result<Dir> {
+        Dir::open(&self.root.join(path))
+    }
+
+    pub fn open_file(&self, path: &Path, opts: &sys::fs::OpenOptions) -> io::Result<sys::fs::File> {
+        sys::fs::File::open(&self.root.join(path), opts)
+    }
+
+    pub fn create_dir(&self, path: &Path) -> io::Result<()> {
+        sys::fs::DirBuilder::new().mkdir(&self.root.join(path))
+    }
+
+    pub fn remove_file(&self, path: &Path) -> io::Result<()> {
+        sys::fs::unlink(&self.root.join(path))
+    }
+
+    pub fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        sys::fs::rmdir(&self.root.join(path))
+    }
+
+    pub fn rename(&self, from: &Path, to_dir: &Dir, to: &Path) -> io::Result<()> {
+        sys::fs::rename(&self.root.join(from), &to_dir.root.join(to))
+    }
+
+    pub fn symlink_metadata(&self, path: &Path) -> io::Result<sys::fs::FileAttr> {
+        sys::fs::lstat(&self.root.join(path))
+    }
+
+    pub fn read_dir(&self) -> io::Result<sys::fs::ReadDir> {
+        sys::fs::readdir(&self.root)
+    }
+}
+
+impl fmt::Debug for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dir").field("path", &self.root).finish()
+    }
+}
@@ -5,7 +5,8 @@
 use crate::ffi::{OsStr, OsString};
 use crate::sys::pipe::read2;
 use crate::sys::process::{EnvKey, ExitStatus, Process, StdioPipes};
-use crate::{env, fmt, io};
+use crate::time::{Duration, Instant};
+use crate::{env, fmt, io, thread};
 
 // Stores a set of changes to an environment
 #[derive(Clone)]
@@ -157,3 +158,27 @@ pub fn wait_with_output(
     let status = process.wait()?;
     Ok((status, stdout, stderr))
 }
+
+/// Waits for `process` to exit by calling `try_wait` at increasing intervals, for platforms
+/// that have no way to block on a child with a timeout.
+pub fn wait_timeout_by_polling(
+    process: &mut Process,
+    timeout: Duration,
+) -> io::Result<Option<ExitStatus>> {
+    // Short enough to notice a quickly exiting child, long enough to not burn a core.
+    const MAX_INTERVAL: Duration = Duration::from_millis(5);
+
+    let start = Instant::now();
+    let mut interval = Duration::from_micros(50);
+    loop {
+        if let Some(status) = process.try_wait()? {
+            return Ok(Some(status));
+        }
+        let remaining = timeout.saturating_sub(start.elapsed());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+        thread::sleep(interval.min(remaining));
+        interval = (interval * 2).min(MAX_INTERVAL);
+    }
+}
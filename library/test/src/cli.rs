@@ -3,7 +3,10 @@
 use std::env;
 use std::io::{self, IsTerminal};
 use std::path::PathBuf;
+use std::time::Duration;
 
+use super::helpers::failure_cache::{LastFailed, cache_file, failed_tests, read_cache};
+use super::helpers::shard::{TestShard, read_durations};
 use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
 use super::time::TestTimeOptions;
 
@@ -23,9 +26,19 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub shuffle: bool,
     pub shuffle_seed: Option<u64>,
+    /// Only run the part of the (filtered) test set that belongs to this shard.
+    pub shard: Option<TestShard>,
+    /// Number of times a failing test is run again before it's reported as failed.
+    pub retries: usize,
+    /// Directory in which the outcome of every test that is run gets recorded.
+    pub failure_cache: Option<PathBuf>,
+    /// Select the tests that failed in the previous run, as recorded in `failure_cache`.
+    pub last_failed: Option<LastFailed>,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    /// Hard limit for the execution time of a single test.
+    pub test_timeout: Option<Duration>,
     /// Stop at first failing test.
     /// May run a few more tests due to threading, but will
     /// abort as soon as possible.
@@ -102,8 +115,9 @@ fn optgroups() -> getopts::Options {
             pretty = Print verbose output;
             terse  = Display one character per test;
             json   = Output a json document;
-            junit  = Output a JUnit document",
-            "pretty|terse|json|junit",
+            junit  = Output a JUnit document;
+            tap    = Output a TAP version 13 stream",
+            "pretty|terse|json|junit|tap",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -142,12 +156,62 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests that run for longer than SECONDS.
+
+            Tests running in a separate process (panic=abort) are killed
+            once the limit is exceeded. Tests running in-process can't be
+            interrupted, so the first one to time out stops the test run.",
+            "SECONDS",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "retry",
+            "Run failing tests up to N more times. Tests that pass on a later
+            attempt are reported as flaky instead of ok",
+            "N",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests of shard INDEX (zero-based), out of --shard-count shards",
+            "INDEX",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Split the tests into COUNT disjoint shards, in a stable order",
+            "COUNT",
+        )
+        .optopt(
+            "",
+            "shard-durations",
+            "Balance the shards using the test durations recorded in PATH.
+            Every line of the file consists of a test name followed by its
+            execution time in seconds, e.g. `tests::slow_test 12.5`",
+            "PATH",
+        )
+        .optopt(
+            "",
+            "failure-cache",
+            "Record the outcome of every test that is run in a file in DIR,
+            for use by --only-failed and --failed-first",
+            "DIR",
+        )
+        .optflag("", "only-failed", "Only run the tests that failed in the previous run")
+        .optflag(
+            "",
+            "failed-first",
+            "Run the tests that failed in the previous run before all other tests",
         );
     opts
 }
@@ -171,6 +235,16 @@ fn usage(binary: &str, options: &getopts::Options) {
 tests in the same order again. Note that --shuffle and --shuffle-seed do not
 affect whether the tests are run in parallel.
 
+The tests can be split across several runs (for example on different CI
+machines) with --shard-index and --shard-count. Every run computes the same
+partition of the filtered tests; --shard-durations balances the shards using
+previously recorded execution times instead of the test count.
+
+With --failure-cache DIR (or RUST_TEST_FAILURE_CACHE set to DIR), the outcome
+of every test that is run is recorded in DIR. The next run can then use
+--only-failed to run just the tests that failed, or --failed-first to run them
+before the other tests.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -259,8 +333,12 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let (failure_cache, last_failed) = get_failure_cache(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -296,9 +374,14 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         shuffle,
         shuffle_seed,
+        shard,
+        retries,
+        failure_cache,
+        last_failed,
         test_threads,
         skip,
         time_options,
+        test_timeout,
         options,
         fail_fast: false,
     };
@@ -336,6 +419,26 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(secs_str) => match secs_str.parse::<f64>().map(Duration::try_from_secs_f64) {
+            Ok(Ok(timeout)) if !timeout.is_zero() => Some(timeout),
+            _ => {
+                return Err(format!(
+                    "argument for --test-timeout must be a positive number of seconds \
+                     (was {secs_str})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -375,6 +478,94 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retry") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retry must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_failure_cache(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<(Option<PathBuf>, Option<LastFailed>)> {
+    let mut failure_cache = unstable_optopt!(matches, allow_unstable, "failure-cache");
+    if failure_cache.is_none() && allow_unstable {
+        failure_cache = env::var("RUST_TEST_FAILURE_CACHE").ok().filter(|dir| !dir.is_empty());
+    }
+    let failure_cache = failure_cache.map(PathBuf::from);
+
+    let only_failed = unstable_optflag!(matches, allow_unstable, "only-failed");
+    let failed_first = unstable_optflag!(matches, allow_unstable, "failed-first");
+    if !only_failed && !failed_first {
+        return Ok((failure_cache, None));
+    }
+    if only_failed && failed_first {
+        return Err("the options --only-failed and --failed-first are mutually exclusive".into());
+    }
+    let Some(ref dir) = failure_cache else {
+        return Err("the --only-failed and --failed-first options require a cache directory \
+                    (use --failure-cache or RUST_TEST_FAILURE_CACHE)"
+            .into());
+    };
+
+    let path = cache_file(dir);
+    let outcomes = read_cache(&path)
+        .map_err(|e| format!("failed to read the failure cache {}: {e}", path.display()))?;
+    let last_failed = LastFailed { names: failed_tests(&outcomes), only: only_failed };
+
+    Ok((failure_cache, Some(last_failed)))
+}
+
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<TestShard>> {
+    let parse = |name: &str, value: String| {
+        value
+            .parse::<usize>()
+            .map_err(|e| format!("argument for --{name} must be a number (error: {e})"))
+    };
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+    let durations = unstable_optopt!(matches, allow_unstable, "shard-durations");
+
+    let shard = match (index, count) {
+        (Some(index), Some(count)) => {
+            let index = parse("shard-index", index)?;
+            let count = parse("shard-count", count)?;
+            if count == 0 {
+                return Err("argument for --shard-count must not be 0".to_string());
+            }
+            if index >= count {
+                return Err(format!(
+                    "argument for --shard-index must be less than --shard-count ({index} >= {count})"
+                ));
+            }
+            TestShard::new(index, count)
+        }
+        (None, None) if durations.is_none() => return Ok(None),
+        _ => {
+            return Err("the options --shard-index and --shard-count must be used together".into());
+        }
+    };
+
+    match durations {
+        Some(path) => match read_durations(&PathBuf::from(&path)) {
+            Ok(durations) => Ok(Some(shard.with_durations(durations))),
+            Err(e) => Err(format!("failed to read test durations from {path}: {e}")),
+        },
+        None => Ok(Some(shard)),
+    }
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -414,9 +605,15 @@ fn get_format(
             }
             OutputFormat::Junit
         }
+        Some("tap") => {
+            if !allow_unstable {
+                return Err("The \"tap\" format is only accepted on the nightly compiler with -Z unstable-options".into());
+            }
+            OutputFormat::Tap
+        }
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, json or junit (was \
+                "argument for --format must be pretty, terse, json, junit or tap (was \
                  {v})"
             ));
         }
@@ -1,17 +1,20 @@
 //! Module providing interface for running tests in the console.
 
+use std::collections::HashSet;
 use std::fs::File;
 use std::io;
 use std::io::prelude::Write;
+use std::path::Path;
 use std::time::Instant;
 
 use super::bench::fmt_bench_samples;
 use super::cli::TestOpts;
 use super::event::{CompletedTest, TestEvent};
 use super::formatters::{
-    JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter,
+    JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TapFormatter, TerseFormatter,
 };
 use super::helpers::concurrency::get_concurrency;
+use super::helpers::failure_cache::{CachedOutcome, cache_file, read_cache, write_cache};
 use super::helpers::metrics::MetricMap;
 use super::options::{Options, OutputFormat};
 use super::test_result::TestResult;
@@ -79,6 +82,7 @@ pub struct ConsoleTestState {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub flaky: usize,
     pub ignored: usize,
     pub filtered_out: usize,
     pub measured: usize,
@@ -86,6 +90,7 @@ pub struct ConsoleTestState {
     pub metrics: MetricMap,
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flakes: Vec<(TestDesc, Vec<u8>)>,
     pub ignores: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
     pub options: Options,
@@ -103,6 +108,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             total: 0,
             passed: 0,
             failed: 0,
+            flaky: 0,
             ignored: 0,
             filtered_out: 0,
             measured: 0,
@@ -110,6 +116,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             metrics: MetricMap::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
+            flakes: Vec::new(),
             ignores: Vec::new(),
             time_failures: Vec::new(),
             options: opts.options,
@@ -154,6 +161,9 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrFlaky(attempts) => {
+                        format!("flaky (passed on attempt {attempts})")
+                    }
                 },
                 name,
             )
@@ -165,7 +175,7 @@ pub fn write_log_result(
     }
 
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured
+        self.passed + self.failed + self.flaky + self.ignored + self.measured
     }
 }
 
@@ -177,7 +187,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
     };
 
     let mut out: Box<dyn OutputFormatter> = match opts.format {
-        OutputFormat::Pretty | OutputFormat::Junit => {
+        OutputFormat::Pretty | OutputFormat::Junit | OutputFormat::Tap => {
             Box::new(PrettyFormatter::new(output, false, 0, false, None))
         }
         OutputFormat::Terse => Box::new(TerseFormatter::new(output, false, 0, false)),
@@ -246,6 +256,10 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flakes.push((test, stdout));
+        }
     }
 }
 
@@ -266,6 +280,8 @@ fn on_test_event(
         }
         TestEvent::TeWait(ref test) => out.write_test_start(test)?,
         TestEvent::TeTimeout(ref test) => out.write_timeout(test)?,
+        // The result of the abandoned test has already been reported.
+        TestEvent::TeAbandoned(_) => {}
         TestEvent::TeResult(completed_test) => {
             let test = &completed_test.desc;
             let result = &completed_test.result;
@@ -310,21 +326,70 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         }
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
         OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
+        OutputFormat::Tap => Box::new(TapFormatter::new(output)),
     };
     let mut st = ConsoleTestState::new(opts)?;
 
+    // Remember which tests exist, so that tests that have since been removed can be dropped
+    // from the failure cache.
+    let known_tests = opts
+        .failure_cache
+        .as_ref()
+        .map(|_| tests.iter().map(|t| t.desc.name.as_slice().to_string()).collect::<HashSet<_>>());
+
     // Prevent the usage of `Instant` in some cases:
     // - It's currently not supported for wasm targets.
     let is_instant_unsupported =
         (cfg!(target_family = "wasm") && !cfg!(target_os = "wasi")) || cfg!(target_os = "zkvm");
 
     let start_time = (!is_instant_unsupported).then(Instant::now);
-    run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
+    // A test that hangs past `--test-timeout` in-process stops the run early.
+    let mut abandoned = false;
+    run_tests(opts, tests, |x| {
+        abandoned |= matches!(x, TestEvent::TeAbandoned(_));
+        on_test_event(&x, &mut st, &mut *out)
+    })?;
     st.exec_time = start_time.map(|t| TestSuiteExecTime(t.elapsed()));
 
-    assert!(opts.fail_fast || st.current_test_count() == st.total);
+    assert!(opts.fail_fast || abandoned || st.current_test_count() == st.total);
+
+    let success = out.write_run_finish(&st)?;
+
+    // Failing to record the outcomes doesn't change the outcome of the run.
+    if let (Some(dir), Some(known_tests)) = (&opts.failure_cache, known_tests) {
+        let path = cache_file(dir);
+        if let Err(e) = update_failure_cache(&path, &st, &known_tests) {
+            eprintln!("warning: failed to write the failure cache to {}: {e}", path.display());
+        }
+    }
+
+    Ok(success)
+}
+
+/// Records the outcomes of the tests that were run in the failure cache. Tests that weren't run
+/// this time keep their previous outcome.
+fn update_failure_cache(
+    path: &Path,
+    st: &ConsoleTestState,
+    known_tests: &HashSet<String>,
+) -> io::Result<()> {
+    // A cache that can't be read is simply started over.
+    let mut outcomes = read_cache(path).unwrap_or_default();
+    outcomes.retain(|name, _| known_tests.contains(name));
+
+    let results = [
+        (&st.not_failures, CachedOutcome::Passed),
+        (&st.failures, CachedOutcome::Failed),
+        (&st.time_failures, CachedOutcome::Failed),
+        (&st.flakes, CachedOutcome::Flaky),
+    ];
+    for (tests, outcome) in results {
+        for (desc, _) in tests {
+            outcomes.insert(desc.name.as_slice().to_string(), outcome);
+        }
+    }
 
-    out.write_run_finish(&st)
+    write_cache(path, &outcomes)
 }
 
 // Calculates padding for given test description.
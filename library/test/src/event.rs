@@ -33,4 +33,7 @@ pub enum TestEvent {
     TeResult(CompletedTest),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
+    /// An in-process test hung past `--test-timeout` and was left running. Its result has
+    /// already been reported, and no further tests are started.
+    TeAbandoned(TestDesc),
 }
@@ -146,6 +146,15 @@ fn write_result(
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrFlaky(attempts) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""attempts": {attempts}"#)),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
@@ -202,6 +211,8 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let ignored = state.ignored;
         let measured = state.measured;
         let filtered_out = state.filtered_out;
+        let flaky_json =
+            if state.flaky > 0 { format!(r#", "flaky": {}"#, state.flaky) } else { String::new() };
         let exec_time_json = if let Some(ref exec_time) = state.exec_time {
             format!(r#", "exec_time": {}"#, exec_time.0.as_secs_f64())
         } else {
@@ -210,7 +221,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{flaky_json}{exec_time_json} }}{newline}"#
         ))?;
 
         Ok(state.failed == 0)
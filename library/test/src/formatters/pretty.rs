@@ -55,6 +55,13 @@ pub fn write_time_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub fn write_flaky(&mut self, attempts: usize) -> io::Result<()> {
+        self.write_short_result(
+            &format!("flaky (passed on attempt {attempts})"),
+            term::color::YELLOW,
+        )
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -166,6 +173,10 @@ pub fn write_time_failures(&mut self, state: &ConsoleTestState) -> io::Result<()
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub fn write_flakes(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flakes, "flaky tests")
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -249,6 +260,7 @@ fn write_result(
                 self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrFlaky(attempts) => self.write_flaky(attempts)?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -277,6 +289,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                 self.write_time_failures(state)?;
             }
         }
+        if !state.flakes.is_empty() {
+            self.write_flakes(state)?;
+        }
 
         self.write_plain("\ntest result: ")?;
 
@@ -294,6 +309,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.write_plain(s)?;
 
+        if state.flaky > 0 {
+            self.write_plain(format!("; {} flaky", state.flaky))?;
+        }
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!("; finished in {exec_time}");
             self.write_plain(time_str)?;
@@ -0,0 +1,229 @@
+use std::fmt::Write as _;
+use std::io;
+use std::io::prelude::Write;
+
+use super::OutputFormatter;
+use crate::bench::fmt_bench_samples;
+use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::test_result::TestResult;
+use crate::time;
+use crate::types::TestDesc;
+
+/// Writes the test results as a [TAP version 13](https://testanything.org/tap-version-13-specification.html)
+/// stream.
+pub(crate) struct TapFormatter<T> {
+    out: OutputLocation<T>,
+    /// Number of the last test point that was written.
+    test_number: usize,
+}
+
+impl<T: Write> TapFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out, test_number: 0 }
+    }
+
+    fn write_message(&mut self, s: &str) -> io::Result<()> {
+        // Every test point is written with a single call, so that it can't be torn apart by
+        // output of tests running with `--nocapture`.
+        assert_eq!(s.chars().last(), Some('\n'));
+
+        self.out.write_all(s.as_ref())?;
+        self.out.flush()
+    }
+}
+
+impl<T: Write> OutputFormatter for TapFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_test_discovered(&mut self, _desc: &TestDesc, _test_type: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_discovery_finish(&mut self, _state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        let mut s = format!("TAP version 13\n1..{test_count}\n");
+        if let Some(shuffle_seed) = shuffle_seed {
+            writeln!(s, "# shuffle seed: {shuffle_seed}").unwrap();
+        }
+        self.write_message(&s)
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.write_message(&format!(
+            "# test {} has been running for over {} seconds\n",
+            desc.name,
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        self.test_number += 1;
+        let name = EscapedDescription(desc.name.as_slice());
+
+        let mut diagnostics = Vec::new();
+        let (status, directive) = match *result {
+            TestResult::TrOk => ("ok", None),
+            TestResult::TrIgnored => match desc.ignore_message {
+                Some(m) => ("ok", Some(format!(" # SKIP {}", EscapedDescription(m)))),
+                None => ("ok", Some(String::from(" # SKIP"))),
+            },
+            TestResult::TrFailed => ("not ok", None),
+            TestResult::TrFailedMsg(ref m) => {
+                diagnostics.push(("message", yaml_quoted(m)));
+                ("not ok", None)
+            }
+            TestResult::TrTimedFail => {
+                diagnostics.push(("message", yaml_quoted("time limit exceeded")));
+                ("not ok", None)
+            }
+            TestResult::TrFlaky(attempts) => {
+                diagnostics.push(("attempts", attempts.to_string()));
+                ("ok", None)
+            }
+            TestResult::TrBench(ref bs) => {
+                diagnostics.push(("bench", yaml_quoted(&fmt_bench_samples(bs))));
+                ("ok", None)
+            }
+        };
+
+        if let Some(exec_time) = exec_time {
+            diagnostics.push(("duration_ms", (exec_time.0.as_secs_f64() * 1000.0).to_string()));
+        }
+
+        // Like in the other formats, the output of passing tests is only shown on request.
+        let show_output = match *result {
+            TestResult::TrOk => state.options.display_output,
+            TestResult::TrIgnored | TestResult::TrBench(_) => false,
+            _ => true,
+        };
+        if show_output && !stdout.is_empty() {
+            diagnostics.push(("stdout", yaml_block(&String::from_utf8_lossy(stdout))));
+        }
+
+        let mut s = format!("{status} {} - {name}", self.test_number);
+        if let Some(directive) = directive {
+            s.push_str(&directive);
+        }
+        s.push('\n');
+        if !diagnostics.is_empty() {
+            s.push_str("  ---\n");
+            for (key, value) in diagnostics {
+                writeln!(s, "  {key}: {value}").unwrap();
+            }
+            s.push_str("  ...\n");
+        }
+        self.write_message(&s)
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let mut s = format!(
+            "# test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            if state.failed == 0 { "ok" } else { "FAILED" },
+            state.passed,
+            state.failed,
+            state.ignored,
+            state.measured,
+            state.filtered_out,
+        );
+        if state.flaky > 0 {
+            write!(s, "; {} flaky", state.flaky).unwrap();
+        }
+        if let Some(ref exec_time) = state.exec_time {
+            write!(s, "; finished in {exec_time}").unwrap();
+        }
+        s.push('\n');
+        self.write_message(&s)?;
+
+        Ok(state.failed == 0)
+    }
+}
+
+/// A wrapper around a test name that escapes the characters that have a special meaning in the
+/// description of a TAP test point.
+struct EscapedDescription<'a>(&'a str);
+
+impl std::fmt::Display for EscapedDescription<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                // A `#` would start a directive, and a newline would end the test point.
+                '\\' => f.write_str("\\\\")?,
+                '#' => f.write_str("\\#")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats a string as a double-quoted YAML scalar.
+fn yaml_quoted(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Formats a string as a YAML literal block scalar, indented to be the value of a top-level key
+/// of a TAP YAML block. Falls back to a quoted scalar for strings that a literal block can't
+/// represent faithfully.
+fn yaml_block(s: &str) -> String {
+    // The indentation of a literal block is taken from its first non-empty line.
+    let first_line = s.trim_start_matches('\n');
+    let representable = !first_line.is_empty()
+        && !first_line.starts_with(' ')
+        && !s.chars().any(|c| c.is_control() && c != '\n' && c != '\t');
+    if !representable {
+        return yaml_quoted(s);
+    }
+
+    // The chomping indicator tells how many of the trailing newlines belong to the value.
+    let body = s.trim_end_matches('\n');
+    let chomping = match s.len() - body.len() {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    };
+    let mut block = format!("|{chomping}\n");
+    for line in s.split_inclusive('\n') {
+        if line != "\n" {
+            block.push_str("    ");
+        }
+        block.push_str(line);
+    }
+    // The caller terminates the value with a newline.
+    if block.ends_with('\n') {
+        block.pop();
+    }
+    block
+}
@@ -47,6 +47,14 @@ pub fn write_ok(&mut self) -> io::Result<()> {
     }
 
     pub fn write_failed(&mut self, name: &str) -> io::Result<()> {
+        self.write_failed_with(name, "FAILED")
+    }
+
+    pub fn write_time_failed(&mut self, name: &str) -> io::Result<()> {
+        self.write_failed_with(name, "FAILED (time limit exceeded)")
+    }
+
+    fn write_failed_with(&mut self, name: &str, result: &str) -> io::Result<()> {
         // Put failed tests on their own line and include the test name, so that it's faster
         // to see which test failed without having to wait for them all to run.
 
@@ -58,10 +66,14 @@ pub fn write_failed(&mut self, name: &str) -> io::Result<()> {
         }
         self.test_count += 1;
         self.write_plain(format!("{name} --- "))?;
-        self.write_pretty("FAILED", term::color::RED)?;
+        self.write_pretty(result, term::color::RED)?;
         self.write_plain("\n")
     }
 
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("f", term::color::YELLOW)
+    }
+
     pub fn write_ignored(&mut self) -> io::Result<()> {
         self.write_short_result("i", term::color::YELLOW)
     }
@@ -147,10 +159,27 @@ pub fn write_outputs(&mut self, state: &ConsoleTestState) -> io::Result<()> {
     }
 
     pub fn write_failures(&mut self, state: &ConsoleTestState) -> io::Result<()> {
-        self.write_plain("\nfailures:\n")?;
+        self.write_results(&state.failures, "failures")
+    }
+
+    pub fn write_time_failures(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.time_failures, "failures (time limit exceeded)")
+    }
+
+    pub fn write_flakes(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flakes, "flaky tests")
+    }
+
+    fn write_results(
+        &mut self,
+        inputs: &[(TestDesc, Vec<u8>)],
+        results_type: &str,
+    ) -> io::Result<()> {
+        let results_out_str = format!("\n{results_type}:\n");
+        self.write_plain(&results_out_str)?;
         let mut failures = Vec::new();
         let mut fail_out = String::new();
-        for (f, stdout) in &state.failures {
+        for (f, stdout) in inputs {
             failures.push(f.name.to_string());
             if !stdout.is_empty() {
                 fail_out.push_str(&format!("---- {} stdout ----\n", f.name));
@@ -164,7 +193,7 @@ pub fn write_failures(&mut self, state: &ConsoleTestState) -> io::Result<()> {
             self.write_plain(&fail_out)?;
         }
 
-        self.write_plain("\nfailures:\n")?;
+        self.write_plain(&results_out_str)?;
         failures.sort();
         for name in &failures {
             self.write_plain(&format!("    {name}\n"))?;
@@ -230,9 +259,11 @@ fn write_result(
     ) -> io::Result<()> {
         match *result {
             TestResult::TrOk => self.write_ok(),
-            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
+            TestResult::TrFailed | TestResult::TrFailedMsg(_) => {
                 self.write_failed(desc.name.as_slice())
             }
+            TestResult::TrTimedFail => self.write_time_failed(desc.name.as_slice()),
+            TestResult::TrFlaky(_) => self.write_flaky(),
             TestResult::TrIgnored => self.write_ignored(),
             TestResult::TrBench(ref bs) => {
                 if self.is_multithreaded {
@@ -258,7 +289,16 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         }
         let success = state.failed == 0;
         if !success {
-            self.write_failures(state)?;
+            if !state.failures.is_empty() {
+                self.write_failures(state)?;
+            }
+
+            if !state.time_failures.is_empty() {
+                self.write_time_failures(state)?;
+            }
+        }
+        if !state.flakes.is_empty() {
+            self.write_flakes(state)?;
         }
 
         self.write_plain("\ntest result: ")?;
@@ -277,6 +317,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.write_plain(s)?;
 
+        if state.flaky > 0 {
+            self.write_plain(format!("; {} flaky", state.flaky))?;
+        }
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!("; finished in {exec_time}");
             self.write_plain(time_str)?;
@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+/// Outcome of a test in the last run that executed it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CachedOutcome {
+    Passed,
+    Failed,
+    Flaky,
+}
+
+impl CachedOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            CachedOutcome::Passed => "ok",
+            CachedOutcome::Failed => "failed",
+            CachedOutcome::Flaky => "flaky",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<CachedOutcome> {
+        match s {
+            "ok" => Some(CachedOutcome::Passed),
+            "failed" => Some(CachedOutcome::Failed),
+            "flaky" => Some(CachedOutcome::Flaky),
+            _ => None,
+        }
+    }
+}
+
+/// Which of the tests that failed in the previous run to select.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LastFailed {
+    /// Names of the tests that failed the last time they were run.
+    pub names: HashSet<String>,
+    /// Run only these tests, instead of running them before all others.
+    pub only: bool,
+}
+
+/// Returns the file in the cache directory that records the outcomes of this test binary.
+///
+/// Every test binary gets its own file, so that test binaries that are run one after another
+/// (like the unit tests and integration tests of a crate) don't overwrite each other's results.
+pub fn cache_file(dir: &Path) -> PathBuf {
+    let binary = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|stem| stem.to_os_string()))
+        .unwrap_or_else(|| "tests".into());
+    dir.join(binary)
+}
+
+/// Reads the outcomes recorded in a cache file. A missing file is treated as empty.
+///
+/// Every line consists of an outcome (`ok`, `failed` or `flaky`) followed by the test name,
+/// separated by a space.
+pub fn read_cache(path: &Path) -> io::Result<BTreeMap<String, CachedOutcome>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e),
+    };
+    let mut outcomes = BTreeMap::new();
+    for (line_no, line) in contents.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let entry = line
+            .split_once(' ')
+            .and_then(|(outcome, name)| Some((CachedOutcome::from_str(outcome)?, name)));
+        let Some((outcome, name)) = entry else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: expected `<outcome> <test name>`", path.display(), line_no + 1),
+            ));
+        };
+        outcomes.insert(name.to_string(), outcome);
+    }
+    Ok(outcomes)
+}
+
+/// Writes the outcomes to a cache file, creating its directory if needed.
+pub fn write_cache(path: &Path, outcomes: &BTreeMap<String, CachedOutcome>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut contents = String::new();
+    for (name, outcome) in outcomes {
+        contents.push_str(outcome.as_str());
+        contents.push(' ');
+        contents.push_str(name);
+        contents.push('\n');
+    }
+    fs::write(path, contents)
+}
+
+/// Returns the names of the tests that failed the last time they were run.
+pub fn failed_tests(outcomes: &BTreeMap<String, CachedOutcome>) -> HashSet<String> {
+    outcomes
+        .iter()
+        .filter(|&(_, &outcome)| outcome == CachedOutcome::Failed)
+        .map(|(name, _)| name.clone())
+        .collect()
+}
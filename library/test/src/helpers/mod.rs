@@ -2,5 +2,7 @@
 //! but used in `libtest`.
 
 pub mod concurrency;
+pub mod failure_cache;
 pub mod metrics;
+pub mod shard;
 pub mod shuffle;
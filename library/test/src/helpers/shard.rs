@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::types::TestDescAndFn;
+
+/// Selects one of several disjoint parts of the test set, so that a test suite
+/// can be split across multiple machines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestShard {
+    /// Zero-based index of the shard to run.
+    pub index: usize,
+    /// Total number of shards the test set is split into.
+    pub count: usize,
+    /// Previously recorded execution times, used to balance the shards.
+    pub durations: Option<HashMap<String, Duration>>,
+}
+
+impl TestShard {
+    pub fn new(index: usize, count: usize) -> TestShard {
+        TestShard { index, count, durations: None }
+    }
+
+    pub fn with_durations(mut self, durations: HashMap<String, Duration>) -> TestShard {
+        self.durations = Some(durations);
+        self
+    }
+}
+
+/// Reads a file with previously recorded test durations.
+///
+/// Every non-empty line consists of a test name followed by its execution time
+/// in seconds, separated by whitespace, e.g. `module::tests::slow_test 12.5`.
+pub fn read_durations(path: &Path) -> io::Result<HashMap<String, Duration>> {
+    let mut durations = HashMap::new();
+    for (line_no, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: expected `<test name> <seconds>`", path.display(), line_no + 1),
+            )
+        };
+        let (name, secs) = line.rsplit_once(char::is_whitespace).ok_or_else(invalid)?;
+        let secs = secs.parse::<f64>().map_err(|_| invalid())?;
+        let duration = Duration::try_from_secs_f64(secs).map_err(|_| invalid())?;
+        durations.insert(name.trim_end().to_string(), duration);
+    }
+    Ok(durations)
+}
+
+/// Keeps only the tests belonging to the given shard.
+///
+/// The assignment only depends on the test names (and the recorded durations,
+/// if any), never on the order in which the tests were passed in, so every
+/// machine computes the same partition of the test set.
+pub fn shard_tests(shard: &TestShard, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
+    let mut order: Vec<usize> = (0..tests.len()).collect();
+    order.sort_by(|&a, &b| tests[a].desc.name.as_slice().cmp(tests[b].desc.name.as_slice()));
+
+    let mut selected = vec![false; tests.len()];
+    match shard.durations {
+        None => {
+            for (position, &i) in order.iter().enumerate() {
+                selected[i] = position % shard.count == shard.index;
+            }
+        }
+        Some(ref durations) => {
+            // Tests that have no recorded duration (e.g. because they were
+            // added since) are assumed to take the average time.
+            let recorded = order
+                .iter()
+                .filter_map(|&i| durations.get(tests[i].desc.name.as_slice()))
+                .collect::<Vec<_>>();
+            let default = match recorded.len() {
+                0 => Duration::from_secs(1),
+                len => recorded.iter().copied().sum::<Duration>() / len as u32,
+            };
+            let weights = tests
+                .iter()
+                .map(|test| durations.get(test.desc.name.as_slice()).copied().unwrap_or(default))
+                .collect::<Vec<_>>();
+
+            // Greedily hand out the longest tests first, each one to the
+            // shard that currently has the least work. The sort is stable,
+            // so tests with equal weights stay in name order.
+            order.sort_by(|&a, &b| weights[b].cmp(&weights[a]));
+            let mut loads = vec![Duration::ZERO; shard.count];
+            for i in order {
+                let (target, _) = loads.iter().enumerate().min_by_key(|&(_, load)| *load).unwrap();
+                loads[target] += weights[i];
+                selected[i] = target == shard.index;
+            }
+        }
+    }
+
+    tests
+        .into_iter()
+        .zip(selected)
+        .filter_map(|(test, selected)| selected.then_some(test))
+        .collect()
+}
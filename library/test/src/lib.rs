@@ -41,7 +41,9 @@
 pub mod test {
     pub use crate::bench::Bencher;
     pub use crate::cli::{TestOpts, parse_opts};
+    pub use crate::helpers::failure_cache::LastFailed;
     pub use crate::helpers::metrics::{Metric, MetricMap};
+    pub use crate::helpers::shard::TestShard;
     pub use crate::options::{Options, RunIgnored, RunStrategy, ShouldPanic};
     pub use crate::test_result::{TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk};
     pub use crate::time::{TestExecTime, TestTimeOptions};
@@ -52,12 +54,12 @@ pub mod test {
     pub use crate::{assert_test_result, filter_tests, run_test, test_main, test_main_static};
 }
 
-use std::collections::VecDeque;
-use std::io::prelude::Write;
+use std::collections::{HashMap, VecDeque};
+use std::io::prelude::{Read, Write};
 use std::mem::ManuallyDrop;
 use std::panic::{self, AssertUnwindSafe, PanicHookInfo, catch_unwind};
 use std::process::{self, Command, Termination};
-use std::sync::mpsc::{Sender, channel};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::{env, io, thread};
@@ -82,6 +84,7 @@ pub mod test {
 
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
+use helpers::shard::shard_tests;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::RunStrategy;
 use test_result::*;
@@ -265,6 +268,68 @@ fn total_len(&self) -> usize {
     }
 }
 
+/// Bookkeeping for `--retry`. Every attempt to run a test gets a fresh `TestId`, so that the
+/// timeouts of an earlier attempt don't apply to the next one.
+struct Retries {
+    max_retries: usize,
+    next_id: usize,
+    /// Copies of the running tests that can still be retried.
+    copies: HashMap<TestId, TestDescAndFn>,
+    /// Attempt number and output of the earlier attempts of retried tests.
+    attempts: HashMap<TestId, (usize, Vec<u8>)>,
+}
+
+impl Retries {
+    fn new(max_retries: usize, next_id: usize) -> Retries {
+        Retries { max_retries, next_id, copies: HashMap::new(), attempts: HashMap::new() }
+    }
+
+    fn is_retry(&self, id: TestId) -> bool {
+        self.attempts.contains_key(&id)
+    }
+
+    fn register(&mut self, id: TestId, test: &TestDescAndFn) {
+        let attempt = self.attempts.get(&id).map_or(1, |&(attempt, _)| attempt);
+        if attempt <= self.max_retries {
+            if let Some(testfn) = test.testfn.try_clone() {
+                self.copies.insert(id, TestDescAndFn { desc: test.desc.clone(), testfn });
+            }
+        }
+    }
+
+    /// Returns the test to run next if the completed test failed and can be retried.
+    /// Otherwise, a test that only passed on a later attempt is marked as flaky, and
+    /// the output of all attempts is combined.
+    fn retry(&mut self, completed_test: &mut CompletedTest) -> Option<(TestId, TestDescAndFn)> {
+        if self.max_retries == 0 {
+            return None;
+        }
+        let copy = self.copies.remove(&completed_test.id);
+        let (attempt, mut output) =
+            self.attempts.remove(&completed_test.id).unwrap_or((1, Vec::new()));
+        if attempt > 1 {
+            writeln!(output, "---- {} attempt {attempt} ----", completed_test.desc.name).unwrap();
+        }
+        output.extend_from_slice(&completed_test.stdout);
+
+        match completed_test.result {
+            TrFailed | TrFailedMsg(_) | TrTimedFail if copy.is_some() => {
+                if let TrFailedMsg(ref msg) = completed_test.result {
+                    writeln!(output, "note: {msg}").unwrap();
+                }
+                let id = TestId(self.next_id);
+                self.next_id += 1;
+                self.attempts.insert(id, (attempt + 1, output));
+                return copy.map(|test| (id, test));
+            }
+            TrOk if attempt > 1 => completed_test.result = TrFlaky(attempt),
+            _ => {}
+        }
+        completed_test.stdout = output;
+        None
+    }
+}
+
 pub fn run_tests<F>(
     opts: &TestOpts,
     tests: Vec<TestDescAndFn>,
@@ -273,9 +338,7 @@ pub fn run_tests<F>(
 where
     F: FnMut(TestEvent) -> io::Result<()>,
 {
-    use std::collections::HashMap;
     use std::hash::{BuildHasherDefault, DefaultHasher};
-    use std::sync::mpsc::RecvTimeoutError;
 
     struct RunningTest {
         join_handle: Option<thread::JoinHandle<()>>,
@@ -337,10 +400,15 @@ struct TimeoutEntry {
 
     let concurrency = opts.test_threads.unwrap_or_else(get_concurrency);
 
+    let mut retries = Retries::new(opts.retries, filtered.next_id);
     let mut remaining = filtered.tests;
     if let Some(shuffle_seed) = shuffle_seed {
         shuffle_tests(shuffle_seed, &mut remaining);
     }
+    if let Some(ref last_failed) = opts.last_failed {
+        // The sort is stable, so both groups keep their order.
+        remaining.sort_by_key(|(_, test)| !last_failed.names.contains(test.desc.name.as_slice()));
+    }
     // Store the tests in a VecDeque so we can efficiently remove the first element to run the
     // tests in the order they were passed (unless shuffled).
     let mut remaining = VecDeque::from(remaining);
@@ -353,13 +421,23 @@ struct TimeoutEntry {
         RunStrategy::InProcess
     };
 
+    // Tests running in a child process are killed by `spawn_test_subprocess` once they exceed
+    // the time limit. Tests running in-process can't be interrupted, so instead they are reported
+    // as timed out here, and no further tests are started.
+    let in_process_timeout = match run_strategy {
+        RunStrategy::InProcess => opts.test_timeout,
+        RunStrategy::SpawnPrimary => None,
+    };
+    let hung_test_exec_time = |timeout| opts.time_options.map(|_| TestExecTime(timeout));
+
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
+    let mut hung_test_queue: VecDeque<TimeoutEntry> = VecDeque::new();
 
     fn get_timed_out_tests(
         running_tests: &TestMap,
         timeout_queue: &mut VecDeque<TimeoutEntry>,
-    ) -> Vec<TestDesc> {
+    ) -> Vec<TimeoutEntry> {
         let now = Instant::now();
         let mut timed_out = Vec::new();
         while let Some(timeout_entry) = timeout_queue.front() {
@@ -368,7 +446,7 @@ fn get_timed_out_tests(
             }
             let timeout_entry = timeout_queue.pop_front().unwrap();
             if running_tests.contains_key(&timeout_entry.id) {
-                timed_out.push(timeout_entry.desc);
+                timed_out.push(timeout_entry);
             }
         }
         timed_out
@@ -384,15 +462,44 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
     if concurrency == 1 {
         while !remaining.is_empty() {
             let (id, test) = remaining.pop_front().unwrap();
-            let event = TestEvent::TeWait(test.desc.clone());
-            notify_about_test_event(event)?;
+            let desc = test.desc.clone();
+            if !retries.is_retry(id) {
+                let event = TestEvent::TeWait(desc.clone());
+                notify_about_test_event(event)?;
+            }
+            retries.register(id, &test);
             let join_handle = run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
             // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
+            let mut completed_test = match in_process_timeout {
+                Some(timeout) => match rx.recv_timeout(timeout) {
+                    Ok(completed_test) => completed_test,
+                    Err(RecvTimeoutError::Timeout) => {
+                        let exec_time = hung_test_exec_time(timeout);
+                        let completed_test = CompletedTest::new(
+                            id,
+                            desc.clone(),
+                            TrTimedFail,
+                            exec_time,
+                            Vec::new(),
+                        );
+                        notify_about_test_event(TestEvent::TeResult(completed_test))?;
+                        notify_about_test_event(TestEvent::TeAbandoned(desc))?;
+                        detach_running_tests(rx);
+                        return Ok(());
+                    }
+                    Err(RecvTimeoutError::Disconnected) => panic!("test result channel closed"),
+                },
+                None => rx.recv().unwrap(),
+            };
             RunningTest { join_handle }.join(&mut completed_test);
 
+            if let Some(retry) = retries.retry(&mut completed_test) {
+                remaining.push_front(retry);
+                continue;
+            }
+
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
+                TrIgnored | TrOk | TrFlaky(_) | TrBench(_) => false,
                 TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
             };
 
@@ -404,36 +511,52 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             }
         }
     } else {
-        while pending > 0 || !remaining.is_empty() {
-            while pending < concurrency && !remaining.is_empty() {
+        // Set once an in-process test exceeded the time limit.
+        let mut hung = false;
+
+        while pending > 0 || (!hung && !remaining.is_empty()) {
+            while !hung && pending < concurrency && !remaining.is_empty() {
                 let (id, test) = remaining.pop_front().unwrap();
                 let timeout = time::get_default_test_timeout();
                 let desc = test.desc.clone();
 
-                let event = TestEvent::TeWait(desc.clone());
-                notify_about_test_event(event)?; //here no pad
+                if !retries.is_retry(id) {
+                    let event = TestEvent::TeWait(desc.clone());
+                    notify_about_test_event(event)?; //here no pad
+                }
+                retries.register(id, &test);
                 let join_handle =
                     run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
                 running_tests.insert(id, RunningTest { join_handle });
+                if let Some(test_timeout) = in_process_timeout {
+                    let timeout = Instant::now() + test_timeout;
+                    hung_test_queue.push_back(TimeoutEntry { id, desc: desc.clone(), timeout });
+                }
                 timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
                 pending += 1;
             }
 
             let mut res;
+            let mut hung_tests = Vec::new();
             loop {
-                if let Some(timeout) = calc_timeout(&timeout_queue) {
+                let next_timeout = calc_timeout(&timeout_queue)
+                    .into_iter()
+                    .chain(calc_timeout(&hung_test_queue))
+                    .min();
+                if let Some(timeout) = next_timeout {
                     res = rx.recv_timeout(timeout);
                     for test in get_timed_out_tests(&running_tests, &mut timeout_queue) {
-                        let event = TestEvent::TeTimeout(test);
+                        let event = TestEvent::TeTimeout(test.desc);
                         notify_about_test_event(event)?;
                     }
+                    hung_tests = get_timed_out_tests(&running_tests, &mut hung_test_queue);
 
                     match res {
-                        Err(RecvTimeoutError::Timeout) => {
+                        Err(RecvTimeoutError::Timeout) if hung_tests.is_empty() => {
                             // Result is not yet ready, continue waiting.
                         }
                         _ => {
-                            // We've got a result, stop the loop.
+                            // We've got a result or a hung test, stop the loop.
                             break;
                         }
                     }
@@ -443,12 +566,41 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 }
             }
 
-            let mut completed_test = res.unwrap();
-            let running_test = running_tests.remove(&completed_test.id).unwrap();
+            for TimeoutEntry { id, desc, .. } in hung_tests {
+                // Leave the thread of the hung test behind, its result is reported right away.
+                running_tests.remove(&id);
+                hung = true;
+                pending -= 1;
+
+                let exec_time = hung_test_exec_time(in_process_timeout.unwrap());
+                let completed_test =
+                    CompletedTest::new(id, desc.clone(), TrTimedFail, exec_time, Vec::new());
+                notify_about_test_event(TestEvent::TeResult(completed_test))?;
+                notify_about_test_event(TestEvent::TeAbandoned(desc))?;
+            }
+            if hung && opts.fail_fast {
+                detach_running_tests(rx);
+                return Ok(());
+            }
+
+            let mut completed_test = match res {
+                Err(RecvTimeoutError::Timeout) => continue,
+                res => res.unwrap(),
+            };
+            let Some(running_test) = running_tests.remove(&completed_test.id) else {
+                // This test has already been reported as hung.
+                continue;
+            };
             running_test.join(&mut completed_test);
 
+            if let Some(retry) = retries.retry(&mut completed_test) {
+                remaining.push_front(retry);
+                pending -= 1;
+                continue;
+            }
+
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
+                TrIgnored | TrOk | TrFlaky(_) | TrBench(_) => false,
                 TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
             };
 
@@ -462,6 +614,11 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 return Ok(());
             }
         }
+
+        if hung {
+            detach_running_tests(rx);
+            return Ok(());
+        }
     }
 
     if opts.bench_benchmarks {
@@ -481,6 +638,18 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
     Ok(())
 }
 
+/// Stops waiting for the tests that are still running in-process after one of them hung, and
+/// leaves their threads behind.
+///
+/// Those threads send their result through `rx` when their test finishes, and they panic if the
+/// receiver is gone. The receiver is deliberately leaked so that the channel stays open for the
+/// rest of the process. This is sound: leaking never causes undefined behavior, the results
+/// sent to the leaked channel are simply never read, and the memory is reclaimed when the process
+/// exits after the run.
+fn detach_running_tests(rx: Receiver<CompletedTest>) {
+    std::mem::forget(rx);
+}
+
 pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     let mut filtered = tests;
     let matches_filter = |test: &TestDescAndFn, filter: &str| {
@@ -519,6 +688,18 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         RunIgnored::No => {}
     }
 
+    // Only keep the tests that failed in the previous run
+    if let Some(ref last_failed) = opts.last_failed {
+        if last_failed.only {
+            filtered.retain(|test| last_failed.names.contains(test.desc.name.as_slice()));
+        }
+    }
+
+    // Only keep the tests assigned to the requested shard
+    if let Some(ref shard) = opts.shard {
+        filtered = shard_tests(shard, filtered);
+    }
+
     filtered
 }
 
@@ -570,7 +751,12 @@ pub fn run_test(
             let name = desc.name.clone();
             let nocapture = opts.nocapture;
             let time_options = opts.time_options;
-            let bench_benchmarks = opts.bench_benchmarks;
+            let subprocess_opts = SubprocessOpts {
+                nocapture,
+                time_opts: time_options,
+                test_timeout: opts.test_timeout,
+                bench_benchmarks: opts.bench_benchmarks,
+            };
 
             let runtest = move || match strategy {
                 RunStrategy::InProcess => run_test_in_process(
@@ -582,15 +768,9 @@ pub fn run_test(
                     monitor_ch,
                     time_options,
                 ),
-                RunStrategy::SpawnPrimary => spawn_test_subprocess(
-                    id,
-                    desc,
-                    nocapture,
-                    time_options.is_some(),
-                    monitor_ch,
-                    time_options,
-                    bench_benchmarks,
-                ),
+                RunStrategy::SpawnPrimary => {
+                    spawn_test_subprocess(id, desc, monitor_ch, subprocess_opts)
+                }
             };
 
             // If the platform is single-threaded we're just going to run
@@ -682,15 +862,22 @@ fn fold_err<T, E>(
     }
 }
 
+/// The options of the run that affect how a test is run in a child process.
+struct SubprocessOpts {
+    nocapture: bool,
+    time_opts: Option<time::TestTimeOptions>,
+    test_timeout: Option<Duration>,
+    bench_benchmarks: bool,
+}
+
 fn spawn_test_subprocess(
     id: TestId,
     desc: TestDesc,
-    nocapture: bool,
-    report_time: bool,
     monitor_ch: Sender<CompletedTest>,
-    time_opts: Option<time::TestTimeOptions>,
-    bench_benchmarks: bool,
+    opts: SubprocessOpts,
 ) {
+    let SubprocessOpts { nocapture, time_opts, test_timeout, bench_benchmarks } = opts;
+    let report_time = time_opts.is_some();
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
         let current_exe = &args[0];
@@ -706,7 +893,11 @@ fn spawn_test_subprocess(
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match test_timeout {
+            Some(timeout) => output_with_timeout(&mut command, !nocapture, timeout),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -723,8 +914,11 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
-        let result =
-            get_result_from_exit_code(&desc, status, time_opts.as_ref(), exec_time.as_ref());
+        let result = if timed_out {
+            TrTimedFail
+        } else {
+            get_result_from_exit_code(&desc, status, time_opts.as_ref(), exec_time.as_ref())
+        };
         (result, test_output, exec_time)
     })();
 
@@ -732,6 +926,65 @@ fn spawn_test_subprocess(
     monitor_ch.send(message).unwrap();
 }
 
+/// Runs `command` to completion like `Command::output`, but kills the child process once it
+/// runs for longer than `timeout`. The returned flag tells whether the child had to be killed.
+fn output_with_timeout(
+    command: &mut Command,
+    capture: bool,
+    timeout: Duration,
+) -> io::Result<(process::Output, bool)> {
+    fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> Option<Receiver<Vec<u8>>> {
+        pipe.map(|mut pipe| {
+            let (tx, rx) = channel();
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = pipe.read_to_end(&mut buf);
+                let _ = tx.send(buf);
+            });
+            rx
+        })
+    }
+
+    command.stdin(process::Stdio::null());
+    if capture {
+        command.stdout(process::Stdio::piped());
+        command.stderr(process::Stdio::piped());
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut child = command.spawn()?;
+    // Drain the pipes while waiting, so that the child can't block on a full pipe.
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let mut poll_interval = Duration::from_millis(1);
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            timed_out = true;
+            // The child might have exited in the meantime, in which case this fails harmlessly.
+            let _ = child.kill();
+            break child.wait()?;
+        }
+        thread::sleep(poll_interval.min(deadline - now));
+        poll_interval = (poll_interval * 2).min(Duration::from_millis(50));
+    };
+
+    // Processes spawned by a killed test may still hold on to the pipes, so don't wait for
+    // them to be closed indefinitely.
+    let collect = |pipe: Option<Receiver<Vec<u8>>>| match pipe {
+        Some(pipe) if timed_out => pipe.recv_timeout(Duration::from_secs(1)).unwrap_or_default(),
+        Some(pipe) => pipe.recv().unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let output = process::Output { status, stdout: collect(stdout), stderr: collect(stderr) };
+    Ok((output, timed_out))
+}
+
 fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -> ! {
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicHookInfo<'_>>| {
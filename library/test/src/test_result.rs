@@ -35,6 +35,9 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test failed, but passed when it was run again. Holds the number of
+    /// attempts it took to pass.
+    TrFlaky(usize),
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
@@ -3,7 +3,9 @@
     console::OutputLocation,
     formatters::PrettyFormatter,
     test::{
+        LastFailed,
         MetricMap,
+        TestShard,
         // FIXME (introduced by #65251)
         // ShouldPanic, StaticTestName, TestDesc, TestDescAndFn, TestOpts, TestTimeOptions,
         // TestType, TrFailedMsg, TrIgnored, TrOk,
@@ -29,9 +31,14 @@ fn new() -> TestOpts {
             format: OutputFormat::Pretty,
             shuffle: false,
             shuffle_seed: None,
+            shard: None,
+            retries: 0,
+            failure_cache: None,
+            last_failed: None,
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
             options: Options::new(),
             fail_fast: false,
         }
@@ -402,6 +409,158 @@ fn test_error_on_exceed() {
     assert_eq!(result, TestResult::TrOk);
 }
 
+/// Runs `tests` as `run_tests` would, and returns the results in the order they were reported.
+fn run_test_list(opts: &TestOpts, tests: Vec<(&'static str, TestFn)>) -> Vec<CompletedTest> {
+    let tests = tests
+        .into_iter()
+        .map(|(name, testfn)| TestDescAndFn {
+            desc: TestDesc { name: StaticTestName(name), ..typed_test_desc(TestType::Unknown) },
+            testfn,
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    run_tests(opts, tests, |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push(completed_test);
+        }
+        Ok(())
+    })
+    .unwrap();
+    results
+}
+
+fn hung_test_template(test_threads: usize) -> Vec<(String, TestResult)> {
+    fn pass() -> Result<(), String> {
+        Ok(())
+    }
+    // The hung test blocks until the run is over and `release` is dropped, so that its thread
+    // doesn't outlive this test.
+    let (release, released) = channel::<()>();
+    let hung = DynTestFn(Box::new(move || {
+        let _ = released.recv();
+        Ok(())
+    }));
+    let tests = vec![("a", StaticTestFn(pass)), ("b", hung), ("c", StaticTestFn(pass))];
+    let test_opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(test_threads),
+        test_timeout: Some(Duration::from_millis(200)),
+        ..TestOpts::new()
+    };
+
+    let results = run_test_list(&test_opts, tests);
+    drop(release);
+    results.into_iter().map(|test| (test.desc.name.to_string(), test.result)).collect()
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_timeout_stops_run_in_process() {
+    // The test after the hung one is never started.
+    let results = hung_test_template(1);
+    assert_eq!(results, [("a".to_string(), TrOk), ("b".to_string(), TestResult::TrTimedFail)]);
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_timeout_waits_for_running_tests() {
+    let mut results = hung_test_template(2);
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        results,
+        [
+            ("a".to_string(), TrOk),
+            ("b".to_string(), TestResult::TrTimedFail),
+            ("c".to_string(), TrOk),
+        ]
+    );
+}
+
+#[test]
+fn parse_test_timeout() {
+    let args = |timeout: &str| {
+        vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            format!("--test-timeout={timeout}"),
+        ]
+    };
+
+    let opts = parse_opts(&args("2.5")).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_millis(2500)));
+
+    assert!(parse_opts(&args("0")).unwrap().is_err());
+    assert!(parse_opts(&args("-1")).unwrap().is_err());
+    assert!(parse_opts(&args("soon")).unwrap().is_err());
+}
+
+fn retry_test_template(test_threads: usize, testfn: TestFn) -> Vec<(TestResult, String)> {
+    let test_opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(test_threads),
+        retries: 2,
+        ..TestOpts::new()
+    };
+
+    run_test_list(&test_opts, vec![("whatever", testfn)])
+        .into_iter()
+        .map(|test| (test.result, String::from_utf8(test.stdout).unwrap()))
+        .collect()
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn retry_flaky_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+    fn flaky_0() -> Result<(), String> {
+        if ATTEMPTS[0].fetch_add(1, Ordering::SeqCst) == 0 { Err("first".into()) } else { Ok(()) }
+    }
+    fn flaky_1() -> Result<(), String> {
+        if ATTEMPTS[1].fetch_add(1, Ordering::SeqCst) == 0 { Err("first".into()) } else { Ok(()) }
+    }
+
+    for (test_threads, testfn) in [(1, flaky_0 as fn() -> _), (2, flaky_1)] {
+        let results = retry_test_template(test_threads, StaticTestFn(testfn));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, TestResult::TrFlaky(2));
+        assert!(results[0].1.contains("---- whatever attempt 2 ----"));
+    }
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn retry_failing_test() {
+    fn f() -> Result<(), String> {
+        Err("always".into())
+    }
+
+    for test_threads in [1, 2] {
+        let results = retry_test_template(test_threads, StaticTestFn(f));
+        assert_eq!(results.len(), 1);
+        assert_ne!(results[0].0, TrOk);
+        assert!(results[0].1.contains("---- whatever attempt 3 ----"));
+    }
+
+    // Dynamic tests can only be run once.
+    let results = retry_test_template(1, DynTestFn(Box::new(f)));
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].1, "");
+}
+
+#[test]
+fn parse_retry_option() {
+    let args =
+        vec!["progname".to_string(), "-Zunstable-options".to_string(), "--retry=3".to_string()];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.retries, 3);
+
+    let args = vec!["progname".to_string(), "--retry=3".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
 fn typed_test_desc(test_type: TestType) -> TestDesc {
     TestDesc {
         name: StaticTestName("whatever"),
@@ -731,6 +890,187 @@ pub fn order_depends_on_more_than_seed() {
     assert!(left.iter().zip(right).any(|(a, b)| a.0 != b.0));
 }
 
+#[test]
+pub fn shard_tests_partition_test_set() {
+    let count = 3;
+    let mut seen = Vec::new();
+    for index in 0..count {
+        let opts = TestOpts { shard: Some(TestShard::new(index, count)), ..TestOpts::new() };
+        let shard = filter_tests(&opts, sample_tests());
+        assert!(shard.len() >= sample_tests().len() / count);
+        seen.extend(shard.into_iter().map(|test| test.desc.name.to_string()));
+    }
+
+    let mut all =
+        sample_tests().into_iter().map(|test| test.desc.name.to_string()).collect::<Vec<_>>();
+    all.sort();
+    seen.sort();
+    assert_eq!(all, seen);
+}
+
+#[test]
+pub fn shard_tests_ignore_input_order() {
+    let opts = TestOpts { shard: Some(TestShard::new(1, 4)), ..TestOpts::new() };
+    let mut reversed = sample_tests();
+    reversed.reverse();
+
+    let mut left =
+        filter_tests(&opts, sample_tests()).into_iter().map(|t| t.desc.name).collect::<Vec<_>>();
+    let mut right =
+        filter_tests(&opts, reversed).into_iter().map(|t| t.desc.name).collect::<Vec<_>>();
+    left.sort_by(|a, b| a.as_slice().cmp(b.as_slice()));
+    right.sort_by(|a, b| a.as_slice().cmp(b.as_slice()));
+    assert_eq!(left, right);
+}
+
+#[test]
+pub fn shard_tests_with_durations() {
+    let durations = [("sha1::test", 10), ("isize::test_pow", 4), ("isize::test_to_str", 3)]
+        .into_iter()
+        .map(|(name, secs)| (name.to_string(), Duration::from_secs(secs)))
+        .collect::<std::collections::HashMap<_, _>>();
+    let shard = |index| TestShard::new(index, 2).with_durations(durations.clone());
+
+    // The slowest test is balanced out by a larger number of faster tests on
+    // the other shard. Tests without a recorded duration count as average.
+    let first =
+        filter_tests(&TestOpts { shard: Some(shard(0)), ..TestOpts::new() }, sample_tests());
+    let second =
+        filter_tests(&TestOpts { shard: Some(shard(1)), ..TestOpts::new() }, sample_tests());
+    assert_eq!(first.len() + second.len(), sample_tests().len());
+    assert!(first.iter().any(|t| t.desc.name.as_slice() == "sha1::test"));
+    assert!(second.iter().all(|t| t.desc.name.as_slice() != "sha1::test"));
+    assert!(second.len() > first.len());
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = |extra: &[&str]| {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        args
+    };
+
+    let opts = parse_opts(&args(&["--shard-index=2", "--shard-count=5"])).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(TestShard::new(2, 5)));
+
+    assert!(parse_opts(&args(&["--shard-index=5", "--shard-count=5"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index=0", "--shard-count=0"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-count=2"])).unwrap().is_err());
+
+    let no_unstable = vec!["progname".to_string(), "--shard-index=0".to_string()];
+    assert!(parse_opts(&no_unstable).unwrap().is_err());
+}
+
+fn last_failed(names: &[&str], only: bool) -> Option<LastFailed> {
+    Some(LastFailed { names: names.iter().map(|name| name.to_string()).collect(), only })
+}
+
+#[test]
+fn only_failed_filters_tests() {
+    let opts = TestOpts {
+        last_failed: last_failed(&["sha1::test", "test::sort_tests", "removed::test"], true),
+        ..TestOpts::new()
+    };
+    let filtered = filter_tests(&opts, sample_tests());
+    let names = filtered.iter().map(|test| test.desc.name.to_string()).collect::<Vec<_>>();
+    assert_eq!(names, ["sha1::test", "test::sort_tests"]);
+
+    let opts = TestOpts { last_failed: last_failed(&[], true), ..TestOpts::new() };
+    assert!(filter_tests(&opts, sample_tests()).is_empty());
+}
+
+#[test]
+fn failed_first_runs_failed_tests_first() {
+    let opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(1),
+        last_failed: last_failed(&["test::sort_tests", "isize::test_pow"], false),
+        ..TestOpts::new()
+    };
+
+    let mut order = Vec::new();
+    run_tests(&opts, sample_tests(), |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            order.push(completed_test.desc.name.to_string());
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    // Both groups keep the order in which the tests were passed.
+    let mut expected = vec!["isize::test_pow".to_string(), "test::sort_tests".to_string()];
+    let rest = sample_tests().into_iter().map(|test| test.desc.name.to_string());
+    expected.extend(rest.filter(|name| !expected.contains(name)).collect::<Vec<_>>());
+    assert_eq!(order, expected);
+}
+
+#[test]
+fn failure_cache_round_trip() {
+    use crate::helpers::failure_cache::{CachedOutcome, failed_tests, read_cache, write_cache};
+
+    let dir = env::temp_dir().join(format!("libtest-failure-cache-{}", process::id()));
+    let path = dir.join("tests");
+    assert!(read_cache(&path).unwrap().is_empty());
+
+    let outcomes = [
+        ("a::passes", CachedOutcome::Passed),
+        ("b::fails", CachedOutcome::Failed),
+        ("c::is flaky", CachedOutcome::Flaky),
+    ]
+    .into_iter()
+    .map(|(name, outcome)| (name.to_string(), outcome))
+    .collect();
+    write_cache(&path, &outcomes).unwrap();
+    let read = read_cache(&path).unwrap();
+    assert_eq!(read, outcomes);
+    assert_eq!(failed_tests(&read), ["b::fails".to_string()].into_iter().collect());
+
+    std::fs::write(&path, "passed a::passes\n").unwrap();
+    assert!(read_cache(&path).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failure_cache_write_error_is_not_fatal() {
+    // The cache directory can't be created inside a regular file.
+    let file = env::temp_dir().join(format!("libtest-failure-cache-file-{}", process::id()));
+    std::fs::write(&file, "").unwrap();
+    let opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(1),
+        failure_cache: Some(file.join("cache")),
+        ..TestOpts::new()
+    };
+    assert!(run_tests_console(&opts, sample_tests()).unwrap());
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn parse_failure_cache_options() {
+    let args = |extra: &[&str]| {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        args
+    };
+    let dir = env::temp_dir().join(format!("libtest-failure-cache-options-{}", process::id()));
+    let cache = format!("--failure-cache={}", dir.display());
+
+    let opts = parse_opts(&args(&[&cache])).unwrap().unwrap();
+    assert_eq!(opts.failure_cache, Some(dir.clone()));
+    assert_eq!(opts.last_failed, None);
+
+    // Without a recorded run, no test has failed yet.
+    let opts = parse_opts(&args(&[&cache, "--failed-first"])).unwrap().unwrap();
+    assert_eq!(opts.last_failed, last_failed(&[], false));
+
+    assert!(parse_opts(&args(&[&cache, "--only-failed", "--failed-first"])).unwrap().is_err());
+
+    let no_unstable = vec!["progname".to_string(), "--only-failed".to_string()];
+    assert!(parse_opts(&no_unstable).unwrap().is_err());
+}
+
 #[test]
 pub fn test_metricmap_compare() {
     let mut m1 = MetricMap::new();
@@ -865,6 +1205,7 @@ fn should_sort_failures_before_printing_them() {
         total: 0,
         passed: 0,
         failed: 0,
+        flaky: 0,
         ignored: 0,
         filtered_out: 0,
         measured: 0,
@@ -873,6 +1214,7 @@ fn should_sort_failures_before_printing_them() {
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
         options: Options::new(),
         not_failures: Vec::new(),
+        flakes: Vec::new(),
         ignores: Vec::new(),
         time_failures: Vec::new(),
     };
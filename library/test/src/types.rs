@@ -102,6 +102,17 @@ pub fn padding(&self) -> NamePadding {
         }
     }
 
+    /// Copies the test function so that the test can be run again. Dynamic test
+    /// functions can't be copied, since they may only be called once.
+    pub(crate) fn try_clone(&self) -> Option<TestFn> {
+        match *self {
+            StaticTestFn(f) => Some(StaticTestFn(f)),
+            StaticBenchFn(f) => Some(StaticBenchFn(f)),
+            StaticBenchAsTestFn(f) => Some(StaticBenchAsTestFn(f)),
+            DynTestFn(..) | DynBenchFn(..) | DynBenchAsTestFn(..) => None,
+        }
+    }
+
     pub(crate) fn into_runnable(self) -> Runnable {
         match self {
             StaticTestFn(f) => Runnable::Test(RunnableTest::Static(f)),
@@ -6,7 +6,7 @@
 use std::{fmt, io};
 
 use rustc_data_structures::fx::FxIndexMap;
-use rustc_errors::DiagCtxtHandle;
+use rustc_errors::{DiagCtxt, DiagCtxtHandle};
 use rustc_session::config::{
     self, CodegenOptions, CrateType, ErrorOutputType, Externs, Input, JsonUnusedExterns,
     UnstableOptions, get_cmd_lint_options, nightly_options, parse_crate_types_from_list,
@@ -355,11 +355,14 @@ fn make_input(early_dcx: &EarlyDiagCtxt, input: &str) -> Input {
 impl Options {
     /// Parses the given command-line for options. If an error message or other early-return has
     /// been printed, returns `Err` with the exit code.
+    ///
+    /// The diagnostic context that reported the problems with the options is returned as well,
+    /// for the rest of the run to report to, so that e.g. a SARIF log includes both.
     pub(crate) fn from_matches(
         early_dcx: &mut EarlyDiagCtxt,
         matches: &getopts::Matches,
         args: Vec<String>,
-    ) -> Option<(InputMode, Options, RenderOptions)> {
+    ) -> Option<(InputMode, Options, RenderOptions, DiagCtxt)> {
         // Check for unstable options.
         nightly_options::check_nightly_options(early_dcx, matches, &opts());
 
@@ -392,8 +395,8 @@ pub(crate) fn from_matches(
             }
         };
 
-        let dcx = new_dcx(error_format, None, diagnostic_width, &unstable_opts);
-        let dcx = dcx.handle();
+        let diag_ctxt = new_dcx(error_format, None, diagnostic_width, &unstable_opts);
+        let dcx = diag_ctxt.handle();
 
         // check for deprecated options
         check_deprecated_options(matches, dcx);
@@ -853,7 +856,7 @@ fn println_condition(condition: Condition) {
             include_parts_dir,
             parts_out_dir,
         };
-        Some((input, options, render_options))
+        Some((input, options, render_options, diag_ctxt))
     }
 }
 
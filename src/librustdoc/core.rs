@@ -6,11 +6,13 @@
 use rustc_data_structures::sync::Lrc;
 use rustc_data_structures::unord::UnordSet;
 use rustc_errors::TerminalUrl;
+use rustc_errors::ci::CiAnnotations;
 use rustc_errors::codes::*;
 use rustc_errors::emitter::{
     DynEmitter, HumanEmitter, HumanReadableErrorType, OutputTheme, stderr_destination,
 };
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::sarif::{SarifEmitter, SarifLog};
 use rustc_feature::UnstableFeatures;
 use rustc_hir::def::Res;
 use rustc_hir::def_id::{DefId, DefIdMap, DefIdSet, LocalDefId};
@@ -141,8 +143,8 @@ pub(crate) fn is_json_output(&self) -> bool {
 
 /// Creates a new `DiagCtxt` that can be used to emit warnings and errors.
 ///
-/// If the given `error_format` is `ErrorOutputType::Json` and no `SourceMap` is given, a new one
-/// will be created for the `DiagCtxt`.
+/// If the given `error_format` is `ErrorOutputType::Json` or `ErrorOutputType::Sarif` and no
+/// `SourceMap` is given, a new one will be created for the `DiagCtxt`.
 pub(crate) fn new_dcx(
     error_format: ErrorOutputType,
     source_map: Option<Lrc<source_map::SourceMap>>,
@@ -168,7 +170,11 @@ pub(crate) fn new_dcx(
                     } else {
                         OutputTheme::Ascii
                     })
-                    .ui_testing(unstable_opts.ui_testing),
+                    .ui_testing(unstable_opts.ui_testing)
+                    .ci_annotations(CiAnnotations::for_error_format(
+                        kind,
+                        unstable_opts.gitlab_code_quality_report.clone(),
+                    )),
             )
         }
         ErrorOutputType::Json { pretty, json_rendered, color_config } => {
@@ -190,6 +196,13 @@ pub(crate) fn new_dcx(
                 .terminal_url(TerminalUrl::No),
             )
         }
+        ErrorOutputType::Sarif => {
+            let source_map = source_map.unwrap_or_else(|| {
+                Lrc::new(source_map::SourceMap::new(source_map::FilePathMapping::empty()))
+            });
+            // Shared with the session, so that a run produces a single log.
+            Box::new(SarifEmitter::new(SarifLog::stderr(), source_map, fallback_bundle))
+        }
     };
 
     rustc_errors::DiagCtxt::new(emitter).with_flags(unstable_opts.dcx_flags(true))
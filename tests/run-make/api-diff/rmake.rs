@@ -0,0 +1,76 @@
+// `-Zapi-diff=OLD,NEW` compares the public API of the crates passed as `--extern OLD` and
+// `--extern NEW`, and prints the changes as JSON along with the version bump they require.
+// This test builds two versions of a crate and checks the reported changes.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{rustc, serde_json};
+
+fn main() {
+    for version in ["v1", "v2"] {
+        rustc()
+            .input(format!("{version}.rs"))
+            .crate_name("foo")
+            .crate_type("lib")
+            .emit("metadata")
+            .arg(format!("-Cmetadata={version}"))
+            .arg(format!("-Cextra-filename=-{version}"))
+            .run();
+    }
+
+    let output = rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .arg("-Zapi-diff=old,new")
+        .extern_("old", "libfoo-v1.rmeta")
+        .extern_("new", "libfoo-v2.rmeta")
+        .run()
+        .stdout_utf8();
+
+    let report: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(report["old"]["name"], "foo");
+    assert_eq!(report["new"]["name"], "foo");
+    assert_ne!(report["old"]["hash"], report["new"]["hash"]);
+    assert_eq!(report["required_bump"], "major");
+
+    let changes = report["changes"].as_array().unwrap();
+    let find = |path: &str, description: &str| {
+        changes
+            .iter()
+            .find(|change| {
+                change["path"] == path
+                    && change["description"].as_str().unwrap().contains(description)
+            })
+            .unwrap_or_else(|| panic!("no change to `{path}` matching `{description}`"))
+    };
+
+    let removed = find("removed", "removed from the public API");
+    assert_eq!(removed["change"], "removed");
+    assert_eq!(removed["severity"], "major");
+
+    let added = find("added", "added to the public API");
+    assert_eq!(added["change"], "added");
+    assert_eq!(added["severity"], "minor");
+
+    assert_eq!(find("parse", "signature changed")["severity"], "major");
+    assert_eq!(find("Mode::Auto", "exhaustive enum")["severity"], "major");
+    assert_eq!(find("Parser", "added private field `cache`")["severity"], "major");
+    assert_eq!(find("Parser", "no longer implements `Send`")["severity"], "major");
+    assert_eq!(find("Visit::leave", "with a default")["severity"], "minor");
+    assert!(!changes.iter().any(|change| change["path"] == "Mode::Fast"));
+    // Renaming a lifetime doesn't change the signature.
+    assert!(!changes.iter().any(|change| change["path"] == "first"));
+
+    // Lifetimes are compared apart from the type parameters they come before, so adding one
+    // doesn't change the other parameters or the signatures using them.
+    let source: Vec<_> = changes
+        .iter()
+        .filter(|change| change["path"].as_str().unwrap().starts_with("Source"))
+        .collect();
+    assert_eq!(source.len(), 1, "{source:?}");
+    assert_eq!(source[0]["description"], "added lifetime parameter `'a`");
+    assert_eq!(source[0]["severity"], "major");
+    // Impls which mention private items can't be used by other crates.
+    assert!(!changes.iter().any(
+        |change| change["kind"] == "impl" && change["path"].as_str().unwrap().contains("From")
+    ));
+}
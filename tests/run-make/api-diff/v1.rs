@@ -0,0 +1,26 @@
+pub fn parse(input: &str) -> u32 {
+    input.len() as u32
+}
+
+pub fn removed() {}
+
+pub fn first<'a>(items: &'a [u8]) -> &'a u8 {
+    &items[0]
+}
+
+pub enum Mode {
+    Fast,
+    Slow,
+}
+
+pub struct Parser {
+    pub depth: usize,
+}
+
+pub trait Visit {
+    fn visit(&self);
+}
+
+pub trait Source<T> {
+    fn next(&mut self) -> Option<T>;
+}
@@ -0,0 +1,48 @@
+use std::rc::Rc;
+
+pub fn parse(input: &str) -> u64 {
+    input.len() as u64
+}
+
+pub fn added() {}
+
+pub fn first<'b>(items: &'b [u8]) -> &'b u8 {
+    &items[0]
+}
+
+pub enum Mode {
+    Fast,
+    Slow,
+    Auto,
+}
+
+pub struct Parser {
+    pub depth: usize,
+    cache: Rc<()>,
+}
+
+pub trait Visit {
+    fn visit(&self);
+
+    fn leave(&self) {}
+}
+
+pub trait Source<'a, T> {
+    fn next(&mut self) -> Option<T>;
+}
+
+struct Secret;
+
+trait Hidden {}
+
+impl From<&Secret> for Parser {
+    fn from(_: &Secret) -> Parser {
+        Parser { depth: 0, cache: Rc::new(()) }
+    }
+}
+
+impl From<Box<dyn Hidden>> for Parser {
+    fn from(_: Box<dyn Hidden>) -> Parser {
+        Parser { depth: 0, cache: Rc::new(()) }
+    }
+}
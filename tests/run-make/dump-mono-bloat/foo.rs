@@ -0,0 +1,11 @@
+fn generic<T: Default>() -> T {
+    T::default()
+}
+
+fn helper() -> u8 {
+    generic()
+}
+
+pub fn bar() -> (u8, u64) {
+    (helper(), generic())
+}
@@ -0,0 +1,28 @@
+// `-Zdump-mono-bloat` writes the instantiations of generic functions, grouped by their
+// definition, along with the uses that required them. This test checks that both
+// instantiations of a generic function are listed, with the chain of uses leading to them.
+
+use run_make_support::serde_json::{Value, json};
+use run_make_support::{cwd, rfs, rustc, serde_json};
+
+fn main() {
+    rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .arg(format!("-Zdump-mono-bloat={}", cwd().display()))
+        .run();
+
+    let report: Value = serde_json::from_str(&rfs::read_to_string("foo.mono_bloat.json")).unwrap();
+    let definitions = report.as_array().unwrap();
+    let generic = definitions.iter().find(|d| d["name"] == "generic").unwrap();
+    assert_eq!(generic["instantiation_count"], 2);
+
+    let instances = generic["instances"].as_array().unwrap();
+    let instance = instances.iter().find(|i| i["name"] == "fn generic::<u8>").unwrap();
+    let users: Vec<_> = instance["users"].as_array().unwrap().iter().map(|u| &u["item"]).collect();
+    assert_eq!(users, [&json!("fn helper")]);
+    let chain: Vec<_> = instance["chain"].as_array().unwrap().iter().map(|u| &u["item"]).collect();
+    assert_eq!(chain, [&json!("fn bar"), &json!("fn helper"), &json!("fn generic::<u8>")]);
+    assert!(instance["chain"][0]["span"].is_null());
+    assert!(instance["chain"][2]["span"].as_str().unwrap().starts_with("foo.rs:6:"));
+}
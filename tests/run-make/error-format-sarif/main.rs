@@ -0,0 +1,4 @@
+fn main() {
+    let unused = 1;
+    let x: u8 = 'a';
+}
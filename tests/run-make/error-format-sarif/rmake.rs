@@ -0,0 +1,86 @@
+// `--error-format=sarif` prints all the diagnostics of a compilation as a single SARIF log,
+// including those that are emitted before the session is set up. This test checks that stderr
+// holds exactly one log, with the expected results.
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{rustc, rustdoc};
+
+fn main() {
+    let stderr = rustc()
+        .input("main.rs")
+        .error_format("sarif")
+        .arg("-Zunstable-options")
+        // Emits a warning while the command line is parsed.
+        .arg("-Zremark-dir=remarks")
+        .run_fail()
+        .stderr_utf8();
+
+    // A second log would be rejected as trailing characters.
+    let log: Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let runs = log["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["tool"]["driver"]["name"], "rustc");
+
+    let results = runs[0]["results"].as_array().unwrap();
+    let result = |rule_id: &str| {
+        results
+            .iter()
+            .find(|result| result["ruleId"] == rule_id)
+            .unwrap_or_else(|| panic!("no result for `{rule_id}`"))
+    };
+
+    let early = result("warning");
+    assert!(early["message"]["text"].as_str().unwrap().contains("-Z remark-dir"));
+    assert!(early["locations"].is_null());
+
+    let error = result("E0308");
+    assert_eq!(error["level"], "error");
+    let location = &error["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "main.rs");
+    assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+    assert_eq!(location["region"]["startLine"], 3);
+    assert_eq!(location["region"]["startColumn"], 17);
+
+    let unused = result("unused_variables");
+    assert_eq!(unused["level"], "warning");
+    assert_eq!(unused["locations"][0]["physicalLocation"]["region"]["startLine"], 2);
+
+    // The rules are listed in the order of their first result, and every result refers to its
+    // rule by index.
+    let rules = runs[0]["tool"]["driver"]["rules"].as_array().unwrap();
+    for result in results {
+        let rule = &rules[result["ruleIndex"].as_u64().unwrap() as usize];
+        assert_eq!(rule["id"], result["ruleId"]);
+    }
+    let e0308 = rules.iter().find(|rule| rule["id"] == "E0308").unwrap();
+    assert_eq!(e0308["helpUri"], "https://doc.rust-lang.org/error_codes/E0308.html");
+
+    // A compilation without any diagnostic still prints a log.
+    let stderr =
+        rustc().input("ok.rs").error_format("sarif").arg("-Zunstable-options").run().stderr_utf8();
+    let log: Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(log["runs"][0]["results"], Value::Array(vec![]));
+
+    // rustdoc reports the problems with its options and those of the crate in the same log.
+    let stderr = rustdoc()
+        .input("doc.rs")
+        .arg("--error-format=sarif")
+        .arg("-Zunstable-options")
+        // Emits a warning while the command line is parsed.
+        .arg("--no-defaults")
+        .run()
+        .stderr_utf8();
+    let log: Value = serde_json::from_str(&stderr).unwrap();
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    let message = |rule_id: &str| {
+        results
+            .iter()
+            .find(|result| result["ruleId"] == rule_id)
+            .unwrap_or_else(|| panic!("no result for `{rule_id}`"))["message"]["text"]
+            .as_str()
+            .unwrap()
+    };
+    assert!(message("warning").contains("`no-defaults`"));
+    assert!(message("rustdoc::broken_intra_doc_links").contains("Missing"));
+}
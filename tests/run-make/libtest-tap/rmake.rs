@@ -0,0 +1,37 @@
+// Check libtest's TAP output against snapshots.
+
+//@ ignore-cross-compile
+//@ needs-unwind (test file contains #[should_panic] test)
+
+use run_make_support::{cmd, diff, rustc};
+
+fn main() {
+    rustc().arg("--test").input("f.rs").run();
+
+    run_tests(&[], "output-default.tap");
+    run_tests(&["--show-output"], "output-stdout-success.tap");
+}
+
+#[track_caller]
+fn run_tests(extra_args: &[&str], expected_file: &str) {
+    let cmd_out = cmd("./f")
+        .env("RUST_BACKTRACE", "0")
+        .args(&["-Zunstable-options", "--test-threads=1", "--format=tap"])
+        .args(extra_args)
+        .run_fail();
+    let test_stdout = &cmd_out.stdout_utf8();
+
+    // Verify that the number of test points matches the plan.
+    let mut lines = test_stdout.lines();
+    assert_eq!(lines.next(), Some("TAP version 13"));
+    let plan = lines.next().and_then(|plan| plan.strip_prefix("1..")).expect("missing plan");
+    let test_points =
+        lines.filter(|line| line.starts_with("ok ") || line.starts_with("not ok ")).count();
+    assert_eq!(plan.parse::<usize>().unwrap(), test_points, "plan does not match test points");
+
+    diff()
+        .expected_file(expected_file)
+        .actual_text("stdout", test_stdout)
+        .normalize(r#"finished in [0-9.]+s"#, r#"finished in $$TIME"#)
+        .run();
+}
@@ -0,0 +1,5 @@
+extern crate dep;
+
+pub fn call() {
+    dep::hello();
+}
@@ -0,0 +1,42 @@
+// `--print=crate-graph` lists the crates which were loaded by rustc as JSON, along with
+// the edition and `cfg` options they were built with. This test checks the entry of a
+// dependency which was built with a non-default edition and an extra `cfg`.
+
+use run_make_support::serde_json::{Value, json};
+use run_make_support::{rust_lib_name, rustc, serde_json};
+
+fn main() {
+    rustc().input("dep.rs").crate_type("rlib").edition("2018").cfg(r#"feature="fast""#).run();
+
+    let output = rustc()
+        .input("foo.rs")
+        .crate_type("rlib")
+        .extern_("dep", rust_lib_name("dep"))
+        .emit("dep-info")
+        .print("crate-graph")
+        .arg("-Zunstable-options")
+        .run()
+        .stdout_utf8();
+
+    let crates: Value = serde_json::from_str(&output).unwrap();
+    let crates = crates.as_array().unwrap();
+    assert!(crates.iter().any(|krate| krate["name"] == "std"));
+
+    let dep = crates.iter().find(|krate| krate["name"] == "dep").unwrap();
+    assert_eq!(dep["dep_kind"], "explicit");
+    assert_eq!(dep["edition"], "2018");
+    // Only the `cfg`s passed on the command line are listed.
+    assert_eq!(dep["cfg"], json!([r#"feature="fast""#]));
+    assert_eq!(dep["stable_crate_id"].as_str().unwrap().len(), 16);
+    assert!(!dep["hash"].as_str().unwrap().is_empty());
+    assert!(dep["source"]["rlib"].as_str().unwrap().ends_with(&rust_lib_name("dep")));
+    assert!(dep["source"]["dylib"].is_null());
+
+    // The print option is unstable.
+    rustc()
+        .input("foo.rs")
+        .extern_("dep", rust_lib_name("dep"))
+        .print("crate-graph")
+        .run_fail()
+        .assert_stderr_contains("the `-Z unstable-options` flag must also be passed");
+}
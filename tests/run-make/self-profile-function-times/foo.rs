@@ -0,0 +1,7 @@
+pub fn generic<T: Default>() -> T {
+    T::default()
+}
+
+pub fn caller() -> (u8, u64) {
+    (generic(), generic())
+}
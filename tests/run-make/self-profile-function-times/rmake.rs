@@ -0,0 +1,28 @@
+// `-Zself-profile-events=function-times` attributes compile time to individual functions
+// and writes it to a JSON report next to the self-profile data. This test checks that the
+// report lists the functions of the crate along with the instances of generic functions.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{cwd, has_suffix, rfs, rustc, serde_json, shallow_find_files};
+
+fn main() {
+    rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .arg(format!("-Zself-profile={}", cwd().display()))
+        .arg("-Zself-profile-events=default,function-times")
+        .run();
+
+    let reports = shallow_find_files(cwd(), |path| has_suffix(path, ".function_times.json"));
+    assert_eq!(reports.len(), 1);
+    let report: Value = serde_json::from_str(&rfs::read_to_string(&reports[0])).unwrap();
+
+    let functions = report["functions"].as_array().unwrap();
+    let function = |name: &str| functions.iter().find(|f| f["name"] == name).unwrap();
+    assert_eq!(function("generic")["instances"], 2);
+    assert!(function("caller")["typeck"].as_f64().unwrap() > 0.0);
+
+    let instances = report["instances"].as_array().unwrap();
+    let instance = instances.iter().find(|i| i["name"] == "generic::<u8>").unwrap();
+    assert_eq!(instance["function"], "generic");
+}
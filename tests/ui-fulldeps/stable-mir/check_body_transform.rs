@@ -0,0 +1,148 @@
+//@ run-pass
+//! Test that a body transformation registered with StableMIR is used by the compiler.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::alloc::GlobalAlloc;
+use stable_mir::mir::mono::Instance;
+use stable_mir::mir::{
+    Body, ConstOperand, Mutability, Operand, Place, Rvalue, Statement, StatementKind,
+    TerminatorKind,
+};
+use stable_mir::ty::{ConstantKind, MirConst};
+use stable_mir::{CrateDef, CrateItem, ItemKind};
+use std::convert::TryFrom;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+const NEW_MSG: &str = "new panic message";
+
+/// Check that the compiler uses the transformed body.
+fn test_body_transform() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let dummy = items
+        .iter()
+        .find(|item| item.kind() == ItemKind::Fn && item.name() == "dummy")
+        .unwrap();
+    let body = Instance::try_from(*dummy).unwrap().body().unwrap();
+    assert_eq!(panic_msg(&body), NEW_MSG);
+    ControlFlow::Continue(())
+}
+
+/// Check that the transformation isn't used by a later compiler run.
+fn test_no_body_transform() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let dummy = items
+        .iter()
+        .find(|item| item.kind() == ItemKind::Fn && item.name() == "dummy")
+        .unwrap();
+    let body = Instance::try_from(*dummy).unwrap().body().unwrap();
+    assert_eq!(panic_msg(&body), "oops");
+    ControlFlow::Continue(())
+}
+
+/// Pass the new panic message to `panic_str` through a new local.
+fn change_panic_msg(item: CrateItem, body: &Body) -> Option<Body> {
+    if item.name() != "dummy" {
+        return None;
+    }
+    let mut body = body.clone();
+    let msg = MirConst::from_str(NEW_MSG);
+    let local = body.add_local(msg.ty(), body.span, Mutability::Not);
+    for bb in &mut body.blocks {
+        if let TerminatorKind::Call { args, .. } = &mut bb.terminator.kind {
+            let span = bb.terminator.span;
+            let const_ = ConstOperand { span, user_ty: None, const_: msg.clone() };
+            bb.statements.push(Statement {
+                kind: StatementKind::Assign(
+                    Place::from(local),
+                    Rvalue::Use(Operand::Constant(const_)),
+                ),
+                span,
+            });
+            args[0] = Operand::Move(Place::from(local));
+        }
+    }
+    Some(body)
+}
+
+/// Returns the message passed to `panic_str`.
+fn panic_msg(body: &Body) -> String {
+    body.blocks
+        .iter()
+        .find_map(|bb| match &bb.terminator.kind {
+            TerminatorKind::Call { args, .. } => {
+                let Operand::Move(place) = &args[0] else { unreachable!() };
+                let msg_const = bb
+                    .statements
+                    .iter()
+                    .find_map(|stmt| match &stmt.kind {
+                        StatementKind::Assign(
+                            destination,
+                            Rvalue::Use(Operand::Constant(msg_const)),
+                        ) if destination == place => Some(msg_const),
+                        _ => None,
+                    })
+                    .unwrap();
+                let ConstantKind::Allocated(alloc) = msg_const.const_.kind() else {
+                    unreachable!()
+                };
+                let alloc_prov_id = alloc.provenance.ptrs[0].1.0;
+                let GlobalAlloc::Memory(val) = GlobalAlloc::from(alloc_prov_id) else {
+                    unreachable!()
+                };
+                let bytes = val.raw_bytes().unwrap();
+                Some(std::str::from_utf8(&bytes).unwrap().to_string())
+            }
+            _ => None,
+        })
+        .expect("Failed to find panic message")
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "body_transform_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    rustc_internal::register_body_transform(change_panic_msg);
+    run!(args.clone(), test_body_transform).unwrap();
+    run!(args, test_no_body_transform).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        fn panic_str(msg: &str) {{ panic!("{{}}", msg); }}
+        pub fn dummy() {{
+            panic_str("oops");
+        }}
+        "#
+    )?;
+    Ok(())
+}
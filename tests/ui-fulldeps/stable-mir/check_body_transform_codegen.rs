@@ -0,0 +1,96 @@
+//@ run-pass
+//! Test that the body returned by a StableMIR body transformation is the one that is codegenned.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::{Body, ConstOperand, Operand, Place, RETURN_LOCAL, Rvalue, StatementKind};
+use stable_mir::ty::MirConst;
+use stable_mir::{CrateDef, CrateItem};
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::process::Command;
+
+const CRATE_NAME: &str = "input";
+const NEW_MSG: &str = "new message";
+
+/// Nothing to check before codegen.
+fn test_stable_mir() -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
+
+/// Make `message` return the new message.
+fn change_message(item: CrateItem, body: &Body) -> Option<Body> {
+    if item.name() != "message" {
+        return None;
+    }
+    let mut body = body.clone();
+    for bb in &mut body.blocks {
+        for stmt in &mut bb.statements {
+            let StatementKind::Assign(place, Rvalue::Use(Operand::Constant(const_))) =
+                &mut stmt.kind
+            else {
+                continue;
+            };
+            if *place == Place::from(RETURN_LOCAL) {
+                let span = const_.span;
+                *const_ = ConstOperand { span, user_ty: None, const_: MirConst::from_str(NEW_MSG) };
+            }
+        }
+    }
+    Some(body)
+}
+
+/// This test will generate a dummy binary crate, and compile it with a body transformation
+/// that changes the message the binary prints. Then it will run the binary.
+fn main() {
+    let path = "body_transform_codegen_input.rs";
+    let output = "body_transform_codegen_output";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=bin".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        "-o".to_string(),
+        output.to_string(),
+        path.to_string(),
+    ];
+    rustc_internal::register_body_transform(change_message);
+    run!(args, test_stable_mir).unwrap();
+
+    let result = Command::new(format!("./{output}")).output().unwrap();
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8(result.stdout).unwrap(), NEW_MSG);
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        #[inline(never)]
+        fn message() -> &'static str {{
+            "old message"
+        }}
+
+        fn main() {{
+            print!("{{}}", message());
+        }}
+        "#
+    )?;
+    Ok(())
+}
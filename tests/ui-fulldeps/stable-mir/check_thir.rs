@@ -0,0 +1,162 @@
+//@ run-pass
+//! Test that users are able to inspect the THIR of an item and map it to its MIR.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::thir::{
+    CaptureBorrowKind, CaptureKind, ExprKind, MatchSource, PatKind, Thir, VarId,
+};
+use stable_mir::ty::RigidTy;
+use stable_mir::{CrateDef, CrateItem};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to inspect the THIR of `dummy`.
+fn test_thir() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let dummy = items.iter().find(|item| item.name() == "dummy").unwrap();
+    let thir = dummy.thir().unwrap();
+
+    // The method call is resolved.
+    let callees: Vec<_> = thir
+        .exprs
+        .iter()
+        .filter_map(|expr| match &expr.kind {
+            ExprKind::Call { ty, .. } => match ty.kind().rigid() {
+                Some(RigidTy::FnDef(def, _)) => Some(def.name()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    assert!(callees.iter().any(|name| name.ends_with("Counter::get")), "{callees:?}");
+
+    // The match has one arm per pattern.
+    let arms = thir
+        .exprs
+        .iter()
+        .find_map(|expr| match &expr.kind {
+            ExprKind::Match { arms, match_source: MatchSource::Normal, .. } => Some(arms),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(arms.len(), 3);
+    assert!(arms.iter().all(|arm| thir.arms[*arm].guard.is_none()));
+
+    // The closure borrows `total`.
+    let closure = thir
+        .exprs
+        .iter()
+        .find_map(|expr| match &expr.kind {
+            ExprKind::Closure(closure) => Some(closure),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(closure.upvars.len(), 1);
+    assert_eq!(closure.captures.len(), 1);
+    let capture = &closure.captures[0];
+    assert_eq!(capture.name, "total");
+    assert_eq!(capture.kind, CaptureKind::ByRef(CaptureBorrowKind::Immutable));
+    let total = binding(&thir, "total");
+    assert_eq!(capture.var, total);
+
+    // The closure body refers to the variable of its parent.
+    let closure_thir = CrateItem(closure.def.def_id()).thir().unwrap();
+    assert!(closure_thir.exprs.iter().any(|expr| expr.kind == ExprKind::UpvarRef {
+        closure: closure.def,
+        var: total
+    }));
+
+    // The MIR local of the `counter` argument maps back to the variable, which is used by the
+    // receiver of the method call.
+    let body = dummy.body();
+    let counter = thir.var_bound_at(body.locals()[1].span).unwrap();
+    assert_eq!(counter, binding(&thir, "counter"));
+    assert!(thir.exprs.iter().any(|expr| expr.kind == ExprKind::VarRef { id: counter }));
+    ControlFlow::Continue(())
+}
+
+/// Without `-Zno-steal-thir`, the THIR is gone by the time the analysis is over.
+fn test_dropped_thir() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let dummy = items.iter().find(|item| item.name() == "dummy").unwrap();
+    let error = dummy.thir().unwrap_err();
+    assert!(error.to_string().contains("-Zno-steal-thir"), "{error}");
+    ControlFlow::Continue(())
+}
+
+/// Returns the variable bound with the given name.
+fn binding(thir: &Thir, name: &str) -> VarId {
+    thir.bindings()
+        .into_iter()
+        .find_map(|pat| match &pat.kind {
+            PatKind::Binding { name: binding_name, var, .. } if binding_name == name => Some(*var),
+            _ => None,
+        })
+        .unwrap()
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "thir_input.rs";
+    generate_input(&path).unwrap();
+    let mut args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        "-Zno-steal-thir".to_string(),
+        path.to_string(),
+    ];
+    run!(args.clone(), test_thir).unwrap();
+
+    args.retain(|arg| arg != "-Zno-steal-thir");
+    run!(args, test_dropped_thir).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub struct Counter {{
+            count: u32,
+        }}
+
+        impl Counter {{
+            pub fn get(&self) -> u32 {{
+                self.count
+            }}
+        }}
+
+        pub fn dummy(counter: Counter, flag: Option<bool>) -> u32 {{
+            let total = counter.get();
+            let add = |n: u32| total + n;
+            match flag {{
+                Some(true) => add(1),
+                Some(false) => add(2),
+                None => 0,
+            }}
+        }}
+        "#
+    )?;
+    Ok(())
+}
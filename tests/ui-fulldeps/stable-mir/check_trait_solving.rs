@@ -0,0 +1,138 @@
+//@ run-pass
+//! Test that users are able to check trait implementations, normalize types and check subtyping.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::ty::{
+    GenericArgs, ImplSource, RigidTy, Solution, TraitDef, TraitRef, Ty, TyKind, UintTy,
+};
+use stable_mir::{CrateDef, CrateItem};
+use std::assert_matches::assert_matches;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn test_trait_solving() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let concrete = find_item(&items, "concrete");
+    let generic = find_item(&items, "generic");
+    let shape = find_trait("Shape");
+    let copy = find_trait("std::marker::Copy");
+
+    let square = input_ty(concrete);
+    let param = input_ty(generic);
+    let u32_ty = Ty::unsigned_ty(UintTy::U32);
+
+    // Implemented by an impl item.
+    let Solution::Yes(ImplSource::UserDefined(impl_def)) =
+        trait_ref(shape, square).is_implemented(None)
+    else {
+        panic!("Expected `Square: Shape` to hold");
+    };
+    assert_eq!(impl_def.trait_impl().value.def_id, shape);
+    // Implemented by the compiler.
+    assert_eq!(trait_ref(copy, u32_ty).is_implemented(None), Solution::Yes(ImplSource::Builtin));
+    assert_eq!(trait_ref(copy, square).is_implemented(None), Solution::No);
+    // Implemented because of a where-clause.
+    assert_eq!(
+        trait_ref(shape, param).is_implemented(Some(generic.def_id())),
+        Solution::Yes(ImplSource::Param)
+    );
+    assert_eq!(trait_ref(copy, param).is_implemented(Some(generic.def_id())), Solution::No);
+
+    // The return type of `concrete` is a projection that normalizes to `u32`.
+    let output = output_ty(concrete);
+    assert_matches!(output.kind(), TyKind::Alias(..));
+    assert_eq!(output.normalize(None).unwrap(), u32_ty);
+    assert_eq!(output.is_subtype_of(u32_ty, None), Solution::Yes(()));
+    assert_eq!(u32_ty.is_subtype_of(Ty::bool_ty(), None), Solution::No);
+    ControlFlow::Continue(())
+}
+
+fn find_item(items: &[CrateItem], name: &str) -> CrateItem {
+    *items.iter().find(|item| item.name() == name).unwrap()
+}
+
+fn find_trait(name: &str) -> TraitDef {
+    stable_mir::all_trait_decls().into_iter().find(|def| def.name() == name).unwrap()
+}
+
+fn trait_ref(def: TraitDef, self_ty: Ty) -> TraitRef {
+    TraitRef::new(def, self_ty, &GenericArgs(vec![]))
+}
+
+fn input_ty(item: CrateItem) -> Ty {
+    fn_sig(item).inputs()[0]
+}
+
+fn output_ty(item: CrateItem) -> Ty {
+    fn_sig(item).output()
+}
+
+fn fn_sig(item: CrateItem) -> stable_mir::ty::FnSig {
+    let TyKind::RigidTy(RigidTy::FnDef(def, _)) = item.ty().kind() else { unreachable!() };
+    def.fn_sig().skip_binder()
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "trait_solving_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_trait_solving).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub trait Shape {{
+            type Output;
+            fn area(&self) -> Self::Output;
+        }}
+
+        pub struct Square(u32);
+
+        impl Shape for Square {{
+            type Output = u32;
+            fn area(&self) -> u32 {{
+                self.0 * self.0
+            }}
+        }}
+
+        pub fn concrete(square: Square) -> <Square as Shape>::Output {{
+            square.area()
+        }}
+
+        pub fn generic<T: Shape>(shape: T) -> T::Output {{
+            shape.area()
+        }}
+        "#
+    )?;
+    Ok(())
+}
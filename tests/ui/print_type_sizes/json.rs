@@ -0,0 +1,28 @@
+//@ compile-flags: -Z print-type-sizes -Z print-type-sizes-format=json --crate-type=lib
+//@ compile-flags: -Z print-type-sizes-filter=Padded,Tagged
+//@ build-pass
+
+// This file illustrates the JSON output of `-Z print-type-sizes`, and
+// how `-Z print-type-sizes-filter` restricts it to the matching types.
+//
+// It avoids using u64/i64 because on some targets that is only 4-byte
+// aligned (while on most it is 8-byte aligned) and so the resulting
+// padding and overall computed sizes can be quite different.
+
+#![allow(dead_code)]
+
+#[repr(C)]
+struct Padded {
+    a: u8,
+    b: u32,
+}
+
+// The tag is grown to the alignment of the fields.
+enum Tagged {
+    A(u32),
+    B(u32),
+}
+
+struct Unrelated {
+    a: u8,
+}